@@ -1,14 +1,40 @@
 #![allow(dead_code)]
 use std::fs;
 const UNDO_LIMIT: usize = 1000;
+use crate::extension::backend::chart::render_chart;
+use crate::extension::backend::export::{range_to_html, range_to_markdown};
 use crate::extension::backend::functions::*;
-use crate::extension::backend::graph::Node;
+use crate::extension::backend::graph::add_edges;
+use crate::extension::backend::graph::break_edges;
 use crate::extension::backend::graph::get_sequence;
 use crate::extension::backend::graph::has_cycle;
 use crate::extension::backend::graph::update_edges;
-use crate::extension::common::{Operation, Value};
+use crate::extension::backend::graph::{LineReader, Node};
+use crate::extension::backend::graph::{
+    full_sequence, line_corners, precedents, rebuild_dependents,
+};
+use crate::extension::backend::macros::{
+    Macros, Recording, default_macro_file, is_macro_command, origin, read_macro_file,
+    shift_command, write_macro_file,
+};
+use crate::extension::backend::pixels::{export_image, read_image};
+use crate::extension::backend::plot::{plot, series, sparkline};
+use crate::extension::backend::search::{formula_contains, replace_in_formula};
+use crate::extension::backend::structure::{
+    Axis, in_block, move_function, move_sheet_refs, offset_function, shift_function, shift_range,
+    shift_sheet_refs,
+};
+use crate::extension::common::{
+    Anchor, Anchors, NameValue, Names, NumberFormat, Operation, SortKey, Value, ViewChange,
+    column_decoder, with_names,
+};
+use crate::extension::common::{MAX_COLUMNS, MAX_ROWS};
+use crate::extension::parser::parser::Details;
 use crate::extension::parser::*;
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
 //init_backend(r,c) -> generate a grid of all nodes : returns void
 //execute(value::cell, value::oper) -> update_edges(Node, value::oper), hasCycle(Box<>, value::cell), get_sequence(Box<>, value::cell), update_grid(sequence) -> return status
 //update_grid(sequence) -> loop assign to Node = <functions>(Box<>, value::oper -> return bool
@@ -22,6 +48,9 @@ pub struct Grid {
     rows: usize,
     columns: usize,
     pub cells_vec: Vec<Vec<Node>>,
+    ///Cells whose function reads whole rows or columns; they depend on every cell of them
+    #[serde(default)]
+    pub line_readers: Vec<LineReader>,
 }
 ///Data structure to represent status of command
 #[derive(PartialEq, Debug)]
@@ -39,6 +68,12 @@ pub enum Status {
     Quit,
     Web(String),
     WebStart,
+    UnsavedChanges(String),
+    Opened,
+    SwitchedSheet,
+    View(ViewChange),
+    Report(String),
+    Error(String),
 }
 
 impl Grid {
@@ -48,6 +83,7 @@ impl Grid {
             rows,
             columns,
             cells_vec: vec![vec![Node::new(0); columns]; rows],
+            line_readers: Vec::new(),
         }
     }
     pub fn set_node(&mut self, row: usize, col: usize, node: Node) {
@@ -65,24 +101,243 @@ impl Grid {
     pub fn get_node_value(&self, row: usize, column: usize) -> Option<isize> {
         self.cells_vec[row][column].get_node_value()
     }
+    ///Returns the cells that read `row`, `column`: its dependents plus the
+    ///formulas over whole rows or columns that include it
+    pub fn dependents(&self, row: usize, column: usize) -> Vec<Value> {
+        let mut dependents = self.cells_vec[row][column].dependents.clone();
+        for reader in &self.line_readers {
+            if let Some((start, end)) = line_corners(&reader.start, &reader.end, self)
+                && in_block(&Value::Cell(row, column), &start, &end)
+                && !dependents.contains(&reader.cell)
+            {
+                dependents.push(reader.cell.clone());
+            }
+        }
+        dependents
+    }
+    ///Inserts an empty row or column so that it becomes number `at`
+    fn insert_line(&mut self, axis: Axis, at: usize) {
+        match axis {
+            Axis::Row => {
+                self.cells_vec.insert(at, vec![Node::new(0); self.columns]);
+                self.rows += 1;
+            }
+            Axis::Column => {
+                for row in self.cells_vec.iter_mut() {
+                    row.insert(at, Node::new(0));
+                }
+                self.columns += 1;
+            }
+        }
+    }
+    ///Removes row or column number `at`
+    fn remove_line(&mut self, axis: Axis, at: usize) {
+        match axis {
+            Axis::Row => {
+                self.cells_vec.remove(at);
+                self.rows -= 1;
+            }
+            Axis::Column => {
+                for row in self.cells_vec.iter_mut() {
+                    row.remove(at);
+                }
+                self.columns -= 1;
+            }
+        }
+    }
+    ///Adds or removes rows and columns at the end so that the sheet is `rows` x `columns`
+    fn resize(&mut self, rows: usize, columns: usize) {
+        self.cells_vec.truncate(rows + 1);
+        for row in self.cells_vec.iter_mut() {
+            row.resize(columns + 1, Node::new(0));
+        }
+        self.cells_vec
+            .resize(rows + 1, vec![Node::new(0); columns + 1]);
+        self.rows = rows + 1;
+        self.columns = columns + 1;
+    }
     // pub fn get_node_mut(&mut self, row: usize, column: usize) -> &mut Node {
     //     &mut self.cells_vec[row][column]
     // }
 }
 
+///Computes the function stored at `row`, `col` from the values of the cells it reads
+fn evaluate(grid: &mut Grid, row: usize, col: usize) -> Option<isize> {
+    let Some(Value::Oper(box1, box2, oper)) = grid.get_node(row, col).function.clone() else {
+        return grid.get_node_value(row, col);
+    };
+    if let (Some(start), Some(end)) = (box1, box2)
+        && let Some((start, end)) = line_corners(&start, &end, grid)
+    {
+        let bounded = Value::Oper(Some(Box::new(start)), Some(Box::new(end)), oper);
+        return evaluate_in(grid, bounded);
+    }
+    match oper {
+        Operation::Sum => sum_function(grid, row, col),
+        Operation::Min => min_function(grid, row, col),
+        Operation::Max => max_function(grid, row, col),
+        Operation::Avg => avg_function(grid, row, col),
+        Operation::Std => std_dev_function(grid, row, col),
+        Operation::Add => add(grid, row, col),
+        Operation::Sub => sub(grid, row, col),
+        Operation::Mul => mul(grid, row, col),
+        Operation::Div => div(grid, row, col),
+        Operation::Slp => slp(grid, row, col),
+        Operation::Cons => cons(grid, row, col),
+        // Handle other operations if needed
+        _ => grid.get_node_value(row, col),
+    }
+}
+
+///Computes `func` against the cells of `grid` without storing it in a cell.
+///Row 0 and column 0 are never part of the sheet, so node (0, 0) serves as scratch space.
+fn evaluate_in(grid: &mut Grid, func: Value) -> Option<isize> {
+    let saved = grid.get_node(0, 0).function.replace(func);
+    let value = evaluate(grid, 0, 0);
+    grid.get_node(0, 0).function = saved;
+    value
+}
+
+///Makes the named operands of a formula absolute, so that copies keep reading what the name stands for
+fn named_anchors(mut anchors: Anchors, names: &Names) -> Anchors {
+    let fixed = Anchor {
+        row: true,
+        col: true,
+    };
+    for (anchor, name) in anchors.iter_mut().zip(names) {
+        if name.is_some() {
+            *anchor = fixed;
+        }
+    }
+    anchors
+}
+
+///Points references to sheet `old` at sheet `new`
+fn rename_sheet_refs(value: &Value, old: &str, new: &str) -> Value {
+    match value {
+        Value::SheetRef(sheet, cell) if sheet == old => {
+            Value::SheetRef(new.to_string(), cell.clone())
+        }
+        Value::Oper(box1, box2, oper) => {
+            let rename = |side: &Option<Box<Value>>| {
+                side.as_ref()
+                    .map(|value| Box::new(rename_sheet_refs(value, old, new)))
+            };
+            Value::Oper(rename(box1), rename(box2), oper.clone())
+        }
+        other => other.clone(),
+    }
+}
+
+///Turns references to the deleted sheet `name` into `#REF!`
+fn break_sheet_refs(value: &Value, name: &str) -> Value {
+    match value {
+        Value::SheetRef(sheet, _) if sheet == name => Value::RefError,
+        Value::Oper(box1, box2, oper) => {
+            let rename = |side: &Option<Box<Value>>| {
+                side.as_ref()
+                    .map(|value| Box::new(break_sheet_refs(value, name)))
+            };
+            Value::Oper(rename(box1), rename(box2), oper.clone())
+        }
+        other => other.clone(),
+    }
+}
+
+///Returns true if `cell` lies inside `grid`
+fn fits(grid: &Grid, cell: &Value) -> bool {
+    matches!(cell, Value::Cell(_, _))
+        && cell.row() < grid.get_row_size()
+        && cell.col() < grid.get_column_size()
+}
+
+///Returns the references of a formula as written, keeping ranges whole, e.g. `A1:A10` or `Data!B2`
+fn written_references(func: &Value) -> Vec<String> {
+    let Value::Oper(box1, box2, oper) = func else {
+        return Vec::new();
+    };
+    match (box1.as_deref(), box2.as_deref()) {
+        (Some(Value::SheetRef(sheet, start)), Some(Value::SheetRef(_, end))) if oper.is_range() => {
+            vec![format!("{}!{}:{}", sheet, start, end)]
+        }
+        (Some(start), Some(end)) if oper.is_range() => vec![format!("{}:{}", start, end)],
+        (side1, side2) => [side1, side2]
+            .into_iter()
+            .flatten()
+            .filter(|side| !matches!(side, Value::Const(_)))
+            .map(ToString::to_string)
+            .collect(),
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Valgrid {
     pub rows: usize,
     pub columns: usize,
     pub cells: Vec<Vec<Option<isize>>>,
+    ///True for the cells that hold nothing, whose value reads as 0
+    #[serde(default)]
+    pub blanks: Vec<Vec<bool>>,
+    ///Display format of each cell, set with the `format` command
+    #[serde(default)]
+    pub formats: Vec<Vec<Option<NumberFormat>>>,
+}
+
+///A cell read for pasting: position, function, anchors and names
+type Snapshot = (Value, Option<Value>, Anchors, Names);
+
+///A named sheet of the workbook
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Sheet {
+    pub name: String,
+    ///Empty while the sheet is active, its cells then live in `Backend::grid`
+    pub grid: Grid,
+}
+
+///Everything an undo step restores. The active grid is flattened so that files
+///saved before sheets existed, whose undo steps are bare grids, still load.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct UndoState {
+    #[serde(flatten)]
+    grid: Grid,
+    #[serde(default)]
+    sheets: Vec<Sheet>,
+    #[serde(default)]
+    active: usize,
+    #[serde(default)]
+    names: BTreeMap<String, NameValue>,
 }
 
 ///Struct that contains data structure as well as methods
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Backend {
+    ///Cells of the active sheet
     pub grid: Grid,
-    undo_stack: Vec<Grid>,
-    redo_stack: Vec<Grid>,
+    #[serde(default)]
+    sheets: Vec<Sheet>,
+    ///Index of the active sheet in `sheets`
+    #[serde(default)]
+    active: usize,
+    ///Names given with `define`, shared by every sheet
+    #[serde(default)]
+    names: BTreeMap<String, NameValue>,
+    undo_stack: Vec<UndoState>,
+    redo_stack: Vec<UndoState>,
+    ///Set when the sheet has changed since it was last saved or opened
+    #[serde(skip)]
+    dirty: bool,
+    ///Cells recalculated by the last command, with the index of their sheet
+    #[serde(skip)]
+    recomputed: Vec<(usize, Value)>,
+    ///Macros recorded with `record start`, saved with the sheet
+    #[serde(default)]
+    macros: Macros,
+    ///Macro being recorded, if any
+    #[serde(skip)]
+    recording: Option<Recording>,
+    ///File holding the macros recorded with `record start NAME global`
+    #[serde(skip, default = "default_macro_file")]
+    pub macro_file: Option<PathBuf>,
 }
 
 impl Backend {
@@ -90,10 +345,36 @@ impl Backend {
     pub fn init_backend(rows: usize, columns: usize) -> Self {
         Backend {
             grid: Grid::new(rows + 1, columns + 1),
+            sheets: vec![Sheet {
+                name: "Sheet1".to_string(),
+                grid: Grid::new(0, 0),
+            }],
+            active: 0,
+            names: BTreeMap::new(),
             undo_stack: Vec::new(),
             redo_stack: Vec::new(),
+            dirty: false,
+            recomputed: Vec::new(),
+            macros: Macros::new(),
+            recording: None,
+            macro_file: default_macro_file(),
         }
     }
+    ///Returns true if the sheet has changed since it was last saved or opened
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.dirty
+    }
+    ///Returns the cells of the active sheet recalculated by the last command, in the
+    ///order they were evaluated
+    pub fn recomputed(&self) -> Vec<Value> {
+        let mut cells: Vec<Value> = Vec::new();
+        for (index, cell) in &self.recomputed {
+            if *index == self.active && !cells.contains(cell) {
+                cells.push(cell.clone());
+            }
+        }
+        cells
+    }
     ///Returns the value of cell
     pub fn get_node_value(&self, cell: Value) -> Option<isize> {
         match cell {
@@ -121,184 +402,1292 @@ impl Backend {
                         .collect()
                 })
                 .collect(),
+            blanks: self
+                .grid
+                .cells_vec
+                .iter()
+                .map(|row| row.iter().map(|cell| cell.is_blank()).collect())
+                .collect(),
+            formats: self
+                .grid
+                .cells_vec
+                .iter()
+                .map(|row| row.iter().map(|cell| cell.format.clone()).collect())
+                .collect(),
         }
     }
     ///Iterates over the sequence of topological sort and updates values
     fn update_grid(&mut self, sequence: Vec<Value>) {
-        for cell in sequence {
-            if let Some(Value::Oper(_box1, _box2, oper)) =
-                self.grid.get_node(cell.row(), cell.col()).function.clone()
-            {
-                match oper {
-                    Operation::Sum => {
-                        let sum = sum_function(&mut self.grid, cell.row(), cell.col());
-                        match sum {
-                            Some(val) => {
-                                self.grid.cells_vec[cell.row()][cell.col()].node_value = val;
-                                self.grid.cells_vec[cell.row()][cell.col()].valid = true;
-                            }
-                            None => {
-                                self.grid.cells_vec[cell.row()][cell.col()].valid = false;
-                            }
-                        }
-                    }
-                    Operation::Min => {
-                        let min = min_function(&mut self.grid, cell.row(), cell.col());
-                        match min {
-                            Some(val) => {
-                                self.grid.cells_vec[cell.row()][cell.col()].node_value = val;
-                                self.grid.cells_vec[cell.row()][cell.col()].valid = true;
-                            }
-                            None => {
-                                self.grid.cells_vec[cell.row()][cell.col()].valid = false;
-                            }
-                        }
-                    }
-                    Operation::Max => {
-                        let max = max_function(&mut self.grid, cell.row(), cell.col());
-                        match max {
-                            Some(val) => {
-                                self.grid.cells_vec[cell.row()][cell.col()].node_value = val;
-                                self.grid.cells_vec[cell.row()][cell.col()].valid = true;
-                            }
-                            None => {
-                                self.grid.cells_vec[cell.row()][cell.col()].valid = false;
-                            }
-                        }
-                    }
-                    Operation::Avg => {
-                        let avg = avg_function(&mut self.grid, cell.row(), cell.col());
-                        match avg {
-                            Some(val) => {
-                                self.grid.cells_vec[cell.row()][cell.col()].node_value = val;
-                                self.grid.cells_vec[cell.row()][cell.col()].valid = true;
-                            }
-                            None => {
-                                self.grid.cells_vec[cell.row()][cell.col()].valid = false;
-                            }
-                        }
-                    }
-                    Operation::Std => {
-                        let std_dev = std_dev_function(&mut self.grid, cell.row(), cell.col());
-                        match std_dev {
-                            Some(val) => {
-                                self.grid.cells_vec[cell.row()][cell.col()].node_value = val;
-                                self.grid.cells_vec[cell.row()][cell.col()].valid = true;
-                            }
-                            None => {
-                                self.grid.cells_vec[cell.row()][cell.col()].valid = false;
-                            }
-                        }
-                    }
-                    Operation::Add => {
-                        let ans = add(&mut self.grid, cell.row(), cell.col());
-                        match ans {
-                            Some(val) => {
-                                self.grid.cells_vec[cell.row()][cell.col()].node_value = val;
-                                self.grid.cells_vec[cell.row()][cell.col()].valid = true;
-                            }
-                            None => {
-                                self.grid.cells_vec[cell.row()][cell.col()].valid = false;
-                            }
-                        }
-                    }
-                    Operation::Sub => {
-                        let ans = sub(&mut self.grid, cell.row(), cell.col());
-                        match ans {
-                            Some(val) => {
-                                self.grid.cells_vec[cell.row()][cell.col()].node_value = val;
-                                self.grid.cells_vec[cell.row()][cell.col()].valid = true;
-                            }
-                            None => {
-                                self.grid.cells_vec[cell.row()][cell.col()].valid = false;
-                            }
-                        }
-                    }
-                    Operation::Mul => {
-                        let ans = mul(&mut self.grid, cell.row(), cell.col());
-                        match ans {
-                            Some(val) => {
-                                self.grid.cells_vec[cell.row()][cell.col()].node_value = val;
-                                self.grid.cells_vec[cell.row()][cell.col()].valid = true;
-                            }
-                            None => {
-                                self.grid.cells_vec[cell.row()][cell.col()].valid = false;
-                            }
-                        }
-                    }
-                    Operation::Div => {
-                        let ans = div(&mut self.grid, cell.row(), cell.col());
-                        match ans {
-                            Some(val) => {
-                                self.grid.cells_vec[cell.row()][cell.col()].node_value = val;
-                                self.grid.cells_vec[cell.row()][cell.col()].valid = true;
-                            }
-                            None => {
-                                self.grid.cells_vec[cell.row()][cell.col()].valid = false;
-                            }
-                        }
-                    }
-                    Operation::Slp => {
-                        let ans = slp(&mut self.grid, cell.row(), cell.col());
-                        match ans {
-                            Some(val) => {
-                                self.grid.cells_vec[cell.row()][cell.col()].node_value = val;
-                                self.grid.cells_vec[cell.row()][cell.col()].valid = true;
-                            }
-                            None => {
-                                self.grid.cells_vec[cell.row()][cell.col()].valid = false;
-                            }
-                        }
-                    }
-                    Operation::Cons => {
-                        let ans = cons(&mut self.grid, cell.row(), cell.col());
-                        match ans {
-                            Some(val) => {
-                                self.grid.cells_vec[cell.row()][cell.col()].node_value = val;
-                                self.grid.cells_vec[cell.row()][cell.col()].valid = true;
-                            }
-                            None => {
-                                self.grid.cells_vec[cell.row()][cell.col()].valid = false;
-                            }
-                        }
-                    }
-                    _ => {
-                        // Handle other operations if needed
-                    }
+        for cell in &sequence {
+            let func = self.grid.get_node(cell.row(), cell.col()).function.clone();
+            let Some(func @ Value::Oper(_, _, _)) = func else {
+                continue;
+            };
+            let value = if func.has_ref_error() {
+                // the formula points at a deleted cell
+                None
+            } else if func.has_sheet_ref() {
+                self.evaluate_remote(&func)
+            } else {
+                evaluate(&mut self.grid, cell.row(), cell.col())
+            };
+            let node = self.grid.get_node(cell.row(), cell.col());
+            match value {
+                Some(val) => {
+                    node.node_value = val;
+                    node.valid = true;
+                }
+                None => {
+                    node.valid = false;
                 }
             }
         }
+        // carry the change over to formulas on other sheets
+        let mut remote = Vec::new();
+        for cell in &sequence {
+            for dep in &self.grid.cells_vec[cell.row()][cell.col()].dependents {
+                if let Value::SheetRef(sheet, target) = dep
+                    && let Some(index) = self.sheet_index(sheet)
+                    && !remote.contains(&(index, (**target).clone()))
+                {
+                    remote.push((index, (**target).clone()));
+                }
+            }
+        }
+        for (index, target) in remote {
+            self.recalc(index, target);
+        }
+    }
+
+    ///Computes a formula that reads other sheets
+    fn evaluate_remote(&mut self, func: &Value) -> Option<isize> {
+        let Value::Oper(box1, box2, oper) = func else {
+            return None;
+        };
+        if let (Some(Value::SheetRef(sheet, start)), Some(Value::SheetRef(_, end))) =
+            (box1.as_deref(), box2.as_deref())
+            && oper.is_range()
+        {
+            let index = self.sheet_index(sheet)?;
+            let grid = self.sheet_grid_mut(index);
+            if !fits(grid, end) {
+                return None;
+            }
+            let local = Value::Oper(Some(start.clone()), Some(end.clone()), oper.clone());
+            return evaluate_in(grid, local);
+        }
+        // other sheets' cells are read here and handed over as constants
+        let resolve = |side: &Option<Box<Value>>| -> Option<Option<Box<Value>>> {
+            match side.as_deref() {
+                Some(Value::SheetRef(sheet, cell)) => {
+                    let grid = self.sheet_grid(self.sheet_index(sheet)?);
+                    if !fits(grid, cell) {
+                        return None;
+                    }
+                    let value = grid.get_node_value(cell.row(), cell.col())?;
+                    Some(Some(Box::new(Value::Const(value))))
+                }
+                _ => Some(side.clone()),
+            }
+        };
+        let local = Value::Oper(resolve(box1)?, resolve(box2)?, oper.clone());
+        evaluate_in(&mut self.grid, local)
     }
 
+    ///Recalculates `cell` and everything that depends on it, remembering the cells
+    ///for `recomputed`
+    fn recompute(&mut self, cell: Value) {
+        let sequence = get_sequence(&mut self.grid, cell);
+        let active = self.active;
+        self.recomputed
+            .extend(sequence.iter().map(|cell| (active, cell.clone())));
+        self.update_grid(sequence);
+    }
     ///Checks for cycles and accordingly updates dependencies
     fn execute(&mut self, cell: Value, func: Option<Value>) -> Status {
         //I want that if func has first and second box as value::const type, then just update graph and evaluate expression by sending Operation as well
+        let old_func = self.grid.get_node(cell.row(), cell.col()).function.clone();
         if let Some(Value::Oper(Some(box1), Some(box2), _oper)) = func.clone() {
             if let (Value::Const(_val1), Value::Const(_val2)) = (*box1, *box2) {
                 update_edges(&mut self.grid, cell.clone(), func.clone(), true); //debug check //add break edges
+                self.remote_edges(self.active, &cell, &old_func, false);
                 // change cell's parameters here
                 let node = self.grid.get_node(cell.row(), cell.col());
                 node.function = func.clone();
-                let sequence = get_sequence(&mut self.grid, cell.clone());
-                self.update_grid(sequence.clone());
+                node.names = Names::default();
+                self.recompute(cell.clone());
             } else {
                 update_edges(&mut self.grid, cell.clone(), func.clone(), true);
-                if has_cycle(&mut self.grid, cell.clone()) {
+                self.remote_edges(self.active, &cell, &func, true);
+                let cycle = if self.sheets.len() > 1 {
+                    self.workbook_cycle(&cell)
+                } else {
+                    has_cycle(&mut self.grid, cell.clone())
+                };
+                if cycle {
                     update_edges(&mut self.grid, cell.clone(), func.clone(), false);
+                    self.remote_edges(self.active, &cell, &func, false);
+                    self.remote_edges(self.active, &cell, &old_func, true);
                     return Status::CircularDependency;
                 }
+                self.remote_edges(self.active, &cell, &old_func, false);
+                self.remote_edges(self.active, &cell, &func, true);
                 // change cell's parameters here
                 let node = self.grid.get_node(cell.row(), cell.col());
                 node.function = func.clone();
-                let sequence = get_sequence(&mut self.grid, cell.clone());
-                self.update_grid(sequence.clone());
+                node.names = Names::default();
+                self.recompute(cell.clone());
+            }
+        }
+        Status::Success
+    }
+    ///Saves the current grid as an undo step before a change
+    fn checkpoint(&mut self) {
+        let state = self.undo_state();
+        self.undo_stack.push(state);
+        if self.undo_stack.len() > UNDO_LIMIT {
+            self.undo_stack.remove(0);
+        }
+        self.redo_stack.clear(); // clear redo stack on new action
+        self.dirty = true;
+    }
+    ///Restores the grid saved by the last `checkpoint`, undoing a batch that failed halfway
+    fn rollback(&mut self) {
+        if let Some(state) = self.undo_stack.pop() {
+            self.restore(state);
+        }
+    }
+    ///Copies the whole workbook for the undo stacks
+    fn undo_state(&self) -> UndoState {
+        UndoState {
+            grid: self.grid.clone(),
+            sheets: self.sheets.clone(),
+            active: self.active,
+            names: self.names.clone(),
+        }
+    }
+    ///Puts back a workbook saved by `undo_state`
+    fn restore(&mut self, state: UndoState) {
+        self.grid = state.grid;
+        // undo steps from older files only know the active grid
+        if !state.sheets.is_empty() {
+            self.sheets = state.sheets;
+            self.active = state.active;
+            self.names = state.names;
+        }
+    }
+    ///Returns the position of the sheet called `name`
+    fn sheet_index(&self, name: &str) -> Option<usize> {
+        self.sheets.iter().position(|sheet| sheet.name == name)
+    }
+    ///Returns the cells of sheet `index`, wherever they are kept
+    fn sheet_grid(&self, index: usize) -> &Grid {
+        if index == self.active {
+            &self.grid
+        } else {
+            &self.sheets[index].grid
+        }
+    }
+    fn sheet_grid_mut(&mut self, index: usize) -> &mut Grid {
+        if index == self.active {
+            &mut self.grid
+        } else {
+            &mut self.sheets[index].grid
+        }
+    }
+    ///Returns the name of the active sheet
+    pub fn sheet_name(&self) -> &str {
+        &self.sheets[self.active].name
+    }
+    ///Returns the names of all sheets in order
+    pub fn sheet_names(&self) -> Vec<String> {
+        self.sheets.iter().map(|sheet| sheet.name.clone()).collect()
+    }
+    ///Makes sheet `index` the active one by swapping its cells into `grid`
+    fn activate(&mut self, index: usize) {
+        let current = self.active;
+        std::mem::swap(&mut self.grid, &mut self.sheets[current].grid);
+        std::mem::swap(&mut self.grid, &mut self.sheets[index].grid);
+        self.active = index;
+    }
+    ///Recalculates `cell` of sheet `index` and everything that depends on it
+    fn recalc(&mut self, index: usize, cell: Value) {
+        let current = self.active;
+        self.activate(index);
+        self.recompute(cell);
+        self.activate(current);
+    }
+    ///Adds (`add`) or removes the edges from the cells on other sheets read by `func`
+    ///to `target` on sheet `index`. Edges within a sheet are kept by `graph`.
+    fn remote_edges(&mut self, index: usize, target: &Value, func: &Option<Value>, add: bool) {
+        let Some(Value::Oper(box1, box2, oper)) = func else {
+            return;
+        };
+        let mut cells = Vec::new();
+        match (box1.as_deref(), box2.as_deref()) {
+            (Some(Value::SheetRef(sheet, start)), Some(Value::SheetRef(_, end)))
+                if oper.is_range() =>
+            {
+                for i in start.row()..=end.row() {
+                    for j in start.col()..=end.col() {
+                        cells.push((sheet.clone(), Value::Cell(i, j)));
+                    }
+                }
+            }
+            (side1, side2) => {
+                for side in [side1, side2].into_iter().flatten() {
+                    if let Value::SheetRef(sheet, cell) = side {
+                        cells.push((sheet.clone(), (**cell).clone()));
+                    }
+                }
+            }
+        }
+        let dependent = Value::SheetRef(self.sheets[index].name.clone(), Box::new(target.clone()));
+        for (sheet, cell) in cells {
+            let Some(other) = self.sheet_index(&sheet) else {
+                continue;
+            };
+            let grid = self.sheet_grid_mut(other);
+            if !fits(grid, &cell) {
+                continue;
+            }
+            let node = grid.get_node(cell.row(), cell.col());
+            if add {
+                node.add_dep(dependent.clone());
+            } else {
+                node.remove_dep(dependent.clone());
+            }
+        }
+    }
+    ///Rebuilds every edge between sheets from the stored functions
+    fn relink_sheets(&mut self) {
+        let mut formulas = Vec::new();
+        for index in 0..self.sheets.len() {
+            let grid = self.sheet_grid_mut(index);
+            for (i, row) in grid.cells_vec.iter_mut().enumerate() {
+                for (j, node) in row.iter_mut().enumerate() {
+                    node.dependents
+                        .retain(|dep| !matches!(dep, Value::SheetRef(_, _)));
+                    if node.function.as_ref().is_some_and(Value::has_sheet_ref) {
+                        formulas.push((index, Value::Cell(i, j), node.function.clone()));
+                    }
+                }
+            }
+        }
+        for (index, cell, func) in formulas {
+            self.remote_edges(index, &cell, &func, true);
+        }
+    }
+    ///Returns true if `cell` of the active sheet depends on itself, following
+    ///references across sheets
+    fn workbook_cycle(&self, cell: &Value) -> bool {
+        let mut visited = HashSet::new();
+        let mut stack = vec![(self.active, cell.clone())];
+        while let Some((index, current)) = stack.pop() {
+            let grid = self.sheet_grid(index);
+            for dep in &grid.dependents(current.row(), current.col()) {
+                let next = match dep {
+                    Value::Cell(_, _) => (index, dep.clone()),
+                    Value::SheetRef(sheet, target) => match self.sheet_index(sheet) {
+                        Some(other) => (other, (**target).clone()),
+                        None => continue,
+                    },
+                    _ => continue,
+                };
+                if next.0 == self.active && next.1 == *cell {
+                    return true;
+                }
+                if visited.insert((next.0, next.1.row(), next.1.col())) {
+                    stack.push(next);
+                }
+            }
+        }
+        false
+    }
+    ///Checks the references to other sheets in a parsed formula. References naming the
+    ///active sheet become plain cell references.
+    fn resolve_sheet_refs(&self, func: Value) -> Result<Value, String> {
+        match func {
+            Value::SheetRef(sheet, cell) => {
+                let index = self
+                    .sheet_index(&sheet)
+                    .ok_or(format!("Unknown sheet {}", sheet))?;
+                if !fits(self.sheet_grid(index), &cell) {
+                    return Err(format!("{}!{} is outside sheet {}", sheet, cell, sheet));
+                }
+                if index == self.active {
+                    Ok(*cell)
+                } else {
+                    Ok(Value::SheetRef(sheet, cell))
+                }
+            }
+            Value::Oper(box1, box2, oper) => {
+                let resolve = |side: Option<Box<Value>>| -> Result<Option<Box<Value>>, String> {
+                    side.map(|value| self.resolve_sheet_refs(*value).map(Box::new))
+                        .transpose()
+                };
+                Ok(Value::Oper(resolve(box1)?, resolve(box2)?, oper))
+            }
+            other => Ok(other),
+        }
+    }
+    ///Replaces the names in a parsed formula by what they stand for, returning the
+    ///formula together with the names it was written with
+    fn expand_names(&self, func: Value) -> Result<(Value, Names), String> {
+        let mut names = Names::default();
+        let Value::Oper(box1, box2, oper) = func else {
+            return Ok((func, names));
+        };
+        let lookup = |name: &String| self.names.get(name).ok_or(format!("Unknown name {}", name));
+        // corners of a range on another sheet are turned back into plain cells later
+        let corner = |sheet: &String, cell: &Value| match cell {
+            Value::RefError => Value::RefError,
+            cell => Value::SheetRef(sheet.clone(), Box::new(cell.clone())),
+        };
+        if oper.is_range() {
+            if let Some(Value::Name(name)) = box1.as_deref() {
+                let NameValue::Range(sheet, start, end) = lookup(name)? else {
+                    return Err(format!("{} is not a range", name));
+                };
+                names = [Some(name.clone()), Some(name.clone())];
+                let (start, end) = (corner(sheet, start), corner(sheet, end));
+                return Ok((
+                    Value::Oper(Some(Box::new(start)), Some(Box::new(end)), oper),
+                    names,
+                ));
+            }
+            return Ok((Value::Oper(box1, box2, oper), names));
+        }
+        let mut sides = [box1, box2];
+        for (side, slot) in sides.iter_mut().zip(names.iter_mut()) {
+            if let Some(Value::Name(name)) = side.as_deref() {
+                let value = match lookup(name)? {
+                    NameValue::Const(val) => Value::Const(*val),
+                    NameValue::Range(sheet, start, end) if start == end => corner(sheet, start),
+                    NameValue::Range(_, _, _) => {
+                        return Err(format!("{} is a range, not a single value", name));
+                    }
+                };
+                *slot = Some(name.clone());
+                *side = Some(Box::new(value));
+            }
+        }
+        let [box1, box2] = sides;
+        Ok((Value::Oper(box1, box2, oper), names))
+    }
+    ///Gives `name` a new meaning as a single undo step and re-evaluates every
+    ///formula written with it
+    pub fn define(&mut self, name: &str, value: NameValue) -> Status {
+        if let NameValue::Range(sheet, _, end) = &value {
+            let Some(index) = self.sheet_index(sheet) else {
+                return Status::Error(format!("Unknown sheet {}", sheet));
+            };
+            if !fits(self.sheet_grid(index), end) {
+                return Status::Error(format!("{}!{} is outside sheet {}", sheet, end, sheet));
+            }
+        }
+        self.checkpoint();
+        self.names.insert(name.to_string(), value);
+        let name = Some(name.to_string());
+        let current = self.active;
+        for index in 0..self.sheets.len() {
+            let mut users = Vec::new();
+            for (i, row) in self.sheet_grid(index).cells_vec.iter().enumerate() {
+                for (j, node) in row.iter().enumerate() {
+                    if let Some(func) = &node.function
+                        && node.names.contains(&name)
+                    {
+                        users.push((
+                            Value::Cell(i, j),
+                            with_names(func, &node.names),
+                            node.anchors,
+                        ));
+                    }
+                }
+            }
+            if users.is_empty() {
+                continue;
+            }
+            self.activate(index);
+            for (cell, func, anchors) in users {
+                let expanded = self
+                    .expand_names(func)
+                    .and_then(|(func, names)| Ok((self.resolve_sheet_refs(func)?, names)));
+                let status = match expanded {
+                    Ok((func, names)) => {
+                        let status = self.execute(cell.clone(), Some(func));
+                        let node = self.grid.get_node(cell.row(), cell.col());
+                        node.anchors = anchors;
+                        node.names = names;
+                        status
+                    }
+                    Err(message) => Status::Error(format!("{} in {}", message, cell)),
+                };
+                if status != Status::Success {
+                    self.rollback();
+                    return status;
+                }
+            }
+            self.activate(current);
+        }
+        Status::Success
+    }
+    ///Adds an empty sheet after the existing ones
+    pub fn add_sheet(&mut self, name: &str) -> Status {
+        if self.sheet_index(name).is_some() {
+            return Status::Error(format!("Sheet {} already exists", name));
+        }
+        let rows = self.grid.get_row_size();
+        let columns = self.grid.get_column_size();
+        self.checkpoint();
+        self.sheets.push(Sheet {
+            name: name.to_string(),
+            grid: Grid::new(rows, columns),
+        });
+        Status::Success
+    }
+    ///Renames a sheet and every reference to it
+    pub fn rename_sheet(&mut self, old: &str, new: &str) -> Status {
+        let Some(index) = self.sheet_index(old) else {
+            return Status::Error(format!("Unknown sheet {}", old));
+        };
+        if self.sheet_index(new).is_some() {
+            return Status::Error(format!("Sheet {} already exists", new));
+        }
+        self.checkpoint();
+        self.sheets[index].name = new.to_string();
+        for value in self.names.values_mut() {
+            if let NameValue::Range(sheet, _, _) = value
+                && sheet == old
+            {
+                *sheet = new.to_string();
+            }
+        }
+        for other in 0..self.sheets.len() {
+            for row in self.sheet_grid_mut(other).cells_vec.iter_mut() {
+                for node in row.iter_mut() {
+                    if let Some(func) = &node.function {
+                        node.function = Some(rename_sheet_refs(func, old, new));
+                    }
+                    for dep in node.dependents.iter_mut() {
+                        *dep = rename_sheet_refs(dep, old, new);
+                    }
+                }
+            }
+        }
+        Status::Success
+    }
+    ///Deletes a sheet; formulas that read it show `#REF!`
+    pub fn delete_sheet(&mut self, name: &str) -> Status {
+        let Some(index) = self.sheet_index(name) else {
+            return Status::Error(format!("Unknown sheet {}", name));
+        };
+        if self.sheets.len() == 1 {
+            return Status::Error("Cannot delete the only sheet".to_string());
+        }
+        self.checkpoint();
+        let deleted = index == self.active;
+        if deleted {
+            self.activate(if index == 0 { 1 } else { index - 1 });
+        }
+        self.sheets.remove(index);
+        for value in self.names.values_mut() {
+            if let NameValue::Range(sheet, start, end) = value
+                && sheet == name
+            {
+                (**start, **end) = (Value::RefError, Value::RefError);
+            }
+        }
+        if self.active > index {
+            self.active -= 1;
+        }
+        let mut broken = Vec::new();
+        for other in 0..self.sheets.len() {
+            let grid = self.sheet_grid_mut(other);
+            for (i, row) in grid.cells_vec.iter_mut().enumerate() {
+                for (j, node) in row.iter_mut().enumerate() {
+                    if let Some(func) = &node.function {
+                        let func = break_sheet_refs(func, name);
+                        if func.has_ref_error() {
+                            broken.push((other, Value::Cell(i, j)));
+                        }
+                        node.function = Some(func);
+                    }
+                }
+            }
+        }
+        self.relink_sheets();
+        for (other, cell) in broken {
+            self.recalc(other, cell);
+        }
+        if deleted {
+            Status::SwitchedSheet
+        } else {
+            Status::Success
+        }
+    }
+    ///Makes the sheet called `name` the active one
+    pub fn switch_sheet(&mut self, name: &str) -> Status {
+        match self.sheet_index(name) {
+            Some(index) => {
+                self.activate(index);
+                Status::SwitchedSheet
+            }
+            None => Status::Error(format!("Unknown sheet {}", name)),
+        }
+    }
+    ///Returns an error if a `width`x`height` block placed at `origin` would leave the sheet
+    fn check_fits(&self, origin: &Value, width: usize, height: usize) -> Result<(), Status> {
+        if origin.row() + height > self.grid.get_row_size()
+            || origin.col() + width > self.grid.get_column_size()
+        {
+            return Err(Status::Error(format!(
+                "{}x{} block does not fit in the sheet at {}{}",
+                width,
+                height,
+                column_decoder(origin.col()),
+                origin.row()
+            )));
+        }
+        Ok(())
+    }
+    ///Assigns constants to a block of cells starting at `origin` as a single undo step
+    pub fn set_constants(&mut self, origin: &Value, values: &[Vec<isize>]) -> Status {
+        let height = values.len();
+        let width = values.iter().map(|row| row.len()).max().unwrap_or(0);
+        if let Err(status) = self.check_fits(origin, width, height) {
+            return status;
+        }
+        self.checkpoint();
+        for (i, row) in values.iter().enumerate() {
+            for (j, value) in row.iter().enumerate() {
+                let cell = Value::Cell(origin.row() + i, origin.col() + j);
+                let func = Value::Oper(
+                    Some(Box::new(Value::Const(*value))),
+                    Some(Box::new(Value::Const(0))),
+                    Operation::Cons,
+                );
+                self.execute(cell, Some(func));
+            }
+        }
+        Status::Success
+    }
+    ///Empties a cell: its function and edges are removed and its dependents recalculated
+    fn clear_cell(&mut self, cell: Value) {
+        break_edges(&mut self.grid, cell.clone(), None, true);
+        let old_func = self.grid.get_node(cell.row(), cell.col()).function.clone();
+        self.remote_edges(self.active, &cell, &old_func, false);
+        let node = self.grid.get_node(cell.row(), cell.col());
+        node.function = None;
+        node.anchors = Anchors::default();
+        node.names = Names::default();
+        node.node_value = 0;
+        node.valid = true;
+        self.recompute(cell);
+    }
+    ///Sets the display format of every cell of `start:end`, or removes it for `None`,
+    ///as a single undo step. Values are unchanged, and clearing a cell keeps its format.
+    pub fn set_format(
+        &mut self,
+        start: &Value,
+        end: &Value,
+        format: Option<NumberFormat>,
+    ) -> Status {
+        self.checkpoint();
+        for i in start.row()..=end.row() {
+            for j in start.col()..=end.col() {
+                self.grid.get_node(i, j).format = format.clone();
+            }
+        }
+        Status::Success
+    }
+    ///Empties every cell of `start:end` as a single undo step and recalculates the
+    ///cells that read them
+    pub fn clear_range(&mut self, start: &Value, end: &Value) -> Status {
+        let cells: Vec<Value> = self
+            .snapshot(start, end)
+            .into_iter()
+            .filter(|(_, func, _, _)| func.is_some())
+            .map(|(cell, _, _, _)| cell)
+            .collect();
+        if cells.is_empty() {
+            return Status::Success;
+        }
+        self.checkpoint();
+        for cell in cells {
+            self.clear_cell(cell);
+        }
+        Status::Success
+    }
+    ///Reads the functions of the cells of `start:end`, with their positions, for pasting
+    fn snapshot(&self, start: &Value, end: &Value) -> Vec<Snapshot> {
+        let mut cells = Vec::new();
+        for i in start.row()..=end.row() {
+            for j in start.col()..=end.col() {
+                let node = &self.grid.cells_vec[i][j];
+                cells.push((
+                    Value::Cell(i, j),
+                    node.function.clone(),
+                    node.anchors,
+                    node.names.clone(),
+                ));
+            }
+        }
+        cells
+    }
+    ///Writes snapshotted cells back `offset` (rows, columns) away from where they were read,
+    ///shifting relative references by the same offset. Not an undo step of its own.
+    fn paste_cells(&mut self, cells: Vec<Snapshot>, offset: (isize, isize)) -> Status {
+        let size = (
+            self.grid.get_row_size() - 1,
+            self.grid.get_column_size() - 1,
+        );
+        for (cell, func, anchors, names) in cells {
+            let cell = Value::Cell(
+                (cell.row() as isize + offset.0) as usize,
+                (cell.col() as isize + offset.1) as usize,
+            );
+            match func {
+                Some(func) => {
+                    let func = offset_function(&func, &anchors, offset, size);
+                    let status = self.execute(cell.clone(), Some(func));
+                    if status != Status::Success {
+                        return status;
+                    }
+                    let node = self.grid.get_node(cell.row(), cell.col());
+                    node.anchors = anchors;
+                    node.names = names;
+                }
+                None => self.clear_cell(cell),
             }
         }
         Status::Success
     }
+    ///Copies the cells of `start:end` so that `start` lands on `target`, shifting relative
+    ///references by the same offset. Not an undo step of its own.
+    fn paste_block(&mut self, start: &Value, end: &Value, target: &Value) -> Status {
+        let offset = (
+            target.row() as isize - start.row() as isize,
+            target.col() as isize - start.col() as isize,
+        );
+        // read every source first, the target block may overlap it
+        let cells = self.snapshot(start, end);
+        self.paste_cells(cells, offset)
+    }
+    ///Copies `start:end` to the block whose top-left cell is `target` as a single undo step
+    pub fn copy_range(&mut self, start: &Value, end: &Value, target: &Value) -> Status {
+        let width = end.col() - start.col() + 1;
+        let height = end.row() - start.row() + 1;
+        if let Err(status) = self.check_fits(target, width, height) {
+            return status;
+        }
+        self.checkpoint();
+        let status = self.paste_block(start, end, target);
+        if status != Status::Success {
+            self.rollback();
+        }
+        status
+    }
+    ///Moves the cells of `start:end` so that `start` lands on `target`, as a single undo step.
+    ///Formulas reading the moved cells, on this sheet or another, are rewritten to follow them,
+    ///and formulas reading cells that get overwritten show `#REF!`. Only the edges of the cells
+    ///involved are updated.
+    pub fn move_range(&mut self, start: &Value, end: &Value, target: &Value) -> Status {
+        let width = end.col() - start.col() + 1;
+        let height = end.row() - start.row() + 1;
+        if let Err(status) = self.check_fits(target, width, height) {
+            return status;
+        }
+        let offset = (
+            target.row() as isize - start.row() as isize,
+            target.col() as isize - start.col() as isize,
+        );
+        let target_end = Value::Cell(target.row() + height - 1, target.col() + width - 1);
+        let cells = |from: &Value, to: &Value| -> Vec<Value> {
+            (from.row()..=to.row())
+                .flat_map(|i| (from.col()..=to.col()).map(move |j| Value::Cell(i, j)))
+                .collect()
+        };
+        let sources = cells(start, end);
+        let targets = cells(target, &target_end);
+        let mut area = sources.clone();
+        area.extend(
+            targets
+                .iter()
+                .filter(|cell| !in_block(cell, start, end))
+                .cloned(),
+        );
+        self.checkpoint();
+        // names of ranges inside the block follow it
+        let sheet = self.sheet_name().to_string();
+        for value in self.names.values_mut() {
+            if let NameValue::Range(name, first, last) = value
+                && *name == sheet
+                && in_block(first, start, end)
+                && in_block(last, start, end)
+            {
+                for corner in [first, last] {
+                    **corner = Value::Cell(
+                        (corner.row() as isize + offset.0) as usize,
+                        (corner.col() as isize + offset.1) as usize,
+                    );
+                }
+            }
+        }
+
+        // formulas outside the area that read one of its cells
+        let mut seen = HashSet::new();
+        let mut readers = Vec::new();
+        for cell in &area {
+            for dep in &self.grid.dependents(cell.row(), cell.col()) {
+                if matches!(dep, Value::Cell(_, _))
+                    && !in_block(dep, start, end)
+                    && !in_block(dep, target, &target_end)
+                    && seen.insert((dep.row(), dep.col()))
+                {
+                    readers.push(dep.clone());
+                }
+            }
+        }
+        for cell in area.iter().chain(readers.iter()) {
+            break_edges(&mut self.grid, cell.clone(), None, true);
+        }
+
+        let moved: Vec<Node> = sources
+            .iter()
+            .map(|cell| self.grid.cells_vec[cell.row()][cell.col()].clone())
+            .collect();
+        for cell in &area {
+            self.grid.set_node(cell.row(), cell.col(), Node::new(0));
+        }
+        for (cell, mut node) in targets.iter().zip(moved) {
+            node.dependents.clear();
+            node.function = node
+                .function
+                .map(|func| move_function(&func, (start, end), offset));
+            self.grid.set_node(cell.row(), cell.col(), node);
+        }
+        for cell in &readers {
+            let node = self.grid.get_node(cell.row(), cell.col());
+            node.function = node
+                .function
+                .as_ref()
+                .map(|func| move_function(func, (start, end), offset));
+        }
+
+        let changed: Vec<Value> = targets.into_iter().chain(readers).collect();
+        for cell in &changed {
+            let func = self.grid.get_node(cell.row(), cell.col()).function.clone();
+            add_edges(&mut self.grid, cell.clone(), func, true);
+        }
+        for cell in &changed {
+            if self
+                .grid
+                .get_node(cell.row(), cell.col())
+                .function
+                .is_some()
+                && has_cycle(&mut self.grid, cell.clone())
+            {
+                self.rollback();
+                return Status::CircularDependency;
+            }
+        }
+        // formulas on other sheets that read the block follow it too
+        let mut remote_readers = Vec::new();
+        if self.sheets.len() > 1 {
+            for index in (0..self.sheets.len()).filter(|&index| index != self.active) {
+                let grid = &mut self.sheets[index].grid;
+                for (i, row) in grid.cells_vec.iter_mut().enumerate() {
+                    for (j, node) in row.iter_mut().enumerate() {
+                        if let Some(func) = &node.function {
+                            let moved = move_sheet_refs(func, &sheet, (start, end), offset);
+                            if moved != *func {
+                                node.function = Some(moved);
+                                remote_readers.push((index, Value::Cell(i, j)));
+                            }
+                        }
+                    }
+                }
+            }
+            self.relink_sheets();
+        }
+        for cell in changed {
+            self.recompute(cell);
+        }
+        for (index, cell) in remote_readers {
+            self.recalc(index, cell);
+        }
+        Status::Success
+    }
+    ///Reorders the rows of `start:end` by the values in the key columns, as a single undo step.
+    ///The sort is stable and cells holding errors go last whatever the direction. Formulas move
+    ///with their row and have their relative references shifted like a copy.
+    pub fn sort_range(&mut self, start: &Value, end: &Value, keys: &[SortKey]) -> Status {
+        let rows: Vec<usize> = (start.row()..=end.row()).collect();
+        let mut order = rows.clone();
+        order.sort_by(|&a, &b| {
+            keys.iter()
+                .map(|key| {
+                    match (
+                        self.grid.get_node_value(a, key.col),
+                        self.grid.get_node_value(b, key.col),
+                    ) {
+                        (Some(x), Some(y)) if key.descending => y.cmp(&x),
+                        (Some(x), Some(y)) => x.cmp(&y),
+                        (Some(_), None) => Ordering::Less,
+                        (None, Some(_)) => Ordering::Greater,
+                        (None, None) => Ordering::Equal,
+                    }
+                })
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal)
+        });
+        let lines: Vec<_> = order
+            .iter()
+            .map(|&row| self.snapshot(&Value::Cell(row, start.col()), &Value::Cell(row, end.col())))
+            .collect();
+        self.checkpoint();
+        // empty the range first so that half-sorted rows never look like a cycle
+        for cell in self.snapshot(start, end) {
+            self.clear_cell(cell.0);
+        }
+        for ((target, source), line) in rows.into_iter().zip(order).zip(lines) {
+            let status = self.paste_cells(line, (target as isize - source as isize, 0));
+            if status != Status::Success {
+                self.rollback();
+                return status;
+            }
+        }
+        Status::Success
+    }
+    ///Copies the first row of `start:end` into the rows below it (`down`), or the
+    ///first column into the columns to its right, as a single undo step
+    pub fn fill(&mut self, start: &Value, end: &Value, down: bool) -> Status {
+        self.checkpoint();
+        let (source_end, targets) = if down {
+            let targets: Vec<Value> = (start.row() + 1..=end.row())
+                .map(|row| Value::Cell(row, start.col()))
+                .collect();
+            (Value::Cell(start.row(), end.col()), targets)
+        } else {
+            let targets: Vec<Value> = (start.col() + 1..=end.col())
+                .map(|col| Value::Cell(start.row(), col))
+                .collect();
+            (Value::Cell(end.row(), start.col()), targets)
+        };
+        for target in targets {
+            let status = self.paste_block(start, &source_end, &target);
+            if status != Status::Success {
+                self.rollback();
+                return status;
+            }
+        }
+        Status::Success
+    }
+    ///Inserts (`insert`) or deletes row/column `at` as a single undo step.
+    ///Formulas are rewritten to follow the cells that move, and references to
+    ///deleted cells become `#REF!` errors.
+    pub fn restructure(&mut self, axis: Axis, at: usize, insert: bool) -> Status {
+        let (size, limit, name) = match axis {
+            Axis::Row => (self.grid.get_row_size() - 1, MAX_ROWS, "rows"),
+            Axis::Column => (self.grid.get_column_size() - 1, MAX_COLUMNS, "columns"),
+        };
+        if insert && size >= limit {
+            return Status::Error(format!(
+                "Sheet already has the maximum of {} {}",
+                limit, name
+            ));
+        }
+        if !insert && size <= 1 {
+            return Status::Error(format!(
+                "Cannot delete the only remaining {}",
+                &name[..name.len() - 1]
+            ));
+        }
+        let last = if insert { size + 1 } else { size };
+        if at == 0 || at > last {
+            return Status::UnrecognizedCmd;
+        }
+        self.checkpoint();
+        for row in self.grid.cells_vec.iter_mut() {
+            for node in row.iter_mut() {
+                if let Some(func) = &node.function {
+                    node.function = Some(shift_function(func, axis, at, insert));
+                }
+            }
+        }
+        let sheet = self.sheet_name().to_string();
+        for value in self.names.values_mut() {
+            if let NameValue::Range(name, start, end) = value
+                && *name == sheet
+            {
+                let (first, last) = shift_range(start, end, axis, at, insert);
+                (**start, **end) = (first, last);
+            }
+        }
+        if insert {
+            self.grid.insert_line(axis, at);
+        } else {
+            self.grid.remove_line(axis, at);
+        }
+        rebuild_dependents(&mut self.grid);
+        let sequence = full_sequence(&mut self.grid);
+        self.update_grid(sequence);
+        if self.sheets.len() > 1 {
+            let name = self.sheet_name().to_string();
+            let mut readers = Vec::new();
+            for index in (0..self.sheets.len()).filter(|&index| index != self.active) {
+                let grid = &mut self.sheets[index].grid;
+                for (i, row) in grid.cells_vec.iter_mut().enumerate() {
+                    for (j, node) in row.iter_mut().enumerate() {
+                        if let Some(func) = &node.function {
+                            let shifted = shift_sheet_refs(func, &name, axis, at, insert);
+                            if shifted != *func {
+                                node.function = Some(shifted);
+                                readers.push((index, Value::Cell(i, j)));
+                            }
+                        }
+                    }
+                }
+            }
+            self.relink_sheets();
+            for (index, cell) in readers {
+                self.recalc(index, cell);
+            }
+        }
+        Status::Success
+    }
+    ///Grows or shrinks the active sheet to `rows` x `columns` as a single undo step.
+    ///Rows and columns are added or dropped at the end. Shrinking is refused while
+    ///a formula that is kept, here or on another sheet, reads a cell that would go.
+    pub fn resize(&mut self, rows: usize, columns: usize) -> Status {
+        if rows == 0 || columns == 0 || rows > MAX_ROWS || columns > MAX_COLUMNS {
+            return Status::Error(format!(
+                "Size must be between 1x1 and {}x{}",
+                MAX_ROWS, MAX_COLUMNS
+            ));
+        }
+        let kept = |cell: &Value| cell.row() <= rows && cell.col() <= columns;
+        for reader in &self.grid.line_readers {
+            let dropped = match (&reader.start, &reader.end) {
+                (_, Value::Column(last)) => *last > columns,
+                (_, Value::Row(last)) => *last > rows,
+                _ => false,
+            };
+            if dropped && kept(&reader.cell) {
+                return Status::Error(format!(
+                    "Cannot resize: {} reads {}:{}",
+                    reader.cell, reader.start, reader.end
+                ));
+            }
+        }
+        for (i, line) in self.grid.cells_vec.iter().enumerate().skip(1) {
+            for (j, node) in line.iter().enumerate().skip(1) {
+                let cell = Value::Cell(i, j);
+                if kept(&cell) {
+                    continue;
+                }
+                for dep in &node.dependents {
+                    let reader = match dep {
+                        Value::Cell(_, _) if kept(dep) => dep.to_string(),
+                        Value::SheetRef(sheet, target) => format!("{}!{}", sheet, target),
+                        _ => continue,
+                    };
+                    return Status::Error(format!("Cannot resize: {} reads {}", reader, cell));
+                }
+            }
+        }
+        self.checkpoint();
+        self.grid.resize(rows, columns);
+        rebuild_dependents(&mut self.grid);
+        if self.sheets.len() > 1 {
+            self.relink_sheets();
+        }
+        // formulas over whole rows or columns see the new cells
+        let sequence = full_sequence(&mut self.grid);
+        self.update_grid(sequence);
+        Status::Success
+    }
+    ///Substitutes names and checks sheet references in a parsed formula
+    fn expand_func(&self, func: Value) -> Result<(Value, Names), String> {
+        self.expand_names(func)
+            .and_then(|(func, names)| Ok((self.resolve_sheet_refs(func)?, names)))
+    }
+    ///Stores a formula expanded by `expand_func` with the anchors and names it was typed with
+    fn assign(&mut self, cell: Value, func: Value, names: Names, anchors: Anchors) -> Status {
+        let status = self.execute(cell.clone(), Some(func));
+        if status == Status::Success {
+            let node = self.grid.get_node(cell.row(), cell.col());
+            node.anchors = named_anchors(anchors, &names);
+            node.names = names;
+        }
+        status
+    }
+    ///Lists the cells of the active sheet whose value equals `text`, or whose formula
+    ///contains it as whole words
+    pub fn find(&self, text: &str) -> Status {
+        let number = text.parse::<isize>().ok();
+        let mut found = Vec::new();
+        for row in 1..self.grid.get_row_size() {
+            for col in 1..self.grid.get_column_size() {
+                let node = &self.grid.cells_vec[row][col];
+                let Some(formula) = node.formula() else {
+                    continue;
+                };
+                let value = node.get_node_value();
+                if (number.is_some() && value == number) || formula_contains(&formula, text) {
+                    let value = value.map_or("ERR".to_string(), |val| val.to_string());
+                    found.push(if formula == value {
+                        format!("{}: {}", Value::Cell(row, col), value)
+                    } else {
+                        format!("{}: {} = {}", Value::Cell(row, col), formula, value)
+                    });
+                }
+            }
+        }
+        if found.is_empty() {
+            return Status::Report("No matches".to_string());
+        }
+        Status::Report(found.join("\n"))
+    }
+    ///Returns the sheet and cell a reference read on sheet `index` points at, if they exist
+    fn locate(&self, index: usize, reference: &Value) -> Option<(usize, Value)> {
+        let (index, cell) = match reference {
+            Value::Cell(_, _) => (index, reference.clone()),
+            Value::SheetRef(sheet, cell) => (self.sheet_index(sheet)?, (**cell).clone()),
+            _ => return None,
+        };
+        fits(self.sheet_grid(index), &cell).then_some((index, cell))
+    }
+    ///Writes a cell as `B1: A1+A10 = 7`, `A1: 3` or `A5: empty`, naming its sheet when
+    ///it is not the active one
+    fn describe(&self, (index, cell): &(usize, Value)) -> String {
+        let label = if *index == self.active {
+            cell.to_string()
+        } else {
+            format!("{}!{}", self.sheets[*index].name, cell)
+        };
+        let node = &self.sheet_grid(*index).cells_vec[cell.row()][cell.col()];
+        let Some(formula) = node.formula() else {
+            return format!("{}: empty", label);
+        };
+        let value = node
+            .get_node_value()
+            .map_or("ERR".to_string(), |val| val.to_string());
+        if formula == value {
+            format!("{}: {}", label, value)
+        } else {
+            format!("{}: {} = {}", label, formula, value)
+        }
+    }
+    ///Returns the cells `cell` reads (`upstream`) or the cells that read it, across sheets.
+    ///Empty cells of a range are left out as they read nothing.
+    fn linked_cells(&self, (index, cell): &(usize, Value), upstream: bool) -> Vec<(usize, Value)> {
+        let grid = self.sheet_grid(*index);
+        if upstream {
+            let Some(func) = &grid.cells_vec[cell.row()][cell.col()].function else {
+                return Vec::new();
+            };
+            let range = matches!(func, Value::Oper(_, _, oper) if oper.is_range());
+            precedents(func, grid)
+                .iter()
+                .filter_map(|reference| self.locate(*index, reference))
+                .filter(|(index, cell)| {
+                    !range || !self.sheet_grid(*index).cells_vec[cell.row()][cell.col()].is_blank()
+                })
+                .collect()
+        } else {
+            grid.dependents(cell.row(), cell.col())
+                .iter()
+                .filter_map(|reference| self.locate(*index, reference))
+                .collect()
+        }
+    }
+    ///Adds the cells linked to `from` to `lines` as an indented tree. Cells reached a
+    ///second time are listed without their subtree.
+    fn trace_tree(
+        &self,
+        from: &(usize, Value),
+        upstream: bool,
+        depth: usize,
+        seen: &mut HashSet<(usize, usize, usize)>,
+        lines: &mut Vec<String>,
+    ) {
+        let indent = "  ".repeat(depth);
+        for next in self.linked_cells(from, upstream) {
+            if !seen.insert((next.0, next.1.row(), next.1.col())) {
+                lines.push(format!("{}{} (see above)", indent, self.describe(&next)));
+                continue;
+            }
+            lines.push(format!("{}{}", indent, self.describe(&next)));
+            self.trace_tree(&next, upstream, depth + 1, seen, lines);
+        }
+    }
+    ///Explains why `cell` of the active sheet is an error, when the reason is known
+    fn error_cause(&self, cell: &Value) -> Option<String> {
+        let func = self.grid.cells_vec[cell.row()][cell.col()]
+            .function
+            .as_ref()?;
+        if func.has_ref_error() {
+            return Some("reads a deleted cell".to_string());
+        }
+        for reference in precedents(func, &self.grid) {
+            if let Some((index, target)) = self.locate(self.active, &reference)
+                && self
+                    .sheet_grid(index)
+                    .get_node_value(target.row(), target.col())
+                    .is_none()
+            {
+                return Some(format!("reads {}, which is an error", reference));
+            }
+        }
+        if let Value::Oper(_, Some(divisor), Operation::Div) = func {
+            let divisor = match &**divisor {
+                Value::Const(val) => Some(*val),
+                reference => self
+                    .locate(self.active, reference)
+                    .and_then(|(index, target)| {
+                        self.sheet_grid(index)
+                            .get_node_value(target.row(), target.col())
+                    }),
+            };
+            if divisor == Some(0) {
+                return Some("division by zero".to_string());
+            }
+        }
+        None
+    }
+    ///Describes a cell of the active sheet: its formula, its value or why it is an
+    ///error, the references it reads and the cells that read it
+    pub fn show(&self, cell: &Value) -> Status {
+        let node = &self.grid.cells_vec[cell.row()][cell.col()];
+        let list = |items: Vec<String>| {
+            if items.is_empty() {
+                "none".to_string()
+            } else {
+                items.join(", ")
+            }
+        };
+        let value = match node.get_node_value() {
+            _ if node.is_blank() => "0 (empty)".to_string(),
+            Some(val) => val.to_string(),
+            None => match self.error_cause(cell) {
+                Some(cause) => format!("ERR ({})", cause),
+                None => "ERR".to_string(),
+            },
+        };
+        let dependents = self.grid.dependents(cell.row(), cell.col());
+        Status::Report(
+            [
+                format!("Cell: {}", cell),
+                format!("Formula: {}", node.formula().unwrap_or("none".to_string())),
+                format!("Value: {}", value),
+                format!(
+                    "Precedents: {}",
+                    list(
+                        node.function
+                            .as_ref()
+                            .map_or(Vec::new(), written_references)
+                    )
+                ),
+                format!(
+                    "Dependents: {}",
+                    list(dependents.iter().map(ToString::to_string).collect())
+                ),
+            ]
+            .join("\n"),
+        )
+    }
+    ///Prints every cell `cell` depends on and every cell that depends on it, as two trees
+    pub fn trace(&self, cell: &Value) -> Status {
+        let start = (self.active, cell.clone());
+        let mut lines = vec![self.describe(&start)];
+        for (title, upstream) in [("Precedents:", true), ("Dependents:", false)] {
+            lines.push(title.to_string());
+            let before = lines.len();
+            self.trace_tree(&start, upstream, 1, &mut HashSet::new(), &mut lines);
+            if lines.len() == before {
+                lines.push("  none".to_string());
+            }
+        }
+        Status::Report(lines.join("\n"))
+    }
+    ///Replaces `old` with `new` in the formulas of the active sheet, matching whole
+    ///references only, as a single undo step
+    pub fn replace(&mut self, old: &str, new: &str) -> Status {
+        let rows = self.grid.get_row_size() - 1;
+        let columns = self.grid.get_column_size() - 1;
+        let mut edits = Vec::new();
+        for row in 1..=rows {
+            for col in 1..=columns {
+                let Some(formula) = self.grid.cells_vec[row][col].formula() else {
+                    continue;
+                };
+                let Some(formula) = replace_in_formula(&formula, old, new) else {
+                    continue;
+                };
+                let cell = Value::Cell(row, col);
+                let cmd = format!("{}={}", cell, formula);
+                let mut details = Details::default();
+                let Some((Some(_), Some(func @ Value::Oper(_, _, _)))) =
+                    parser::validate_with(&cmd, &columns, &rows, &mut details)
+                else {
+                    return Status::Error(format!(
+                        "Replacing gives an invalid formula in {}: {}",
+                        cell, formula
+                    ));
+                };
+                match self.expand_func(func) {
+                    Ok((func, names)) => edits.push((cell, func, names, details.anchors)),
+                    Err(message) => return Status::Error(format!("{} in {}", message, cell)),
+                }
+            }
+        }
+        if edits.is_empty() {
+            return Status::Report("No matches".to_string());
+        }
+        self.checkpoint();
+        let cells: Vec<String> = edits.iter().map(|edit| edit.0.to_string()).collect();
+        for (cell, func, names, anchors) in edits {
+            let status = self.assign(cell, func, names, anchors);
+            if status != Status::Success {
+                self.rollback();
+                return status;
+            }
+        }
+        Status::Report(format!("Replaced in {}", cells.join(", ")))
+    }
     ///Takes command from frontend, calls the Parser, and sends the decoded command to execute function
     pub fn process_command(&mut self, rows: usize, columns: usize, cmd: String) -> Status {
-        match parser::validate(&cmd, &columns, &rows) {
+        self.recomputed.clear();
+        let status = self.run_command(rows, columns, &cmd);
+        self.record(rows, columns, &cmd, &status);
+        status
+    }
+    ///Adds an edit that did not fail to the macro being recorded. Commands that only
+    ///look at the sheet, such as `show` or `save`, are left out whatever they return.
+    fn record(&mut self, rows: usize, columns: usize, cmd: &str, status: &Status) {
+        let Some(recording) = &mut self.recording else {
+            return;
+        };
+        let failed = matches!(
+            status,
+            Status::UnrecognizedCmd
+                | Status::CircularDependency
+                | Status::Error(_)
+                | Status::UnsavedChanges(_)
+        );
+        if !failed
+            && let Some((_, Some(Value::Oper(_, _, op)))) = parser::validate(cmd, &columns, &rows)
+            && op.is_edit()
+        {
+            recording.commands.push(cmd.trim().to_string());
+        }
+    }
+    ///Runs one command for `process_command` or a macro being played
+    fn run_command(&mut self, rows: usize, columns: usize, cmd: &str) -> Status {
+        let mut details = Details::default();
+        match parser::validate_with(cmd, &columns, &rows, &mut details) {
             Some((None, Some(Value::Oper(None, None, op)))) => match op {
                 Operation::EnableOutput => Status::PrintEnabled,
                 Operation::DisableOutput => Status::PrintDisabled,
@@ -308,29 +1697,86 @@ impl Backend {
                 Operation::Down => Status::Down,
                 Operation::Quit => Status::Quit,
                 Operation::Web(path) => Status::Web(path),
+                Operation::View(change) => Status::View(change),
                 Operation::WebStart => Status::WebStart,
                 Operation::Save(path) => {
                     if self.serial(&path).is_err() {
                         return Status::UnrecognizedCmd;
                     }
+                    self.dirty = false;
                     Status::Success
                 }
+                Operation::Open(path) => {
+                    if self.dirty {
+                        return Status::UnsavedChanges(path);
+                    }
+                    self.open(&path)
+                }
+                Operation::Resize(rows, columns) => self.resize(rows, columns),
+                Operation::InsertRow(at) => self.restructure(Axis::Row, at, true),
+                Operation::DeleteRow(at) => self.restructure(Axis::Row, at, false),
+                Operation::InsertCol(at) => self.restructure(Axis::Column, at, true),
+                Operation::DeleteCol(at) => self.restructure(Axis::Column, at, false),
+                Operation::AddSheet(name) => self.add_sheet(&name),
+                Operation::RenameSheet(old, new) => self.rename_sheet(&old, &new),
+                Operation::DeleteSheet(name) => self.delete_sheet(&name),
+                Operation::SwitchSheet(name) => self.switch_sheet(&name),
+                Operation::Help(name) => match help::help_text(name.as_deref()) {
+                    Some(text) => Status::Report(text),
+                    None => Status::UnrecognizedCmd,
+                },
+                Operation::RecordStart(name, global) => self.record_start(name, global),
+                Operation::RecordStop => self.record_stop(),
+                Operation::Play(name) => self.play(&name, None),
+                Operation::ListMacros => self.list_macros(),
+                Operation::Find(text) => self.find(&text),
+                Operation::Replace(old, new) => self.replace(&old, &new),
+                Operation::ListNames => {
+                    if self.names.is_empty() {
+                        return Status::Report("No names defined".to_string());
+                    }
+                    let names: Vec<String> = self
+                        .names
+                        .iter()
+                        .map(|(name, value)| format!("{} = {}", name, value))
+                        .collect();
+                    Status::Report(names.join("\n"))
+                }
+                Operation::ListSheets => {
+                    let names: Vec<String> = self
+                        .sheets
+                        .iter()
+                        .enumerate()
+                        .map(|(index, sheet)| {
+                            if index == self.active {
+                                format!("* {}", sheet.name)
+                            } else {
+                                format!("  {}", sheet.name)
+                            }
+                        })
+                        .collect();
+                    Status::Report(names.join("\n"))
+                }
                 Operation::Undo => {
-                    if let Some(prev_grid) = self.undo_stack.pop() {
-                        self.redo_stack.push(self.grid.clone());
-                        self.grid = prev_grid;
+                    if let Some(prev_state) = self.undo_stack.pop() {
+                        let state = self.undo_state();
+                        self.redo_stack.push(state);
+                        self.restore(prev_state);
+                        self.dirty = true;
                         Status::Success
                     } else {
                         Status::UnrecognizedCmd
                     }
                 }
                 Operation::Redo => {
-                    if let Some(next_grid) = self.redo_stack.pop() {
-                        self.undo_stack.push(self.grid.clone());
+                    if let Some(next_state) = self.redo_stack.pop() {
+                        let state = self.undo_state();
+                        self.undo_stack.push(state);
                         if self.undo_stack.len() > UNDO_LIMIT {
                             self.undo_stack.remove(0); // drop oldest
                         }
-                        self.grid = next_grid;
+                        self.restore(next_state);
+                        self.dirty = true;
                         Status::Success
                     } else {
                         Status::UnrecognizedCmd
@@ -342,15 +1788,113 @@ impl Backend {
                 Some(Value::Cell(col, row)),
                 Some(Value::Oper(None, None, Operation::ScrollTo)),
             )) => Status::ScrollTo(col, row),
+            Some((Some(cell), Some(Value::Oper(None, None, Operation::Show)))) => self.show(&cell),
+            Some((Some(cell), Some(Value::Oper(None, None, Operation::Play(name))))) => {
+                self.play(&name, Some(&cell))
+            }
+            Some((Some(cell), Some(Value::Oper(None, None, Operation::Trace)))) => {
+                self.trace(&cell)
+            }
+            Some((
+                None,
+                Some(Value::Oper(Some(start), Some(end), Operation::ExportImage(path, scale))),
+            )) => match export_image(&self.grid, &start, &end, &path, scale) {
+                Ok(()) => Status::Success,
+                Err(e) => Status::Error(e),
+            },
+            Some((
+                None,
+                Some(Value::Oper(
+                    Some(start),
+                    Some(end),
+                    Operation::ExportMarkdown(path, formulas),
+                )),
+            )) => {
+                let table = range_to_markdown(&self.grid, &start, &end, formulas);
+                match fs::write(&path, table) {
+                    Ok(()) => Status::Success,
+                    Err(e) => Status::Error(format!("File write error: {}", e)),
+                }
+            }
+            Some((
+                None,
+                Some(Value::Oper(Some(start), Some(end), Operation::ExportHtml(path, formulas))),
+            )) => {
+                let table = range_to_html(&self.grid, &start, &end, formulas);
+                match fs::write(&path, table) {
+                    Ok(()) => Status::Success,
+                    Err(e) => Status::Error(format!("File write error: {}", e)),
+                }
+            }
+            Some((Some(cell), Some(Value::Oper(None, None, Operation::ImportImage(path))))) => {
+                match read_image(&path) {
+                    Ok(pixels) => self.set_constants(&cell, &pixels),
+                    Err(e) => Status::Error(e),
+                }
+            }
+            Some((Some(target), Some(Value::Oper(Some(start), Some(end), Operation::Copy)))) => {
+                self.copy_range(&start, &end, &target)
+            }
+            Some((Some(target), Some(Value::Oper(Some(start), Some(end), Operation::Move)))) => {
+                self.move_range(&start, &end, &target)
+            }
+            Some((None, Some(Value::Oper(Some(start), Some(end), Operation::Sort(keys))))) => {
+                self.sort_range(&start, &end, &keys)
+            }
+            Some((None, Some(Value::Oper(Some(start), Some(end), Operation::Clear)))) => {
+                self.clear_range(&start, &end)
+            }
+            Some((None, Some(Value::Oper(Some(start), Some(end), Operation::Format(format))))) => {
+                self.set_format(&start, &end, format)
+            }
+            Some((None, Some(Value::Oper(Some(start), Some(end), Operation::FillDown)))) => {
+                self.fill(&start, &end, true)
+            }
+            Some((None, Some(Value::Oper(Some(start), Some(end), Operation::FillRight)))) => {
+                self.fill(&start, &end, false)
+            }
+            Some((None, Some(Value::Oper(Some(first), second, Operation::Define(name))))) => {
+                let value = match (*first, second.map(|end| *end)) {
+                    (Value::Const(val), None) => NameValue::Const(val),
+                    (Value::SheetRef(sheet, start), Some(Value::SheetRef(_, end))) => {
+                        NameValue::Range(sheet, start, end)
+                    }
+                    (start, Some(end)) => NameValue::Range(
+                        self.sheet_name().to_string(),
+                        Box::new(start),
+                        Box::new(end),
+                    ),
+                    _ => return Status::UnrecognizedCmd,
+                };
+                self.define(&name, value)
+            }
+            Some((None, Some(Value::Oper(Some(start), Some(end), Operation::Chart(spec))))) => {
+                match render_chart(&self.grid, &start, &end, &spec) {
+                    Ok(()) => Status::Success,
+                    Err(e) => Status::Error(e),
+                }
+            }
+            Some((None, Some(Value::Oper(Some(start), Some(end), Operation::Plot(kind))))) => {
+                match series(&self.grid, &start, &end) {
+                    Ok(values) => Status::Report(plot(&values, &kind, &start, &end)),
+                    Err(e) => Status::Error(e),
+                }
+            }
+            Some((None, Some(Value::Oper(Some(start), Some(end), Operation::Spark)))) => {
+                match series(&self.grid, &start, &end) {
+                    Ok(values) => Status::Report(sparkline(&values)),
+                    Err(e) => Status::Error(e),
+                }
+            }
             Some((Some(Value::Cell(col, row)), Some(Value::Oper(box1, box2, op)))) => {
                 // change here
                 // either have to change parser or change the inside parts of box1 and box2
-                self.undo_stack.push(self.grid.clone());
-                if self.undo_stack.len() > UNDO_LIMIT {
-                    self.undo_stack.remove(0);
-                }
-                self.redo_stack.clear(); // clear redo stack on new action
-                self.execute(Value::Cell(col, row), Some(Value::Oper(box1, box2, op)))
+                let (func, names) = match self.expand_func(Value::Oper(box1, box2, op)) {
+                    Ok(expanded) => expanded,
+                    Err(message) => return Status::Error(message),
+                };
+                self.checkpoint();
+                self.assign(Value::Cell(col, row), func, names, details.anchors)
             }
             _ => Status::UnrecognizedCmd,
         }
@@ -368,10 +1912,215 @@ impl Backend {
 
     pub fn deserial(path: &str) -> Result<Self, String> {
         let json = fs::read_to_string(path).map_err(|e| format!("File read error: {}", e))?;
-        serde_json::from_str(&json).map_err(|e| format!("Deserialization error: {}", e))
+        Self::deserial_text(json)
     }
 
     pub fn deserial_text(text: String) -> Result<Self, String> {
-        serde_json::from_str(&text).map_err(|e| format!("Deserialization error: {}", e))
+        let mut backend: Self =
+            serde_json::from_str(&text).map_err(|e| format!("Deserialization error: {}", e))?;
+        // files saved before sheets existed hold a single unnamed sheet
+        if backend.sheets.is_empty() {
+            backend.sheets.push(Sheet {
+                name: "Sheet1".to_string(),
+                grid: Grid::new(0, 0),
+            });
+            backend.active = 0;
+        }
+        Ok(backend)
+    }
+
+    ///Reads a sheet from a file, picking the format from the file extension (`.json` or `.csv`)
+    pub fn load(path: &str) -> Result<Self, String> {
+        let extension = std::path::Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("json") => Self::deserial(path),
+            Some("csv") => {
+                let text =
+                    fs::read_to_string(path).map_err(|e| format!("File read error: {}", e))?;
+                Self::from_csv_text(&text)
+            }
+            _ => Err(format!("Unsupported file format: {}", path)),
+        }
+    }
+
+    ///Builds a sheet from CSV text. Each field is either empty, an integer or a formula starting with '='
+    pub fn from_csv_text(text: &str) -> Result<Self, String> {
+        let lines: Vec<Vec<&str>> = text
+            .lines()
+            .map(|line| line.split(',').map(|field| field.trim()).collect())
+            .collect();
+        let rows = lines.len();
+        let columns = lines.iter().map(|fields| fields.len()).max().unwrap_or(0);
+        if !(1..=MAX_ROWS).contains(&rows) || !(1..=MAX_COLUMNS).contains(&columns) {
+            return Err(format!("CSV dimensions {}x{} out of bounds", rows, columns));
+        }
+        let mut backend = Backend::init_backend(rows, columns);
+        for (i, fields) in lines.iter().enumerate() {
+            for (j, field) in fields.iter().enumerate() {
+                if field.is_empty() {
+                    continue;
+                }
+                let cell = format!("{}{}", column_decoder(j + 1), i + 1);
+                let exp = field.strip_prefix('=').unwrap_or(field);
+                let status = backend.process_command(rows, columns, format!("{}={}", cell, exp));
+                if status != Status::Success {
+                    return Err(format!("Invalid entry '{}' at {}", field, cell));
+                }
+            }
+        }
+        backend.undo_stack.clear();
+        backend.dirty = false;
+        Ok(backend)
+    }
+
+    ///Replaces the current sheet with the one stored at `path`
+    pub fn open(&mut self, path: &str) -> Status {
+        match Self::load(path) {
+            Ok(mut backend) => {
+                backend.macro_file = self.macro_file.take();
+                backend.recording = self.recording.take();
+                *self = backend;
+                Status::Opened
+            }
+            Err(e) => Status::Error(e),
+        }
+    }
+
+    ///Starts recording the edits made to the sheet as macro `name`
+    fn record_start(&mut self, name: String, global: bool) -> Status {
+        if let Some(recording) = &self.recording {
+            return Status::Error(format!("Already recording {}", recording.name));
+        }
+        let message = format!("Recording {}, finish with record stop", name);
+        self.recording = Some(Recording {
+            name,
+            commands: Vec::new(),
+            global,
+        });
+        Status::Report(message)
+    }
+    ///Stops recording and keeps the macro with the sheet or in the user macro file
+    fn record_stop(&mut self) -> Status {
+        let Some(recording) = self.recording.take() else {
+            return Status::Error("Not recording".to_string());
+        };
+        if recording.commands.is_empty() {
+            return Status::Error(format!("Nothing recorded for {}", recording.name));
+        }
+        let message = format!(
+            "Recorded {} commands as {}",
+            recording.commands.len(),
+            recording.name
+        );
+        if recording.global {
+            let Some(path) = &self.macro_file else {
+                return Status::Error("No user macro file".to_string());
+            };
+            let saved = read_macro_file(path).and_then(|mut macros| {
+                macros.insert(recording.name, recording.commands);
+                write_macro_file(path, &macros)
+            });
+            if let Err(e) = saved {
+                return Status::Error(e);
+            }
+        } else {
+            self.macros.insert(recording.name, recording.commands);
+            self.dirty = true;
+        }
+        Status::Report(message)
+    }
+    ///Macros of the user macro file; a file that cannot be read holds none
+    fn user_macros(&self) -> Macros {
+        self.macro_file
+            .as_deref()
+            .and_then(|path| read_macro_file(path).ok())
+            .unwrap_or_default()
+    }
+    ///Lists the macros of the sheet and of the user macro file
+    fn list_macros(&self) -> Status {
+        let mut lines = Vec::new();
+        for (macros, place) in [(&self.macros, "sheet"), (&self.user_macros(), "user")] {
+            for (name, commands) in macros {
+                lines.push(format!("{} ({}): {}", name, place, commands.join("; ")));
+            }
+        }
+        if lines.is_empty() {
+            return Status::Report("No macros recorded".to_string());
+        }
+        Status::Report(lines.join("\n"))
+    }
+    ///Replays macro `name`, moving its relative references so that its top-left cell
+    ///lands on `at`. The whole macro is one undo step and is undone if a command fails.
+    fn play(&mut self, name: &str, at: Option<&Value>) -> Status {
+        let Some(commands) = self
+            .macros
+            .get(name)
+            .cloned()
+            .or_else(|| self.user_macros().remove(name))
+        else {
+            return Status::Error(format!("Unknown macro {}", name));
+        };
+        let (rows, columns) = match (at, origin(&commands)) {
+            (Some(at), Some((row, column))) => (
+                at.row() as isize - row as isize,
+                at.col() as isize - column as isize,
+            ),
+            _ => (0, 0),
+        };
+        // the commands push their own undo steps on an empty stack, the first of
+        // which holds the sheet as it was before the macro
+        let undo_stack = std::mem::take(&mut self.undo_stack);
+        let mut recomputed = Vec::new();
+        let mut played = Vec::new();
+        let mut failure = None;
+        for command in commands.iter().filter(|cmd| !is_macro_command(cmd)) {
+            let Some(command) = shift_command(command, rows, columns) else {
+                failure = Some(format!("'{}': it would move off the sheet", command));
+                break;
+            };
+            let grid_rows = self.grid.get_row_size() - 1;
+            let grid_columns = self.grid.get_column_size() - 1;
+            let status = self.run_command(grid_rows, grid_columns, &command);
+            recomputed.append(&mut self.recomputed);
+            let message = match status {
+                Status::Error(message) => message,
+                Status::CircularDependency => "circular dependency".to_string(),
+                Status::UnrecognizedCmd => {
+                    match parser::check(&command, &grid_columns, &grid_rows) {
+                        Err(error) => parser::describe(&error),
+                        Ok(_) => "command failed".to_string(),
+                    }
+                }
+                Status::UnsavedChanges(_) => "unsaved changes".to_string(),
+                _ => {
+                    played.push((grid_rows, grid_columns, command, status));
+                    continue;
+                }
+            };
+            failure = Some(format!("'{}': {}", command, message));
+            break;
+        }
+        let before = (!self.undo_stack.is_empty()).then(|| self.undo_stack.remove(0));
+        self.undo_stack = undo_stack;
+        if let Some(failure) = failure {
+            if let Some(state) = before {
+                self.restore(state);
+            }
+            return Status::Error(format!("{} stopped at {}", name, failure));
+        }
+        if let Some(state) = before {
+            self.undo_stack.push(state);
+            if self.undo_stack.len() > UNDO_LIMIT {
+                self.undo_stack.remove(0);
+            }
+        }
+        for (rows, columns, command, status) in played {
+            self.record(rows, columns, &command, &status);
+        }
+        self.recomputed = recomputed;
+        Status::Success
     }
 }
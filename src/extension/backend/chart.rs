@@ -0,0 +1,133 @@
+/// Headless chart rendering for the `chart` command.
+///
+/// Draws line, bar and scatter charts of a range into SVG or PNG files using
+/// plotters' file backends, so reports can be produced without the web UI.
+use crate::extension::backend::backend::Grid;
+use crate::extension::common::{ChartKind, ChartSpec, Value};
+use plotters::coord::Shift;
+use plotters::prelude::*;
+
+/// Collects the points to plot from a range.
+///
+/// A single row or column gives its values against their position (1, 2, ...).
+/// Two columns give x values from the first column and y values from the second.
+/// Cells holding an error are skipped.
+pub fn chart_points(grid: &Grid, start: &Value, end: &Value) -> Result<Vec<(f64, f64)>, String> {
+    let height = end.row() - start.row() + 1;
+    let width = end.col() - start.col() + 1;
+    let mut points = Vec::new();
+    if height == 1 || width == 1 {
+        let mut position = 0.0;
+        for i in start.row()..=end.row() {
+            for j in start.col()..=end.col() {
+                position += 1.0;
+                if let Some(y) = grid.get_node_value(i, j) {
+                    points.push((position, y as f64));
+                }
+            }
+        }
+    } else if width == 2 {
+        for i in start.row()..=end.row() {
+            let x = grid.get_node_value(i, start.col());
+            let y = grid.get_node_value(i, end.col());
+            if let (Some(x), Some(y)) = (x, y) {
+                points.push((x as f64, y as f64));
+            }
+        }
+    } else {
+        return Err("Chart range must be one row, one column or two columns".to_string());
+    }
+    if points.is_empty() {
+        return Err("Chart range has no valid values".to_string());
+    }
+    Ok(points)
+}
+
+/// Returns `(min, max)` padded so that the axis never collapses to a point.
+fn axis_bounds(values: impl Iterator<Item = f64>, include_zero: bool, pad: f64) -> (f64, f64) {
+    let (mut min, mut max) =
+        values.fold((f64::MAX, f64::MIN), |(lo, hi), v| (lo.min(v), hi.max(v)));
+    if include_zero {
+        min = min.min(0.0);
+        max = max.max(0.0);
+    }
+    if (max - min).abs() < f64::EPSILON {
+        max += 1.0;
+        min -= 1.0;
+    }
+    (min - pad, max + pad)
+}
+
+fn draw<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    points: &[(f64, f64)],
+    spec: &ChartSpec,
+) -> Result<(), DrawingAreaErrorKind<DB::ErrorType>> {
+    root.fill(&WHITE)?;
+    let bar = spec.kind == ChartKind::Bar;
+    let x_pad = if bar { 0.5 } else { 0.0 };
+    let (x_min, x_max) = axis_bounds(points.iter().map(|p| p.0), false, x_pad);
+    let (y_min, y_max) = axis_bounds(points.iter().map(|p| p.1), bar, 0.0);
+
+    let mut chart = ChartBuilder::on(&root)
+        .caption(&spec.title, ("sans-serif", 28))
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(x_min..x_max, y_min..y_max)?;
+    chart
+        .configure_mesh()
+        .x_desc(spec.x_label.as_str())
+        .y_desc(spec.y_label.as_str())
+        .draw()?;
+
+    match spec.kind {
+        ChartKind::Line => {
+            chart.draw_series(LineSeries::new(points.iter().copied(), &BLUE))?;
+        }
+        ChartKind::Scatter => {
+            chart.draw_series(
+                points
+                    .iter()
+                    .map(|&point| Circle::new(point, 4, BLUE.filled())),
+            )?;
+        }
+        ChartKind::Bar => {
+            chart.draw_series(
+                points
+                    .iter()
+                    .map(|&(x, y)| Rectangle::new([(x - 0.4, 0.0), (x + 0.4, y)], BLUE.filled())),
+            )?;
+        }
+    }
+    root.present()
+}
+
+/// Renders the chart of a range into the file named in `spec`.
+///
+/// The backend is picked from the file extension: `.svg` or `.png`.
+pub fn render_chart(
+    grid: &Grid,
+    start: &Value,
+    end: &Value,
+    spec: &ChartSpec,
+) -> Result<(), String> {
+    let points = chart_points(grid, start, end)?;
+    let size = (800, 500);
+    let path = std::path::Path::new(&spec.path);
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("svg") => draw(
+            SVGBackend::new(path, size).into_drawing_area(),
+            &points,
+            spec,
+        )
+        .map_err(|e| format!("Chart error: {}", e)),
+        Some("png") => draw(
+            BitMapBackend::new(path, size).into_drawing_area(),
+            &points,
+            spec,
+        )
+        .map_err(|e| format!("Chart error: {}", e)),
+        _ => Err(format!("Unsupported chart format: {}", spec.path)),
+    }
+}
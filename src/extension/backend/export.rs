@@ -0,0 +1,141 @@
+/// Renders computed values of a sheet as text for use outside the spreadsheet.
+use crate::extension::backend::backend::{Grid, Valgrid};
+use crate::extension::common::{Operation, Value, column_decoder};
+use serde_json::{Map, json};
+
+/// Label of a range in `A1:B2` notation.
+pub fn range_label(start: &Value, end: &Value) -> String {
+    format!(
+        "{}{}:{}{}",
+        column_decoder(start.col()),
+        start.row(),
+        column_decoder(end.col()),
+        end.row()
+    )
+}
+
+/// Renders the values of a range as CSV, one line per row.
+///
+/// Cells holding an error are written as `ERR` and blank cells as empty fields.
+pub fn range_to_csv(valgrid: &Valgrid, start: &Value, end: &Value) -> String {
+    let mut out = String::new();
+    for i in start.row()..=end.row() {
+        let line: Vec<String> = (start.col()..=end.col())
+            .map(|j| match valgrid.cells[i][j] {
+                _ if valgrid.blanks[i][j] => String::new(),
+                Some(value) => value.to_string(),
+                None => "ERR".to_string(),
+            })
+            .collect();
+        out.push_str(&line.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// Renders several ranges as a JSON object keyed by range label.
+///
+/// Each range maps to an array of rows; blank cells and cells holding an error are `null`.
+pub fn ranges_to_json(valgrid: &Valgrid, ranges: &[(Value, Value)]) -> String {
+    let mut object = Map::new();
+    for (start, end) in ranges {
+        let rows: Vec<Vec<Option<isize>>> = (start.row()..=end.row())
+            .map(|i| {
+                (start.col()..=end.col())
+                    .map(|j| valgrid.cells[i][j].filter(|_| !valgrid.blanks[i][j]))
+                    .collect()
+            })
+            .collect();
+        object.insert(range_label(start, end), json!(rows));
+    }
+    serde_json::to_string(&object).unwrap_or_default()
+}
+
+/// Text of a cell for a report table and whether it holds an error.
+///
+/// Blank cells are empty. With `formulas` set, cells computed from other cells also show
+/// their formula.
+fn report_cell(grid: &Grid, row: usize, col: usize, formulas: bool) -> (String, Option<String>) {
+    let node = &grid.cells_vec[row][col];
+    let value = match grid.get_node_value(row, col) {
+        _ if node.is_blank() => String::new(),
+        Some(value) => value.to_string(),
+        None => "ERR".to_string(),
+    };
+    let formula = match &node.function {
+        // plain constants have nothing to show besides their value
+        Some(Value::Oper(Some(box1), _, Operation::Cons)) if matches!(**box1, Value::Const(_)) => {
+            None
+        }
+        Some(_) if formulas => node.formula().map(|formula| format!("={}", formula)),
+        _ => None,
+    };
+    (value, formula)
+}
+
+/// Renders a range as a Markdown table with Excel-style column headers.
+///
+/// Error cells are shown in bold; with `formulas` set, formula cells show
+/// their formula after the value.
+pub fn range_to_markdown(grid: &Grid, start: &Value, end: &Value, formulas: bool) -> String {
+    let columns = start.col()..=end.col();
+    let mut out = String::from("|   |");
+    for j in columns.clone() {
+        out.push_str(&format!(" {} |", column_decoder(j)));
+    }
+    out.push_str("\n|---|");
+    for _ in columns.clone() {
+        out.push_str("---:|");
+    }
+    out.push('\n');
+    for i in start.row()..=end.row() {
+        out.push_str(&format!("| {} |", i));
+        for j in columns.clone() {
+            let (value, formula) = report_cell(grid, i, j, formulas);
+            let value = if grid.get_node_value(i, j).is_none() {
+                format!("**{}**", value)
+            } else {
+                value
+            };
+            match formula {
+                Some(formula) => out.push_str(&format!(" {} `{}` |", value, formula)),
+                None => out.push_str(&format!(" {} |", value)),
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Renders a range as an HTML table with Excel-style column headers.
+///
+/// Error cells get the `error` class and are coloured red; with `formulas`
+/// set, formula cells show their formula after the value.
+pub fn range_to_html(grid: &Grid, start: &Value, end: &Value, formulas: bool) -> String {
+    let columns = start.col()..=end.col();
+    let mut out = String::from("<table>\n  <thead>\n    <tr><th></th>");
+    for j in columns.clone() {
+        out.push_str(&format!("<th>{}</th>", column_decoder(j)));
+    }
+    out.push_str("</tr>\n  </thead>\n  <tbody>\n");
+    for i in start.row()..=end.row() {
+        out.push_str(&format!("    <tr><th>{}</th>", i));
+        for j in columns.clone() {
+            let (value, formula) = report_cell(grid, i, j, formulas);
+            let formula = formula
+                .map(|formula| format!(" <code>{}</code>", formula))
+                .unwrap_or_default();
+            if grid.get_node_value(i, j).is_none() {
+                out.push_str(&format!(
+                    "<td class=\"error\" style=\"color: red\">{}{}</td>",
+                    value, formula
+                ));
+            } else {
+                out.push_str(&format!("<td>{}{}</td>", value, formula));
+            }
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("  </tbody>\n</table>\n");
+    out
+}
@@ -8,6 +8,7 @@
 use crate::extension::backend::backend::Grid;
 use crate::extension::common::Operation;
 use crate::extension::common::Value;
+use crate::extension::common::{Anchors, Names, NumberFormat, format_formula, with_names};
 use serde::{Deserialize, Serialize};
 ///Data structure for strong data of each cell
 /// Contains Dependency list, value, function and a few booleans
@@ -22,6 +23,9 @@ use serde::{Deserialize, Serialize};
 /// * `function` - Function/operation assigned to this cell
 /// * `visited` - Flag used during graph traversal algorithms
 /// * `valid` - Flag indicating whether the cell value is valid
+/// * `anchors` - Which parts of the function's references are absolute (`$A$1`)
+/// * `names` - Names the function was written with, in place of its references
+/// * `format` - How the value is displayed, set with the `format` command
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 //clone trait used due to vec![vec![Cell::new(0); columns]; rows]
@@ -31,6 +35,12 @@ pub struct Node {
     pub function: Option<Value>,
     pub visited: bool,
     pub valid: bool,
+    #[serde(default)]
+    pub anchors: Anchors,
+    #[serde(default)]
+    pub names: Names,
+    #[serde(default)]
+    pub format: Option<NumberFormat>,
 }
 
 impl Node {
@@ -42,8 +52,16 @@ impl Node {
             function: None,
             visited: false,
             valid: true,
+            anchors: Anchors::default(),
+            names: Names::default(),
+            format: None,
         }
     }
+    ///True for a cell that holds nothing, as opposed to one set to 0.
+    ///Formulas read blank cells as 0.
+    pub fn is_blank(&self) -> bool {
+        self.function.is_none()
+    }
     pub fn get_node_value(&self) -> Option<isize> {
         if self.valid {
             Some(self.node_value)
@@ -51,6 +69,24 @@ impl Node {
             None
         }
     }
+    ///Returns the function as formula text, e.g. `$A$1+B2` or `SUM(Revenue)`
+    pub fn formula(&self) -> Option<String> {
+        self.function
+            .as_ref()
+            .map(|func| format_formula(&with_names(func, &self.names), &self.anchors))
+    }
+    ///Returns the value as displayed: in the cell's format, `ERR` for an error and
+    ///nothing for a blank cell
+    pub fn display_value(&self) -> String {
+        if self.is_blank() {
+            return String::new();
+        }
+        match (self.get_node_value(), &self.format) {
+            (Some(value), Some(format)) => format.apply(value),
+            (Some(value), None) => value.to_string(),
+            (None, _) => "ERR".to_string(),
+        }
+    }
     pub fn remove_dep(&mut self, cell: Value) {
         self.dependents.retain(|x| x != &cell);
     }
@@ -64,6 +100,15 @@ impl Node {
     }
 }
 
+/// A formula over whole rows or columns (`SUM(A:A)`). It is kept once in
+/// `Grid::line_readers` instead of as a dependent of every cell it reads.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LineReader {
+    pub cell: Value,
+    pub start: Value,
+    pub end: Value,
+}
+
 // update_edges
 // has_cycle
 // get_sequence
@@ -80,7 +125,10 @@ pub fn break_edges(grid: &mut Grid, target: Value, func: Option<Value>, flag: bo
     };
     if let Some(Value::Oper(box1, box2, oper)) = old_func {
         match oper {
-            Operation::Sum | Operation::Avg | Operation::Max | Operation::Min => {
+            Operation::Sum | Operation::Avg | Operation::Max | Operation::Min | Operation::Std => {
+                if let Some(Value::Column(_) | Value::Row(_)) = box1.as_deref() {
+                    grid.line_readers.retain(|reader| reader.cell != target);
+                }
                 if let Some(Value::Cell(row1, col1)) = box1.as_deref() {
                     if let Some(Value::Cell(row2, col2)) = box2.as_deref() {
                         for i in *row1..=*row2 {
@@ -132,7 +180,18 @@ pub fn add_edges(grid: &mut Grid, target: Value, func: Option<Value>, flag: bool
 
     if let Some(Value::Oper(box1, box2, oper)) = old_func {
         match oper {
-            Operation::Sum | Operation::Avg | Operation::Max | Operation::Min => {
+            Operation::Sum | Operation::Avg | Operation::Max | Operation::Min | Operation::Std => {
+                // whole rows and columns are watched through one entry, not one edge per cell
+                if let (Some(start @ (Value::Column(_) | Value::Row(_))), Some(end)) =
+                    (box1.as_deref(), box2.as_deref())
+                {
+                    grid.line_readers.retain(|reader| reader.cell != target);
+                    grid.line_readers.push(LineReader {
+                        cell: target.clone(),
+                        start: start.clone(),
+                        end: end.clone(),
+                    });
+                }
                 if let Some(Value::Cell(row1, col1)) = box1.as_deref() {
                     if let Some(Value::Cell(row2, col2)) = box2.as_deref() {
                         for i in *row1..=*row2 {
@@ -192,15 +251,17 @@ pub fn has_cycle(grid: &mut Grid, target: Value) -> bool {
     let node = grid.get_node(target.row(), target.col());
     node.visited = true;
     while let Some(Value::Cell(row, col)) = stack.pop() {
-        let dependents = grid.get_node(row, col).dependents.clone();
+        let dependents = grid.dependents(row, col);
         for dep in dependents {
             if let Value::Cell(dep_r, dep_c) = dep {
-                let dep_node = grid.get_node(dep_r, dep_c);
-                if dep_node.visited {
+                if dep == target {
                     // cycle detected
                     reset_visited(grid, target.clone());
                     return true;
-                } else {
+                }
+                // a cell reached twice through different paths is not a cycle
+                let dep_node = grid.get_node(dep_r, dep_c);
+                if !dep_node.visited {
                     dep_node.visited = true;
                     stack.push(dep);
                 }
@@ -218,7 +279,7 @@ pub fn reset_visited(grid: &mut Grid, start: Value) {
         grid.get_node(row, col).visited = false;
 
         while let Some(Value::Cell(r, c)) = stack.pop() {
-            let dependents = grid.get_node(r, c).dependents.clone();
+            let dependents = grid.dependents(r, c);
 
             for dep in dependents {
                 if let Value::Cell(dep_r, dep_c) = dep {
@@ -250,7 +311,7 @@ pub fn topological_sort(grid: &mut Grid, target: Value, stack: &mut Vec<Value>)
         }
         node.visited = true;
 
-        for dep in node.dependents.clone() {
+        for dep in grid.dependents(row, col) {
             topological_sort(grid, dep, stack);
             // if let Value::Cell(dep_row, dep_col) = dep {
             //     topological_sort(grid, Value::Cell(dep_row, dep_col), stack);
@@ -259,3 +320,97 @@ pub fn topological_sort(grid: &mut Grid, target: Value, stack: &mut Vec<Value>)
         stack.push(target);
     }
 }
+
+/// Clears every dependents list and rebuilds it from the stored functions.
+/// Used after structural edits that move cells around in the grid.
+pub fn rebuild_dependents(grid: &mut Grid) {
+    grid.line_readers.clear();
+    for row in grid.cells_vec.iter_mut() {
+        for node in row.iter_mut() {
+            node.dependents.clear();
+        }
+    }
+    for i in 0..grid.get_row_size() {
+        for j in 0..grid.get_column_size() {
+            let func = grid.get_node(i, j).function.clone();
+            if func.is_some() {
+                add_edges(grid, Value::Cell(i, j), func, true);
+            }
+        }
+    }
+}
+
+/// Returns every cell of the grid in topological order, so that a cell always
+/// comes after the cells it depends on.
+pub fn full_sequence(grid: &mut Grid) -> Vec<Value> {
+    let mut stack = Vec::new();
+    for i in 0..grid.get_row_size() {
+        for j in 0..grid.get_column_size() {
+            topological_sort(grid, Value::Cell(i, j), &mut stack);
+        }
+    }
+    stack.reverse();
+    for row in grid.cells_vec.iter_mut() {
+        for node in row.iter_mut() {
+            node.visited = false;
+        }
+    }
+    stack
+}
+
+/// Returns the cells of a range given as whole columns (`A:C`) or rows (`2:2`),
+/// bounded by the size of `grid`. `None` for any other pair of corners.
+pub fn line_corners(start: &Value, end: &Value, grid: &Grid) -> Option<(Value, Value)> {
+    let rows = grid.get_row_size() - 1;
+    let columns = grid.get_column_size() - 1;
+    match (start, end) {
+        (Value::Column(first), Value::Column(last)) => {
+            Some((Value::Cell(1, *first), Value::Cell(rows, *last)))
+        }
+        (Value::Row(first), Value::Row(last)) => {
+            Some((Value::Cell(*first, 1), Value::Cell(*last, columns)))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the cells read by `func`, including cells on other sheets (`Data!A1`).
+/// Ranges are spelled out cell by cell, with whole rows and columns bounded by `grid`.
+pub fn precedents(func: &Value, grid: &Grid) -> Vec<Value> {
+    let Value::Oper(box1, box2, oper) = func else {
+        return Vec::new();
+    };
+    if !oper.is_range() {
+        return [box1, box2]
+            .into_iter()
+            .flatten()
+            .filter(|side| matches!(***side, Value::Cell(_, _) | Value::SheetRef(_, _)))
+            .map(|side| (**side).clone())
+            .collect();
+    }
+    let (Some(start), Some(end)) = (box1.as_deref(), box2.as_deref()) else {
+        return Vec::new();
+    };
+    let (sheet, start, end) = match (start, end) {
+        (Value::SheetRef(sheet, start), Value::SheetRef(_, end)) => {
+            (Some(sheet), (**start).clone(), (**end).clone())
+        }
+        _ => match line_corners(start, end, grid) {
+            Some((start, end)) => (None, start, end),
+            None => (None, start.clone(), end.clone()),
+        },
+    };
+    if !matches!((&start, &end), (Value::Cell(_, _), Value::Cell(_, _))) {
+        return Vec::new();
+    }
+    let mut cells = Vec::new();
+    for i in start.row()..=end.row() {
+        for j in start.col()..=end.col() {
+            cells.push(match sheet {
+                Some(sheet) => Value::SheetRef(sheet.clone(), Box::new(Value::Cell(i, j))),
+                None => Value::Cell(i, j),
+            });
+        }
+    }
+    cells
+}
@@ -0,0 +1,168 @@
+/// Recording and replaying commands for `record` and `play`.
+///
+/// A macro is the list of edits made while it was recorded. Replaying it at
+/// another cell moves the relative cell references of every command, the way `copy`
+/// moves formulas, so a macro recorded on one block can be run on the next one.
+use crate::extension::backend::search::segments;
+use crate::extension::common::{MAX_COLUMNS, MAX_ROWS, column_decoder};
+use crate::extension::parser::parser::{TEXT_COMMANDS, column_number};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Macros by name, as stored with the sheet or in the user macro file.
+pub type Macros = BTreeMap<String, Vec<String>>;
+
+/// A macro being recorded with `record start`.
+#[derive(Debug, Clone)]
+pub struct Recording {
+    pub name: String,
+    pub commands: Vec<String>,
+    /// Save to the user macro file instead of the sheet
+    pub global: bool,
+}
+
+/// Returns true for the commands that manage macros, which are neither recorded nor
+/// replayed.
+pub fn is_macro_command(cmd: &str) -> bool {
+    matches!(
+        cmd.split_whitespace().next(),
+        Some("record" | "play" | "macros")
+    )
+}
+
+/// Returns true for commands whose arguments are text and are replayed unchanged.
+///
+/// `replace` is the exception: the references it rewrites move with the macro, so a
+/// replayed replace edits the formulas written by the same replay.
+fn is_text_command(cmd: &str) -> bool {
+    cmd.split_whitespace()
+        .next()
+        .is_some_and(|keyword| keyword != "replace" && TEXT_COMMANDS.contains(&keyword))
+}
+
+/// A cell reference split into its anchors, column and row, e.g. `$B7`.
+struct Reference<'a> {
+    sheet: Option<&'a str>,
+    column_anchored: bool,
+    column: usize,
+    row_anchored: bool,
+    row: usize,
+}
+
+impl<'a> Reference<'a> {
+    /// Parses a word of a command, `None` unless it is a cell such as `B7`, `$B$7` or `Data!B7`.
+    fn parse(word: &'a str) -> Option<Self> {
+        let (sheet, cell) = match word.rsplit_once('!') {
+            Some((sheet, cell)) => (Some(sheet), cell),
+            None => (None, word),
+        };
+        let (column_anchored, cell) = match cell.strip_prefix('$') {
+            Some(cell) => (true, cell),
+            None => (false, cell),
+        };
+        let split = cell.find(|c: char| !c.is_ascii_uppercase())?;
+        let (letters, digits) = cell.split_at(split);
+        let (row_anchored, digits) = match digits.strip_prefix('$') {
+            Some(digits) => (true, digits),
+            None => (false, digits),
+        };
+        if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        Some(Reference {
+            sheet,
+            column_anchored,
+            column: column_number(letters)?,
+            row_anchored,
+            row: digits.parse().ok()?,
+        })
+    }
+
+    fn to_text(&self) -> String {
+        let anchor = |anchored: bool| if anchored { "$" } else { "" };
+        let sheet = self.sheet.map(|sheet| format!("{}!", sheet));
+        format!(
+            "{}{}{}{}{}",
+            sheet.unwrap_or_default(),
+            anchor(self.column_anchored),
+            column_decoder(self.column),
+            anchor(self.row_anchored),
+            self.row
+        )
+    }
+}
+
+/// Top-left corner of the cells a macro refers to, as `(row, column)`.
+///
+/// This is the cell that `play NAME at CELL` moves to `CELL`; `None` when no command
+/// refers to a cell.
+pub fn origin(commands: &[String]) -> Option<(usize, usize)> {
+    let references: Vec<(usize, usize)> = commands
+        .iter()
+        .filter(|cmd| !is_text_command(cmd) && !is_macro_command(cmd))
+        .flat_map(|cmd| segments(cmd))
+        .filter_map(Reference::parse)
+        .map(|reference| (reference.row, reference.column))
+        .collect();
+    let row = references.iter().map(|&(row, _)| row).min()?;
+    let column = references.iter().map(|&(_, column)| column).min()?;
+    Some((row, column))
+}
+
+/// Moves the cell references of a command by `rows` and `columns`.
+///
+/// Parts anchored with `$` stay where they are, as with `copy`. Commands whose arguments
+/// are text, such as `save` or `find`, are returned unchanged. Returns `None` if a
+/// reference would move off the sheet.
+pub fn shift_command(cmd: &str, rows: isize, columns: isize) -> Option<String> {
+    if is_text_command(cmd) {
+        return Some(cmd.to_string());
+    }
+    let mut shifted = String::new();
+    for segment in segments(cmd) {
+        let Some(mut reference) = Reference::parse(segment) else {
+            shifted.push_str(segment);
+            continue;
+        };
+        if !reference.row_anchored {
+            reference.row = reference
+                .row
+                .checked_add_signed(rows)
+                .filter(|row| (1..=MAX_ROWS).contains(row))?;
+        }
+        if !reference.column_anchored {
+            reference.column = reference
+                .column
+                .checked_add_signed(columns)
+                .filter(|column| (1..=MAX_COLUMNS).contains(column))?;
+        }
+        shifted.push_str(&reference.to_text());
+    }
+    Some(shifted)
+}
+
+/// Location of the user macro file: `$SPREADSHEET_MACROS`, or `.spreadsheet_macros.json`
+/// in the home directory.
+pub fn default_macro_file() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("SPREADSHEET_MACROS") {
+        return Some(PathBuf::from(path));
+    }
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".spreadsheet_macros.json"))
+}
+
+/// Reads the user macro file; a file that does not exist yet holds no macros.
+pub fn read_macro_file(path: &Path) -> Result<Macros, String> {
+    match fs::read_to_string(path) {
+        Ok(text) => serde_json::from_str(&text)
+            .map_err(|e| format!("Invalid macro file {}: {}", path.display(), e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Macros::new()),
+        Err(e) => Err(format!("File read error: {}", e)),
+    }
+}
+
+/// Writes the user macro file.
+pub fn write_macro_file(path: &Path, macros: &Macros) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(macros).map_err(|e| e.to_string())?;
+    fs::write(path, text).map_err(|e| format!("File write error: {}", e))
+}
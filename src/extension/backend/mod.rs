@@ -1,4 +1,17 @@
 #![allow(clippy::module_inception)]
 pub mod backend;
+pub mod chart;
+pub mod export;
 pub mod functions;
 pub mod graph;
+pub mod macros;
+pub mod pixels;
+pub mod plot;
+/// Text matching for `find` and `replace`.
+///
+/// Formula text is split into words (references, names, numbers and function
+/// names, with their `$` anchors and sheet prefixes) and the symbols between
+/// them. A pattern only matches whole words, so `A1` finds `A1` and `$A$1`
+/// but not `A10` or `Data!A1`.
+pub mod search;
+pub mod structure;
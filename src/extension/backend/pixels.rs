@@ -0,0 +1,67 @@
+/// Conversion between sheets and raster images.
+///
+/// A sheet can be used as an image by storing `0xRRGGBB` colours in its cells,
+/// as the web image view does. This module writes a range out as a PNG or PPM
+/// file and reads an image back as a block of colour values.
+use crate::extension::backend::backend::Grid;
+use crate::extension::common::{Value, number_to_rgb, rgb_to_number};
+use image::{ImageFormat, Rgb, RgbImage};
+
+/// Colour of a cell, or white if it holds an error or a value that is not a colour.
+fn cell_colour(grid: &Grid, row: usize, col: usize) -> Rgb<u8> {
+    match grid.get_node_value(row, col).map(u32::try_from) {
+        Some(Ok(n)) => {
+            let (r, g, b) = number_to_rgb(n);
+            Rgb([r, g, b])
+        }
+        _ => Rgb([255, 255, 255]),
+    }
+}
+
+/// Picks the image format from the file extension; only PNG and PPM are supported.
+fn image_format(path: &str) -> Result<ImageFormat, String> {
+    match ImageFormat::from_path(path) {
+        Ok(ImageFormat::Png) => Ok(ImageFormat::Png),
+        Ok(ImageFormat::Pnm) => Ok(ImageFormat::Pnm),
+        _ => Err(format!("Unsupported image format: {}", path)),
+    }
+}
+
+/// Writes a range as an image, one `scale` x `scale` block of pixels per cell.
+pub fn export_image(
+    grid: &Grid,
+    start: &Value,
+    end: &Value,
+    path: &str,
+    scale: u32,
+) -> Result<(), String> {
+    let format = image_format(path)?;
+    let width = (end.col() - start.col() + 1) as u32 * scale;
+    let height = (end.row() - start.row() + 1) as u32 * scale;
+    let image = RgbImage::from_fn(width, height, |x, y| {
+        cell_colour(
+            grid,
+            start.row() + (y / scale) as usize,
+            start.col() + (x / scale) as usize,
+        )
+    });
+    image
+        .save_with_format(path, format)
+        .map_err(|e| format!("Image write error: {}", e))
+}
+
+/// Reads an image as rows of `0xRRGGBB` values, one per pixel.
+pub fn read_image(path: &str) -> Result<Vec<Vec<isize>>, String> {
+    let format = image_format(path)?;
+    let bytes = std::fs::read(path).map_err(|e| format!("File read error: {}", e))?;
+    let image = image::load_from_memory_with_format(&bytes, format)
+        .map_err(|e| format!("Image read error: {}", e))?
+        .to_rgb8();
+    Ok(image
+        .rows()
+        .map(|row| {
+            row.map(|Rgb([r, g, b])| rgb_to_number(*r, *g, *b) as isize)
+                .collect()
+        })
+        .collect())
+}
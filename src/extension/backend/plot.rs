@@ -0,0 +1,188 @@
+/// Text charts for the `plot` and `spark` commands.
+///
+/// Charts are drawn with block characters and returned as text for the terminal,
+/// so trends can be looked at without the web UI or an image viewer.
+use crate::extension::backend::backend::Grid;
+use crate::extension::common::{ChartKind, Value};
+
+/// Rows of a `plot` chart.
+pub const PLOT_HEIGHT: usize = 10;
+
+/// Blocks filling the lower eighths of a character, from one eighth to all of it.
+const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Reads the values of a range that is a single row or column.
+///
+/// Empty cells and cells holding an error are `None` and leave a gap in the chart.
+pub fn series(grid: &Grid, start: &Value, end: &Value) -> Result<Vec<Option<isize>>, String> {
+    if start.row() != end.row() && start.col() != end.col() {
+        return Err("Plot range must be one row or one column".to_string());
+    }
+    let mut values = Vec::new();
+    for i in start.row()..=end.row() {
+        for j in start.col()..=end.col() {
+            let node = &grid.cells_vec[i][j];
+            values.push(node.function.as_ref().and(node.get_node_value()));
+        }
+    }
+    if values.iter().all(Option::is_none) {
+        return Err("Plot range has no values".to_string());
+    }
+    Ok(values)
+}
+
+/// Smallest and largest of the values, `None` if there are none.
+fn bounds(values: &[Option<isize>]) -> Option<(isize, isize)> {
+    let values = values.iter().flatten();
+    Some((*values.clone().min()?, *values.max()?))
+}
+
+/// Draws the values as a one-line sparkline followed by their smallest, largest
+/// and last value, e.g. `▁▃▅█▆ min 1, max 9, last 7`.
+pub fn sparkline(values: &[Option<isize>]) -> String {
+    let Some((min, max)) = bounds(values) else {
+        return String::new();
+    };
+    let span = (max - min).max(1) as f64;
+    let line: String = values
+        .iter()
+        .map(|value| match value {
+            Some(value) => {
+                let level = ((value - min) as f64 / span * 7.0).round() as usize;
+                BLOCKS[level]
+            }
+            None => ' ',
+        })
+        .collect();
+    let last = values.iter().rev().flatten().next().copied().unwrap_or(min);
+    format!("{} min {}, max {}, last {}", line, min, max, last)
+}
+
+/// Character of a bar chart in the row covering `low..high` for a bar reaching `value`.
+///
+/// Bars grow up from zero, or down from it for negative values.
+fn bar_cell(value: isize, low: f64, high: f64) -> char {
+    let value = value as f64;
+    let (bottom, top) = if value >= 0.0 {
+        (0.0, value)
+    } else {
+        (value, 0.0)
+    };
+    let filled = (top.min(high) - bottom.max(low)).max(0.0) / (high - low);
+    if filled >= 1.0 - f64::EPSILON {
+        '█'
+    } else if value >= 0.0 {
+        // the bar ends inside this row, filled from the bottom
+        let eighths = (filled * 8.0).round() as usize;
+        if eighths == 0 {
+            ' '
+        } else {
+            BLOCKS[eighths - 1]
+        }
+    } else if filled >= 0.5 {
+        '▀'
+    } else if filled > 0.0 {
+        '▔'
+    } else {
+        ' '
+    }
+}
+
+/// Draws the values of `start:end` as a bar or line chart of `PLOT_HEIGHT` rows.
+///
+/// The y axis is labelled with the top and bottom of the scale (and zero when it lies
+/// in between), the x axis with the first and last cell. A line chart marks each value
+/// with `•` and joins neighbours with `│`.
+pub fn plot(values: &[Option<isize>], kind: &ChartKind, start: &Value, end: &Value) -> String {
+    let Some((min, max)) = bounds(values) else {
+        return String::new();
+    };
+    // bars grow from zero, a line only needs to span the values
+    let (low, high) = match kind {
+        ChartKind::Line => (min, max),
+        _ => (min.min(0), max.max(0)),
+    };
+    let high = high.max(low + 1);
+    let (top, step) = scale(low, high);
+    let row_of = |value: isize| {
+        let row = ((top - value as f64) / step).floor() as usize;
+        row.min(PLOT_HEIGHT - 1)
+    };
+    // the row whose bottom edge is zero
+    let zero_row = (low < 0 && high > 0).then(|| (top / step).round() as usize - 1);
+    let label = |row: usize| match row {
+        0 => Some(high.to_string()),
+        row if row == PLOT_HEIGHT - 1 => Some(low.to_string()),
+        row if Some(row) == zero_row => Some("0".to_string()),
+        _ => None,
+    };
+    let width = [high, low, 0]
+        .iter()
+        .map(|value| value.to_string().len())
+        .max()
+        .unwrap_or(1);
+
+    let mut grid = vec![vec![' '; values.len()]; PLOT_HEIGHT];
+    let mut previous: Option<usize> = None;
+    for (x, value) in values.iter().enumerate() {
+        let Some(value) = *value else {
+            previous = None;
+            continue;
+        };
+        match kind {
+            ChartKind::Line => {
+                let row = row_of(value);
+                if let Some(previous) = previous {
+                    for line in grid
+                        .iter_mut()
+                        .take(row.max(previous))
+                        .skip(row.min(previous) + 1)
+                    {
+                        line[x] = '│';
+                    }
+                }
+                grid[row][x] = '•';
+                previous = Some(row);
+            }
+            _ => {
+                for (row, line) in grid.iter_mut().enumerate() {
+                    let row_top = top - row as f64 * step;
+                    line[x] = bar_cell(value, row_top - step, row_top);
+                }
+            }
+        }
+    }
+
+    let mut lines = Vec::new();
+    for (row, line) in grid.iter().enumerate() {
+        let text: String = line.iter().collect();
+        let axis = if label(row).is_some() { '┤' } else { '│' };
+        let label = label(row).unwrap_or_default();
+        lines.push(format!("{:>width$} {}{}", label, axis, text.trim_end()));
+    }
+    lines.push(format!("{:>width$} └{}", "", "─".repeat(values.len())));
+    let (first, last) = (start.to_string(), end.to_string());
+    let gap = values.len().saturating_sub(first.len() + last.len());
+    let x_labels = if values.len() > 1 && gap > 0 {
+        format!("{}{}{}", first, " ".repeat(gap), last)
+    } else {
+        first
+    };
+    lines.push(format!("{:>width$}  {}", "", x_labels));
+    lines.join("\n")
+}
+
+/// Top of the scale `low..high` and the height of a row.
+///
+/// When the scale crosses zero the rows are split between the positive and negative
+/// side so that zero falls on the edge of a row, where bars start.
+fn scale(low: isize, high: isize) -> (f64, f64) {
+    if low >= 0 || high <= 0 {
+        return (high as f64, (high - low) as f64 / PLOT_HEIGHT as f64);
+    }
+    let above = (PLOT_HEIGHT as f64 * high as f64 / (high - low) as f64).round() as usize;
+    let above = above.clamp(1, PLOT_HEIGHT - 1);
+    let below = PLOT_HEIGHT - above;
+    let step = (high as f64 / above as f64).max(-low as f64 / below as f64);
+    (step * above as f64, step)
+}
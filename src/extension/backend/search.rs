@@ -0,0 +1,91 @@
+/// Characters that make up a word of formula text
+pub fn is_word(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '$' | '!')
+}
+
+/// Splits `text` into alternating runs of word and non-word characters.
+pub fn segments(text: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut word = text.starts_with(is_word);
+    for (i, c) in text.char_indices() {
+        if is_word(c) != word {
+            parts.push(&text[start..i]);
+            start = i;
+            word = !word;
+        }
+    }
+    if start < text.len() {
+        parts.push(&text[start..]);
+    }
+    parts
+}
+
+/// Compares two segments, ignoring the `$` anchors of words.
+fn same_segment(found: &str, wanted: &str) -> bool {
+    if found.starts_with(is_word) {
+        found.replace('$', "") == wanted.replace('$', "")
+    } else {
+        found == wanted
+    }
+}
+
+/// Writes `new` with the anchors of the reference `old`, so that replacing
+/// `A1` with `B2` turns `$A$1` into `$B$2`. Anything that is not a plain cell
+/// reference is left as written.
+fn reanchor(old: &str, new: &str) -> String {
+    let letters = new.chars().take_while(|c| c.is_ascii_alphabetic()).count();
+    let digits = &new[letters..];
+    if !old.contains('$')
+        || new.contains('$')
+        || letters == 0
+        || digits.is_empty()
+        || !digits.chars().all(|c| c.is_ascii_digit())
+    {
+        return new.to_string();
+    }
+    let column = if old.starts_with('$') { "$" } else { "" };
+    let row = if old[1..].contains('$') { "$" } else { "" };
+    format!("{}{}{}{}", column, &new[..letters], row, digits)
+}
+
+/// True when `pattern` matches the segments of `text` starting at `at`.
+fn matches_at(text: &[&str], at: usize, pattern: &[&str]) -> bool {
+    !pattern.is_empty()
+        && at + pattern.len() <= text.len()
+        && text[at..at + pattern.len()]
+            .iter()
+            .zip(pattern)
+            .all(|(found, wanted)| same_segment(found, wanted))
+}
+
+/// True when `formula` contains `pattern` as whole words.
+pub fn formula_contains(formula: &str, pattern: &str) -> bool {
+    let (text, pattern) = (segments(formula), segments(pattern));
+    (0..text.len()).any(|at| matches_at(&text, at, &pattern))
+}
+
+/// Replaces every whole-word occurrence of `old` in `formula` with `new`.
+/// Returns `None` when nothing matched.
+pub fn replace_in_formula(formula: &str, old: &str, new: &str) -> Option<String> {
+    let text = segments(formula);
+    let pattern = segments(old);
+    let mut result = String::new();
+    let mut replaced = false;
+    let mut i = 0;
+    while i < text.len() {
+        if matches_at(&text, i, &pattern) {
+            if pattern.len() == 1 {
+                result.push_str(&reanchor(text[i], new));
+            } else {
+                result.push_str(new);
+            }
+            i += pattern.len();
+            replaced = true;
+        } else {
+            result.push_str(text[i]);
+            i += 1;
+        }
+    }
+    replaced.then_some(result)
+}
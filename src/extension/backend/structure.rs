@@ -0,0 +1,320 @@
+/// Reference rewriting for edits that move formulas around the sheet.
+///
+/// Inserting or deleting a row/column moves every cell after the edit point by
+/// one, so stored functions are rewritten to follow them. Copying a formula
+/// shifts its relative references by the copy offset. References that end up
+/// nowhere become `Value::RefError`, which evaluates to an error and displays as `#REF!`.
+use crate::extension::common::{Anchor, Anchors, Value};
+
+/// Direction of a structural edit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Axis {
+    Row,
+    Column,
+}
+
+/// Position of a cell, or of a whole row/column, along `axis`.
+/// `None` for references the edit cannot move, such as a whole column during a row edit.
+fn position(cell: &Value, axis: Axis) -> Option<usize> {
+    match (cell, axis) {
+        (Value::Cell(row, _), Axis::Row) | (Value::Row(row), Axis::Row) => Some(*row),
+        (Value::Cell(_, col), Axis::Column) | (Value::Column(col), Axis::Column) => Some(*col),
+        _ => None,
+    }
+}
+
+/// Copy of `cell` moved to `new_position` along `axis`.
+fn moved(cell: &Value, axis: Axis, new_position: usize) -> Value {
+    match cell {
+        Value::Row(_) => Value::Row(new_position),
+        Value::Column(_) => Value::Column(new_position),
+        _ => {
+            let mut cell = cell.clone();
+            match axis {
+                Axis::Row => cell.assign_row(new_position),
+                Axis::Column => cell.assign_col(new_position),
+            }
+            cell
+        }
+    }
+}
+
+/// Where a single reference ends up after inserting (`insert`) or deleting
+/// the row/column `at`; deleting the referenced cell gives `RefError`.
+pub fn shift_cell(cell: &Value, axis: Axis, at: usize, insert: bool) -> Value {
+    let Some(pos) = position(cell, axis) else {
+        return cell.clone();
+    };
+    if insert {
+        if pos >= at {
+            return moved(cell, axis, pos + 1);
+        }
+    } else if pos == at {
+        return Value::RefError;
+    } else if pos > at {
+        return moved(cell, axis, pos - 1);
+    }
+    cell.clone()
+}
+
+/// Where a range ends up after the edit. Inserting inside a range widens it,
+/// deleting inside narrows it, and deleting all of it leaves `RefError` corners.
+pub fn shift_range(
+    start: &Value,
+    end: &Value,
+    axis: Axis,
+    at: usize,
+    insert: bool,
+) -> (Value, Value) {
+    let (Some(first), Some(last)) = (position(start, axis), position(end, axis)) else {
+        return (start.clone(), end.clone());
+    };
+    if insert || at < first || at > last {
+        return (
+            shift_cell(start, axis, at, insert),
+            shift_cell(end, axis, at, insert),
+        );
+    }
+    if first == last {
+        return (Value::RefError, Value::RefError);
+    }
+    // the start stays put while the rows/columns after it close the gap
+    (start.clone(), moved(end, axis, last - 1))
+}
+
+/// Rewrites every reference in a stored function for the edit.
+pub fn shift_function(func: &Value, axis: Axis, at: usize, insert: bool) -> Value {
+    match func {
+        Value::Oper(Some(box1), Some(box2), oper) if oper.is_range() => {
+            let (start, end) = shift_range(box1, box2, axis, at, insert);
+            Value::Oper(Some(Box::new(start)), Some(Box::new(end)), oper.clone())
+        }
+        Value::Oper(box1, box2, oper) => {
+            let shift = |side: &Option<Box<Value>>| {
+                side.as_ref()
+                    .map(|value| Box::new(shift_cell(value, axis, at, insert)))
+            };
+            Value::Oper(shift(box1), shift(box2), oper.clone())
+        }
+        _ => shift_cell(func, axis, at, insert),
+    }
+}
+
+/// Rewrites the references to sheet `sheet` in a function stored on another sheet.
+pub fn shift_sheet_refs(func: &Value, sheet: &str, axis: Axis, at: usize, insert: bool) -> Value {
+    let wrap = |cell: Value| match cell {
+        Value::RefError => Value::RefError,
+        cell => Value::SheetRef(sheet.to_string(), Box::new(cell)),
+    };
+    match func {
+        Value::Oper(Some(box1), Some(box2), oper) if oper.is_range() => {
+            if let (Value::SheetRef(name, start), Value::SheetRef(_, end)) = (&**box1, &**box2)
+                && name == sheet
+            {
+                let (start, end) = shift_range(start, end, axis, at, insert);
+                return Value::Oper(
+                    Some(Box::new(wrap(start))),
+                    Some(Box::new(wrap(end))),
+                    oper.clone(),
+                );
+            }
+            func.clone()
+        }
+        Value::Oper(box1, box2, oper) => {
+            let shift = |side: &Option<Box<Value>>| {
+                side.as_ref().map(|value| match &**value {
+                    Value::SheetRef(name, cell) if name == sheet => {
+                        Box::new(wrap(shift_cell(cell, axis, at, insert)))
+                    }
+                    _ => value.clone(),
+                })
+            };
+            Value::Oper(shift(box1), shift(box2), oper.clone())
+        }
+        _ => func.clone(),
+    }
+}
+
+/// Where a reference lands when its formula is copied `d_row`/`d_col` cells away.
+/// Anchored parts stay put; landing outside the sheet gives `RefError`.
+fn offset_cell(
+    cell: &Value,
+    anchor: Anchor,
+    (d_row, d_col): (isize, isize),
+    (rows, columns): (usize, usize),
+) -> Value {
+    let (row, col) = match cell {
+        Value::Cell(row, col) => (row, col),
+        // a whole row or column only moves along its own axis
+        Value::Row(row) => {
+            return match offset_cell(&Value::Cell(*row, 1), anchor, (d_row, 0), (rows, columns)) {
+                Value::Cell(row, _) => Value::Row(row),
+                other => other,
+            };
+        }
+        Value::Column(col) => {
+            return match offset_cell(&Value::Cell(1, *col), anchor, (0, d_col), (rows, columns)) {
+                Value::Cell(_, col) => Value::Column(col),
+                other => other,
+            };
+        }
+        _ => return cell.clone(),
+    };
+    let row = if anchor.row {
+        *row as isize
+    } else {
+        *row as isize + d_row
+    };
+    let col = if anchor.col {
+        *col as isize
+    } else {
+        *col as isize + d_col
+    };
+    if row < 1 || col < 1 || row as usize > rows || col as usize > columns {
+        return Value::RefError;
+    }
+    Value::Cell(row as usize, col as usize)
+}
+
+/// Rewrites a function for a copy `offset` (rows, columns) away from where it was written,
+/// in a sheet of `size` (rows, columns).
+pub fn offset_function(
+    func: &Value,
+    anchors: &Anchors,
+    offset: (isize, isize),
+    size: (usize, usize),
+) -> Value {
+    match func {
+        Value::Oper(Some(box1), Some(box2), oper) if oper.is_range() => {
+            let start = offset_cell(box1, anchors[0], offset, size);
+            let end = offset_cell(box2, anchors[1], offset, size);
+            // a mix of anchored and relative corners can swap over
+            let (start, end) = match (start, end) {
+                (Value::RefError, _) | (_, Value::RefError) => (Value::RefError, Value::RefError),
+                (Value::Cell(r1, c1), Value::Cell(r2, c2)) => (
+                    Value::Cell(r1.min(r2), c1.min(c2)),
+                    Value::Cell(r1.max(r2), c1.max(c2)),
+                ),
+                (Value::Row(first), Value::Row(last)) => {
+                    (Value::Row(first.min(last)), Value::Row(first.max(last)))
+                }
+                (Value::Column(first), Value::Column(last)) => (
+                    Value::Column(first.min(last)),
+                    Value::Column(first.max(last)),
+                ),
+                // ranges on other sheets are not shifted
+                corners => corners,
+            };
+            Value::Oper(Some(Box::new(start)), Some(Box::new(end)), oper.clone())
+        }
+        Value::Oper(box1, box2, oper) => {
+            let shift = |side: &Option<Box<Value>>, anchor| {
+                side.as_ref()
+                    .map(|value| Box::new(offset_cell(value, anchor, offset, size)))
+            };
+            Value::Oper(
+                shift(box1, anchors[0]),
+                shift(box2, anchors[1]),
+                oper.clone(),
+            )
+        }
+        _ => offset_cell(func, Anchor::default(), offset, size),
+    }
+}
+
+/// Returns true if `cell` lies inside the block `start:end`.
+pub fn in_block(cell: &Value, start: &Value, end: &Value) -> bool {
+    matches!(cell, Value::Cell(_, _))
+        && (start.row()..=end.row()).contains(&cell.row())
+        && (start.col()..=end.col()).contains(&cell.col())
+}
+
+/// Where a reference points after the block `start:end` moves by `offset`.
+/// Cells of the block follow it; other cells it lands on are overwritten, so
+/// references to them become `RefError`.
+fn moved_cell(cell: &Value, (start, end): (&Value, &Value), offset: (isize, isize)) -> Value {
+    let target = |cell: &Value| {
+        Value::Cell(
+            (cell.row() as isize + offset.0) as usize,
+            (cell.col() as isize + offset.1) as usize,
+        )
+    };
+    if in_block(cell, start, end) {
+        target(cell)
+    } else if in_block(cell, &target(start), &target(end)) {
+        Value::RefError
+    } else {
+        cell.clone()
+    }
+}
+
+/// Rewrites a function for moving the block `start:end` by `offset`.
+///
+/// Ranges follow the block only when they lie entirely inside it; a range that
+/// just overlaps the block keeps reading the same cells.
+pub fn move_function(func: &Value, block: (&Value, &Value), offset: (isize, isize)) -> Value {
+    match func {
+        Value::Oper(Some(box1), Some(box2), oper) if oper.is_range() => {
+            let (start, end) =
+                if in_block(box1, block.0, block.1) && in_block(box2, block.0, block.1) {
+                    (
+                        moved_cell(box1, block, offset),
+                        moved_cell(box2, block, offset),
+                    )
+                } else {
+                    (*box1.clone(), *box2.clone())
+                };
+            Value::Oper(Some(Box::new(start)), Some(Box::new(end)), oper.clone())
+        }
+        Value::Oper(box1, box2, oper) => {
+            let shift = |side: &Option<Box<Value>>| {
+                side.as_ref()
+                    .map(|value| Box::new(moved_cell(value, block, offset)))
+            };
+            Value::Oper(shift(box1), shift(box2), oper.clone())
+        }
+        _ => moved_cell(func, block, offset),
+    }
+}
+
+/// Rewrites the references to sheet `sheet` in a function stored on another sheet
+/// for moving the block `start:end` of that sheet by `offset`.
+pub fn move_sheet_refs(
+    func: &Value,
+    sheet: &str,
+    block: (&Value, &Value),
+    offset: (isize, isize),
+) -> Value {
+    let wrap = |cell: Value| match cell {
+        Value::RefError => Value::RefError,
+        cell => Value::SheetRef(sheet.to_string(), Box::new(cell)),
+    };
+    match func {
+        Value::Oper(Some(box1), Some(box2), oper) if oper.is_range() => {
+            if let (Value::SheetRef(name, start), Value::SheetRef(_, end)) = (&**box1, &**box2)
+                && name == sheet
+                && in_block(start, block.0, block.1)
+                && in_block(end, block.0, block.1)
+            {
+                return Value::Oper(
+                    Some(Box::new(wrap(moved_cell(start, block, offset)))),
+                    Some(Box::new(wrap(moved_cell(end, block, offset)))),
+                    oper.clone(),
+                );
+            }
+            func.clone()
+        }
+        Value::Oper(box1, box2, oper) => {
+            let shift = |side: &Option<Box<Value>>| {
+                side.as_ref().map(|value| match &**value {
+                    Value::SheetRef(name, cell) if name == sheet => {
+                        Box::new(wrap(moved_cell(cell, block, offset)))
+                    }
+                    _ => value.clone(),
+                })
+            };
+            Value::Oper(shift(box1), shift(box2), oper.clone())
+        }
+        _ => func.clone(),
+    }
+}
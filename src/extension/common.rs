@@ -1,4 +1,12 @@
 use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Largest sheet the parser can address: rows 1-999 and columns A-ZZZ.
+pub const MAX_ROWS: usize = 999;
+pub const MAX_COLUMNS: usize = 18278;
+
+/// Largest number of image pixels drawn for a single cell by `export image`.
+pub const MAX_SCALE: u32 = 64;
 
 /// Represents a value that can appear in a spreadsheet.
 //Clone required for `Vec<Value>`(in graph.rs) to implement `Clone`
@@ -7,6 +15,11 @@ pub enum Value {
     Cell(usize, usize),
     Const(isize),
     Oper(Option<Box<Value>>, Option<Box<Value>>, Operation), //value1 and value2, and the operation or command, respectively
+    RefError,                     //reference to a cell that has been deleted
+    SheetRef(String, Box<Value>), //reference to a cell on another sheet, e.g. Data!A1
+    Name(String), //name given with `define`, replaced by what it stands for before a formula is stored
+    Column(usize), //whole column as a range corner, as in SUM(A:A)
+    Row(usize),   //whole row as a range corner, as in MAX(3:3)
 }
 
 impl Value {
@@ -36,6 +49,154 @@ impl Value {
             *col = new_col;
         }
     }
+
+    /// returns true if the value or any of its operands is a deleted reference
+    pub fn has_ref_error(&self) -> bool {
+        match self {
+            Value::RefError => true,
+            Value::SheetRef(_, cell) => cell.has_ref_error(),
+            Value::Oper(box1, box2, _) => {
+                box1.as_deref().is_some_and(Value::has_ref_error)
+                    || box2.as_deref().is_some_and(Value::has_ref_error)
+            }
+            _ => false,
+        }
+    }
+
+    /// returns true if the value or any of its operands refers to another sheet
+    pub fn has_sheet_ref(&self) -> bool {
+        match self {
+            Value::SheetRef(_, _) => true,
+            Value::Oper(box1, box2, _) => {
+                box1.as_deref().is_some_and(Value::has_sheet_ref)
+                    || box2.as_deref().is_some_and(Value::has_sheet_ref)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Marks which parts of a cell reference are absolute (`$A$1`, `A$1`, `$A1`).
+///
+/// Absolute parts stay fixed when a formula is copied to another cell.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Anchor {
+    pub row: bool,
+    pub col: bool,
+}
+
+/// Anchors for the two operands of a formula, in the same order as the boxes of `Value::Oper`.
+pub type Anchors = [Anchor; 2];
+
+/// Names written in place of the two operands of a formula, in the same order as the
+/// boxes of `Value::Oper`. For a range function both entries hold the name of the whole range.
+pub type Names = [Option<String>; 2];
+
+/// What a name given with `define` stands for.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum NameValue {
+    Const(isize),
+    Range(String, Box<Value>, Box<Value>), //sheet, top-left and bottom-right cells
+}
+
+impl fmt::Display for NameValue {
+    /// Writes the definition as it would be typed, e.g. `18` or `Sheet1!B2:B365`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NameValue::Const(val) => write!(f, "{}", val),
+            NameValue::Range(sheet, start, end) if start == end => {
+                write!(f, "{}!{}", sheet, start)
+            }
+            NameValue::Range(sheet, start, end) => write!(f, "{}!{}:{}", sheet, start, end),
+        }
+    }
+}
+
+/// Puts the names a formula was written with back in place of the references
+/// they were replaced by, for display.
+pub fn with_names(func: &Value, names: &Names) -> Value {
+    let Value::Oper(box1, box2, oper) = func else {
+        return func.clone();
+    };
+    let named = |name: &String| Some(Box::new(Value::Name(name.clone())));
+    if oper.is_range() {
+        if let Some(name) = &names[0] {
+            return Value::Oper(named(name), named(name), oper.clone());
+        }
+        return func.clone();
+    }
+    let side = |value: &Option<Box<Value>>, name: &Option<String>| match name {
+        Some(name) => named(name),
+        None => value.clone(),
+    };
+    Value::Oper(side(box1, &names[0]), side(box2, &names[1]), oper.clone())
+}
+
+/// Writes a formula back as source text, adding `$` for the anchored parts of its references.
+pub fn format_formula(func: &Value, anchors: &Anchors) -> String {
+    let reference = |value: &Value, anchor: Anchor| match value {
+        Value::Cell(row, col) => format!(
+            "{}{}{}{}",
+            if anchor.col { "$" } else { "" },
+            column_decoder(*col),
+            if anchor.row { "$" } else { "" },
+            row
+        ),
+        Value::Const(val) => val.to_string(),
+        Value::RefError => "#REF!".to_string(),
+        Value::Name(name) => name.clone(),
+        Value::Column(col) => format!(
+            "{}{}",
+            if anchor.col { "$" } else { "" },
+            column_decoder(*col)
+        ),
+        Value::Row(row) => format!("{}{}", if anchor.row { "$" } else { "" }, row),
+        other => format_formula(other, &Anchors::default()),
+    };
+    let reference = |value: &Value, anchor: Anchor| match value {
+        Value::SheetRef(sheet, cell) => format!("{}!{}", sheet, reference(cell, anchor)),
+        other => reference(other, anchor),
+    };
+    match func {
+        Value::Oper(box1, box2, oper) => {
+            let operand = |side: &Option<Box<Value>>, anchor| match side {
+                Some(value) => reference(value, anchor),
+                None => String::new(),
+            };
+            let (left, right) = (operand(box1, anchors[0]), operand(box2, anchors[1]));
+            let range = match (box1.as_deref(), box2.as_deref()) {
+                _ if left == right && left == "#REF!" => left.clone(),
+                (Some(Value::Name(_)), Some(Value::Name(_))) => left.clone(),
+                // the sheet of a range is written once, as in SUM(Data!A1:A9)
+                (Some(Value::SheetRef(_, _)), Some(Value::SheetRef(_, end))) => {
+                    format!("{}:{}", left, reference(end, anchors[1]))
+                }
+                _ => format!("{}:{}", left, right),
+            };
+            match oper {
+                Operation::Cons => left,
+                Operation::Add => format!("{}+{}", left, right),
+                Operation::Sub => format!("{}-{}", left, right),
+                Operation::Mul => format!("{}*{}", left, right),
+                Operation::Div => format!("{}/{}", left, right),
+                Operation::Slp => format!("SLEEP({})", left),
+                Operation::Min => format!("MIN({})", range),
+                Operation::Max => format!("MAX({})", range),
+                Operation::Avg => format!("AVG({})", range),
+                Operation::Sum => format!("SUM({})", range),
+                Operation::Std => format!("STDEV({})", range),
+                _ => format!("{:?}", oper),
+            }
+        }
+        _ => reference(func, Anchor::default()),
+    }
+}
+
+impl fmt::Display for Value {
+    /// Writes the value back as formula source text, e.g. `A1+3` or `SUM(A1:B5)`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", format_formula(self, &Anchors::default()))
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -65,7 +226,263 @@ pub enum Operation {
     Quit,
     Web(String),
     Save(String),
+    Open(String),
+    Chart(ChartSpec),
+    Plot(ChartKind),
+    Spark,
+    ExportImage(String, u32),
+    ImportImage(String),
+    ExportMarkdown(String, bool),
+    ExportHtml(String, bool),
+    Copy,
+    Move,
+    Sort(Vec<SortKey>),
+    AddSheet(String),
+    RenameSheet(String, String),
+    DeleteSheet(String),
+    SwitchSheet(String),
+    ListSheets,
+    Define(String),
+    ListNames,
+    Find(String),
+    Replace(String, String),
+    Show,
+    Trace,
+    Help(Option<String>),
+    RecordStart(String, bool),
+    RecordStop,
+    Play(String),
+    ListMacros,
+    Format(Option<NumberFormat>),
+    View(ViewChange),
+    Clear,
+    FillDown,
+    FillRight,
+    Resize(usize, usize),
+    InsertRow(usize),
+    DeleteRow(usize),
+    InsertCol(usize),
+    DeleteCol(usize),
     Undo,
     Redo,
     WebStart,
 }
+
+impl Operation {
+    /// returns true for the functions that take a range of cells, like SUM(A1:B5)
+    pub fn is_range(&self) -> bool {
+        matches!(
+            self,
+            Operation::Min | Operation::Max | Operation::Avg | Operation::Sum | Operation::Std
+        )
+    }
+
+    /// returns true for everything that can be assigned to a cell, like A1+2 or SUM(A1:B5)
+    pub fn is_formula(&self) -> bool {
+        self.is_range()
+            || matches!(
+                self,
+                Operation::Cons
+                    | Operation::Add
+                    | Operation::Sub
+                    | Operation::Mul
+                    | Operation::Div
+                    | Operation::Slp
+            )
+    }
+
+    /// returns true for the commands that change the workbook, which are the ones recorded
+    /// into macros; undo and redo are left out since they depend on what came before
+    pub fn is_edit(&self) -> bool {
+        self.is_formula()
+            || matches!(
+                self,
+                Operation::ImportImage(_)
+                    | Operation::Copy
+                    | Operation::Move
+                    | Operation::Sort(_)
+                    | Operation::AddSheet(_)
+                    | Operation::RenameSheet(_, _)
+                    | Operation::DeleteSheet(_)
+                    | Operation::SwitchSheet(_)
+                    | Operation::Define(_)
+                    | Operation::Replace(_, _)
+                    | Operation::Format(_)
+                    | Operation::Clear
+                    | Operation::FillDown
+                    | Operation::FillRight
+                    | Operation::Resize(_, _)
+                    | Operation::InsertRow(_)
+                    | Operation::DeleteRow(_)
+                    | Operation::InsertCol(_)
+                    | Operation::DeleteCol(_)
+            )
+    }
+}
+
+/// Kind of chart drawn by the `chart` command.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ChartKind {
+    Line,
+    Bar,
+    Scatter,
+}
+
+/// Options of the `chart` command; the plotted range travels in the `Oper` boxes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChartSpec {
+    pub kind: ChartKind,
+    /// Output file, `.svg` or `.png`
+    pub path: String,
+    pub title: String,
+    pub x_label: String,
+    pub y_label: String,
+}
+
+/// Change to the terminal view asked for by a `set` command.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ViewChange {
+    /// `set view 25x8`: rows and columns shown
+    Size(usize, usize),
+    /// `set view auto`: fit the view to the terminal
+    Auto,
+    /// `set width B 20`: width of a column
+    Width(usize, usize),
+    /// `show_formulas on|off`: show formulas in place of values
+    Formulas(bool),
+}
+
+/// Display format of a cell's value, set with the `format` command. The stored
+/// value is never changed, only the way it is written.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum NumberFormat {
+    /// `1,234,567`
+    Thousands,
+    /// A fixed number of decimals, `1234.00`
+    Fixed(usize),
+    /// The value read as a number of percent, `25%`
+    Percent,
+    /// A currency symbol and thousands separators, `$1,234`
+    Currency(String),
+    /// Three significant digits and an exponent, `1.23E+06`
+    Scientific,
+    /// The value read as days since 1899-12-30, like dates in other spreadsheets,
+    /// written as `2023-03-15`
+    Date,
+}
+
+impl NumberFormat {
+    /// Writes `value` in this format.
+    pub fn apply(&self, value: isize) -> String {
+        let sign = if value < 0 { "-" } else { "" };
+        match self {
+            NumberFormat::Thousands => format!("{}{}", sign, group_thousands(value.unsigned_abs())),
+            NumberFormat::Fixed(0) => value.to_string(),
+            NumberFormat::Fixed(decimals) => format!("{}.{}", value, "0".repeat(*decimals)),
+            NumberFormat::Percent => format!("{}%", value),
+            NumberFormat::Currency(symbol) => format!(
+                "{}{}{}",
+                sign,
+                symbol,
+                group_thousands(value.unsigned_abs())
+            ),
+            NumberFormat::Scientific => {
+                let text = format!("{:.2e}", value as f64);
+                let (mantissa, exponent) = text.split_once('e').unwrap_or((&text, "0"));
+                let exponent: i32 = exponent.parse().unwrap_or(0);
+                let exponent_sign = if exponent < 0 { '-' } else { '+' };
+                format!("{}E{}{:02}", mantissa, exponent_sign, exponent.abs())
+            }
+            NumberFormat::Date => {
+                let (year, month, day) = civil_date(value as i64 - 25569);
+                format!("{:04}-{:02}-{:02}", year, month, day)
+            }
+        }
+    }
+}
+
+impl fmt::Display for NumberFormat {
+    /// Writes the format as it is typed after `format A1:B2`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NumberFormat::Thousands => write!(f, "thousands"),
+            NumberFormat::Fixed(decimals) => write!(f, "fixed {}", decimals),
+            NumberFormat::Percent => write!(f, "percent"),
+            NumberFormat::Currency(symbol) => write!(f, "currency {}", symbol),
+            NumberFormat::Scientific => write!(f, "scientific"),
+            NumberFormat::Date => write!(f, "date"),
+        }
+    }
+}
+
+/// Writes `n` with a comma between each group of three digits.
+fn group_thousands(n: usize) -> String {
+    let digits = n.to_string();
+    let mut grouped = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/// Converts a number of days since 1970-01-01 into a (year, month, day) date.
+fn civil_date(days: i64) -> (i64, i64, i64) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// One key of the `sort` command: a sheet column and its direction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SortKey {
+    pub col: usize,
+    pub descending: bool,
+}
+
+/// Converts a column number to an Excel-style column label.
+///
+/// # Arguments
+///
+/// * `j` - The column number (1-based index) to convert
+///
+/// # Returns
+///
+/// A String representation of the column (e.g., 1 -> "A", 27 -> "AA")
+pub fn column_decoder(mut j: usize) -> String {
+    let mut cc = Vec::new();
+    while j > 0 {
+        j -= 1;
+        cc.push((b'A' + (j % 26) as u8) as char);
+        j /= 26;
+    }
+    cc.reverse();
+    cc.into_iter().collect()
+}
+
+/// Splits a cell value into the red, green and blue bytes of `0xRRGGBB`.
+pub fn number_to_rgb(n: u32) -> (u8, u8, u8) {
+    let r = (n >> 16) & 0xFF;
+    let g = (n >> 8) & 0xFF;
+    let b = n & 0xFF;
+    (r as u8, g as u8, b as u8)
+}
+
+/// Packs red, green and blue bytes into a `0xRRGGBB` cell value.
+pub fn rgb_to_number(r: u8, g: u8, b: u8) -> u32 {
+    ((r as u32) << 16) | ((g as u32) << 8) | b as u32
+}
@@ -0,0 +1,427 @@
+/// Line editing for the terminal prompt.
+///
+/// Keeps the line being typed with a cursor that the arrow keys move, a command
+/// history browsed with Up and Down and saved to a file between sessions, a
+/// reverse search through that history (Ctrl-R) and tab completion of command
+/// keywords and function names. Key handling is split from the terminal so that
+/// `handle_key` can be used without one.
+use crate::extension::parser::parser::{COMMANDS, FUNCTIONS};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::Print;
+use crossterm::{cursor, queue, terminal};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+/// Most commands kept in the history
+const HISTORY_LIMIT: usize = 1000;
+
+/// What a key press did to the line.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Edit {
+    /// The line changed or the cursor moved
+    Continue,
+    /// Tab found several completions; they are listed below the line
+    Candidates(Vec<String>),
+    /// Enter was pressed on this line
+    Submit(String),
+    /// Ctrl-D on an empty line
+    Eof,
+}
+
+/// A line being typed, with the history it can be recalled from.
+pub struct Editor {
+    /// Characters of the line
+    pub line: Vec<char>,
+    /// Position of the cursor in `line`
+    pub cursor: usize,
+    /// Earlier commands, oldest first
+    pub history: Vec<String>,
+    /// File the history is saved to, if any
+    path: Option<PathBuf>,
+    /// Entry of `history` shown while browsing with Up and Down
+    browsing: Option<usize>,
+    /// Line typed before browsing started, given back by Down
+    draft: Vec<char>,
+    /// Text searched for with Ctrl-R, while searching
+    search: Option<String>,
+    /// Entry of `history` matching the search
+    found: Option<usize>,
+}
+
+impl Editor {
+    /// Creates an editor with the history saved in `path`, if given.
+    pub fn new(path: Option<PathBuf>) -> Self {
+        let history = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|text| text.lines().map(str::to_string).collect::<Vec<_>>())
+            .unwrap_or_default();
+        let mut editor = Editor {
+            line: Vec::new(),
+            cursor: 0,
+            history: Vec::new(),
+            path,
+            browsing: None,
+            draft: Vec::new(),
+            search: None,
+            found: None,
+        };
+        if history.len() > HISTORY_LIMIT {
+            editor.history = history[history.len() - HISTORY_LIMIT..].to_vec();
+            editor.save_history();
+        } else {
+            editor.history = history;
+        }
+        editor
+    }
+
+    /// History file: `$SPREADSHEET_HISTORY`, or `.spreadsheet_history` in the home directory.
+    pub fn default_history_path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os("SPREADSHEET_HISTORY") {
+            return Some(PathBuf::from(path));
+        }
+        std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".spreadsheet_history"))
+    }
+
+    /// Adds a command to the history and to the history file. Empty lines and
+    /// repeats of the last command are skipped.
+    pub fn add_history(&mut self, line: &str) {
+        if line.trim().is_empty() || self.history.last().is_some_and(|last| last == line) {
+            return;
+        }
+        self.history.push(line.to_string());
+        if self.history.len() > HISTORY_LIMIT {
+            self.history.remove(0);
+            self.save_history();
+        } else if let Some(path) = &self.path
+            && let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path)
+        {
+            let _ = writeln!(file, "{}", line);
+        }
+    }
+
+    /// Rewrites the history file with the current history.
+    fn save_history(&self) {
+        if let Some(path) = &self.path {
+            let mut text = self.history.join("\n");
+            text.push('\n');
+            let _ = fs::write(path, text);
+        }
+    }
+
+    /// Text shown after the prompt and the cursor column within it.
+    pub fn display(&self) -> (String, usize) {
+        match &self.search {
+            Some(query) => {
+                let found = self.found.map_or("", |index| &self.history[index]);
+                let text = format!("(reverse-i-search)`{}': {}", query, found);
+                let column = text.chars().count();
+                (text, column)
+            }
+            None => (self.line.iter().collect(), self.cursor),
+        }
+    }
+
+    /// Handles one key press.
+    pub fn handle_key(&mut self, key: KeyEvent) -> Edit {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        if self.search.is_some()
+            && let Some(edit) = self.handle_search_key(key, ctrl)
+        {
+            return edit;
+        }
+        match key.code {
+            KeyCode::Enter => {
+                let line = self.line.iter().collect();
+                self.line.clear();
+                self.cursor = 0;
+                self.browsing = None;
+                return Edit::Submit(line);
+            }
+            KeyCode::Char('d') if ctrl => {
+                if self.line.is_empty() {
+                    return Edit::Eof;
+                }
+                if self.cursor < self.line.len() {
+                    self.line.remove(self.cursor);
+                }
+            }
+            KeyCode::Char('c') if ctrl => {
+                self.line.clear();
+                self.cursor = 0;
+                self.browsing = None;
+            }
+            KeyCode::Char('a') if ctrl => self.cursor = 0,
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::Char('e') if ctrl => self.cursor = self.line.len(),
+            KeyCode::End => self.cursor = self.line.len(),
+            KeyCode::Char('b') if ctrl => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Char('f') if ctrl => self.cursor = (self.cursor + 1).min(self.line.len()),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(self.line.len()),
+            KeyCode::Char('u') if ctrl => {
+                self.line.drain(..self.cursor);
+                self.cursor = 0;
+            }
+            KeyCode::Char('k') if ctrl => self.line.truncate(self.cursor),
+            KeyCode::Char('w') if ctrl => {
+                let mut start = self.cursor;
+                while start > 0 && self.line[start - 1] == ' ' {
+                    start -= 1;
+                }
+                while start > 0 && self.line[start - 1] != ' ' {
+                    start -= 1;
+                }
+                self.line.drain(start..self.cursor);
+                self.cursor = start;
+            }
+            KeyCode::Char('r') if ctrl => {
+                self.search = Some(String::new());
+                self.found = None;
+            }
+            KeyCode::Char('p') if ctrl => self.recall(-1),
+            KeyCode::Up => self.recall(-1),
+            KeyCode::Char('n') if ctrl => self.recall(1),
+            KeyCode::Down => self.recall(1),
+            KeyCode::Tab => return self.complete(),
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.line.remove(self.cursor);
+            }
+            KeyCode::Delete if self.cursor < self.line.len() => {
+                self.line.remove(self.cursor);
+            }
+            KeyCode::Char(c) if !ctrl => {
+                self.line.insert(self.cursor, c);
+                self.cursor += 1;
+            }
+            _ => {}
+        }
+        Edit::Continue
+    }
+
+    /// Keys typed during a reverse search. Returns `None` for keys that end the search
+    /// and are then handled on the found line, like the arrow keys.
+    fn handle_search_key(&mut self, key: KeyEvent, ctrl: bool) -> Option<Edit> {
+        let query = self.search.as_mut()?;
+        match key.code {
+            // search further back
+            KeyCode::Char('r') if ctrl => {
+                let before = self.found.unwrap_or(self.history.len());
+                self.find_before(before);
+            }
+            KeyCode::Char('g') if ctrl => self.search = None,
+            KeyCode::Esc => self.search = None,
+            KeyCode::Backspace => {
+                query.pop();
+                self.find_before(self.history.len());
+            }
+            KeyCode::Char(c) if !ctrl => {
+                query.push(c);
+                // the current match may still fit the longer text
+                let before = self.found.map_or(self.history.len(), |index| index + 1);
+                self.find_before(before);
+            }
+            _ => {
+                if let Some(index) = self.found {
+                    self.line = self.history[index].chars().collect();
+                    self.cursor = self.line.len();
+                }
+                self.search = None;
+                return None;
+            }
+        }
+        Some(Edit::Continue)
+    }
+
+    /// Finds the newest history entry before `before` that contains the search text.
+    /// The previous match is kept when there is none.
+    fn find_before(&mut self, before: usize) {
+        let Some(query) = &self.search else {
+            return;
+        };
+        if let Some(index) = (0..before)
+            .rev()
+            .find(|&index| self.history[index].contains(query.as_str()))
+        {
+            self.found = Some(index);
+        }
+    }
+
+    /// Shows an older (`step` -1) or newer (`step` 1) history entry. Going past the
+    /// newest entry gives back the line that was being typed.
+    fn recall(&mut self, step: isize) {
+        let next = match (self.browsing, step < 0) {
+            (None, true) if !self.history.is_empty() => {
+                self.draft = self.line.clone();
+                Some(self.history.len() - 1)
+            }
+            (Some(index), true) => Some(index.saturating_sub(1)),
+            (Some(index), false) if index + 1 < self.history.len() => Some(index + 1),
+            (Some(_), false) => None,
+            _ => return,
+        };
+        self.browsing = next;
+        self.line = match next {
+            Some(index) => self.history[index].chars().collect(),
+            None => std::mem::take(&mut self.draft),
+        };
+        self.cursor = self.line.len();
+    }
+
+    /// Completes the word before the cursor: a command keyword at the start of the
+    /// line, or a function name in a formula.
+    fn complete(&mut self) -> Edit {
+        let line: String = self.line.iter().collect();
+        let cursor_byte = line
+            .char_indices()
+            .nth(self.cursor)
+            .map_or(line.len(), |(i, _)| i);
+        let (start, candidates) = completions(&line[..cursor_byte]);
+        let start = line[..start].chars().count();
+        let word_length = self.cursor - start;
+        let replacement = match candidates.as_slice() {
+            [] => return Edit::Continue,
+            [only] => only.clone(),
+            _ => {
+                let prefix = common_prefix(&candidates);
+                if prefix.chars().count() <= word_length {
+                    return Edit::Candidates(candidates);
+                }
+                prefix
+            }
+        };
+        self.line.splice(start..self.cursor, replacement.chars());
+        self.cursor = start + replacement.chars().count();
+        Edit::Continue
+    }
+
+    /// Reads a line from the terminal, echoing it after whatever prompt is already
+    /// printed. Returns `None` at Ctrl-D on an empty line.
+    pub fn read_line(&mut self) -> io::Result<Option<String>> {
+        terminal::enable_raw_mode()?;
+        let mut out = io::stdout();
+        let result = self.edit_loop(&mut out);
+        terminal::disable_raw_mode()?;
+        let line = result?;
+        if let Some(line) = &line {
+            self.add_history(line);
+        }
+        Ok(line)
+    }
+
+    fn edit_loop(&mut self, out: &mut impl Write) -> io::Result<Option<String>> {
+        let (start, _) = cursor::position()?;
+        let start = start as usize;
+        loop {
+            self.draw(out, start)?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match self.handle_key(key) {
+                Edit::Continue => {}
+                Edit::Candidates(candidates) => {
+                    let list = candidates.join("  ");
+                    let lines = list.chars().count() / terminal_width() + 1;
+                    queue!(
+                        out,
+                        Print("\r\n"),
+                        Print(list),
+                        cursor::MoveUp(lines as u16)
+                    )?;
+                }
+                Edit::Submit(line) => {
+                    // show the whole line, not the search, before moving on
+                    self.line = line.chars().collect();
+                    self.cursor = self.line.len();
+                    self.draw(out, start)?;
+                    self.line.clear();
+                    self.cursor = 0;
+                    queue!(out, Print("\r\n"))?;
+                    out.flush()?;
+                    return Ok(Some(line));
+                }
+                Edit::Eof => {
+                    queue!(out, Print("\r\n"))?;
+                    out.flush()?;
+                    return Ok(None);
+                }
+            }
+        }
+    }
+
+    /// Redraws the line after the prompt, which ends at column `start`. Lines too long
+    /// for the terminal scroll sideways to keep the cursor visible.
+    fn draw(&self, out: &mut impl Write, start: usize) -> io::Result<()> {
+        let room = terminal_width().saturating_sub(start + 1).max(1);
+        let (text, column) = self.display();
+        let skip = column.saturating_sub(room);
+        let shown: String = text.chars().skip(skip).take(room).collect();
+        queue!(
+            out,
+            cursor::MoveToColumn(start as u16),
+            terminal::Clear(terminal::ClearType::FromCursorDown),
+            Print(shown),
+            cursor::MoveToColumn((start + column - skip) as u16)
+        )?;
+        out.flush()
+    }
+}
+
+/// Width of the terminal, 80 when it does not say.
+fn terminal_width() -> usize {
+    match terminal::size() {
+        Ok((width, _)) if width > 0 => width as usize,
+        _ => 80,
+    }
+}
+
+/// Returns where the word ending `line` starts and the completions of that word:
+/// command keywords followed by a space at the start of the line, and function
+/// names followed by `(` after the `=` of a formula.
+pub fn completions(line: &str) -> (usize, Vec<String>) {
+    let start = line
+        .rfind(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .map_or(0, |i| i + 1);
+    let word = &line[start..];
+    if word.is_empty() {
+        return (start, Vec::new());
+    }
+    let candidates = if line[..start].trim().is_empty() {
+        COMMANDS
+            .iter()
+            .filter(|command| command.starts_with(word))
+            .map(|command| format!("{} ", command))
+            .collect()
+    } else if line[..start].contains('=') {
+        let word = word.to_ascii_uppercase();
+        FUNCTIONS
+            .iter()
+            .filter(|function| function.starts_with(&word))
+            .map(|function| format!("{}(", function))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    (start, candidates)
+}
+
+/// Longest text every candidate starts with.
+fn common_prefix(candidates: &[String]) -> String {
+    let first = &candidates[0];
+    let mut length = first.len();
+    for candidate in &candidates[1..] {
+        length = first
+            .char_indices()
+            .zip(candidate.chars())
+            .take_while(|((_, a), b)| a == b)
+            .last()
+            .map_or(0, |((i, a), _)| i + a.len_utf8())
+            .min(length);
+    }
+    first[..length].to_string()
+}
@@ -1,2 +1,7 @@
+#[cfg(not(target_arch = "wasm32"))]
+pub mod editor;
+pub mod script;
 pub mod terminal;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod tui;
 pub mod web;
@@ -0,0 +1,80 @@
+/// Non-interactive driver that runs a file of commands against a sheet.
+///
+/// Used by `--script` mode: every line of the file is passed to the backend
+/// exactly as if it had been typed at the prompt, and the final values are
+/// written to stdout as CSV or JSON instead of being printed as a grid.
+use crate::extension::backend::backend::{Backend, Status};
+use crate::extension::backend::export::{range_to_csv, ranges_to_json};
+use crate::extension::common::column_decoder;
+use crate::extension::parser::parser::{check, describe, parse_range};
+use crate::script::{OutputFormat, ScriptOptions, run_commands};
+
+/// Describes why a command failed, or `None` if it succeeded.
+///
+/// A command the parser rejected is explained with the cause and column, as at the prompt.
+fn failure(status: &Status, command: &str, rows: usize, columns: usize) -> Option<String> {
+    match status {
+        Status::UnrecognizedCmd => Some(match check(command, &columns, &rows) {
+            Err(error) => describe(&error),
+            Ok(_) => "unrecognized command".to_string(),
+        }),
+        Status::CircularDependency => Some("cycle not allowed".to_string()),
+        Status::Error(message) => Some(message.clone()),
+        _ => None,
+    }
+}
+
+/// Runs `script` against `backend` and renders the requested ranges.
+///
+/// # Arguments
+///
+/// * `backend` - The sheet the commands are applied to
+/// * `script` - The contents of the command file
+/// * `options` - Error handling, output format and ranges
+///
+/// # Returns
+///
+/// The rendered output, or the first error when `keep_going` is off.
+/// With `keep_going` on, failures are reported on stderr and skipped.
+pub fn run_script(
+    backend: &mut Backend,
+    script: &str,
+    options: &ScriptOptions,
+) -> Result<String, String> {
+    run_commands(script, options, |command| {
+        if command == "q" {
+            return Ok(false);
+        }
+        let rows = backend.get_grid().get_row_size() - 1;
+        let columns = backend.get_grid().get_column_size() - 1;
+        let mut status = backend.process_command(rows, columns, command.to_string());
+        // nobody is around to confirm, so an open always goes ahead
+        if let Status::UnsavedChanges(path) = &status {
+            status = backend.open(&path.clone());
+        }
+        failure(&status, command, rows, columns).map_or(Ok(true), Err)
+    })?;
+
+    let rows = backend.get_grid().get_row_size() - 1;
+    let columns = backend.get_grid().get_column_size() - 1;
+    let ranges = if options.ranges.is_empty() {
+        vec![format!("A1:{}{}", column_decoder(columns), rows)]
+    } else {
+        options.ranges.clone()
+    };
+    let mut parsed = Vec::new();
+    for range in &ranges {
+        parsed
+            .push(parse_range(range, &rows, &columns).ok_or(format!("invalid range: {}", range))?);
+    }
+
+    let valgrid = backend.get_valgrid();
+    match options.format {
+        OutputFormat::Csv => Ok(parsed
+            .iter()
+            .map(|(start, end)| range_to_csv(&valgrid, start, end))
+            .collect::<Vec<String>>()
+            .join("\n")),
+        OutputFormat::Json => Ok(ranges_to_json(&valgrid, &parsed) + "\n"),
+    }
+}
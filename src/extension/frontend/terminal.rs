@@ -1,11 +1,19 @@
 #![allow(dead_code)]
-use crate::extension::common::Value;
+pub use crate::extension::common::column_decoder;
+use crate::extension::common::{Value, ViewChange};
+use crate::extension::parser::parser::{check, describe};
 use std::cmp;
+use std::collections::HashMap;
 use std::fs;
 use std::io;
-use std::io::Write;
+use std::io::{BufRead, Write};
 use std::process::Command;
 
+#[cfg(not(target_arch = "wasm32"))]
+use crate::extension::frontend::editor::Editor;
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::IsTerminal;
+
 //init_frontend(r, c) -> init_backend(r, c), Print_grid(), run_counter(): returns void
 //print grid() -> get_value(value::cell) : returns void
 //run_counter -> while loop for argument, process_command(r,c, string), Print_grid() : return void
@@ -13,6 +21,144 @@ use std::process::Command;
 use crate::extension::backend::backend::*;
 use std::time::{Duration, Instant};
 
+/// Part of the sheet printed after each command and the width of its columns.
+///
+/// Changed with `set view 25x8`, `set view auto`, `set width B 20` and `show_formulas on`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct View {
+    /// Rows shown, also the step of `w` and `s`
+    pub rows: usize,
+    /// Columns shown, also the step of `a` and `d`
+    pub columns: usize,
+    /// Fit the rows and columns to the terminal instead
+    pub auto: bool,
+    /// Width of the row labels and of the columns missing from `widths`
+    pub default_width: usize,
+    pub widths: HashMap<usize, usize>,
+    /// Show each cell's formula instead of its value
+    pub formulas: bool,
+}
+
+impl Default for View {
+    fn default() -> Self {
+        View {
+            rows: 10,
+            columns: 10,
+            auto: false,
+            default_width: 12,
+            widths: HashMap::new(),
+            formulas: false,
+        }
+    }
+}
+
+impl View {
+    /// Width of column `col`.
+    pub fn width(&self, col: usize) -> usize {
+        self.widths.get(&col).copied().unwrap_or(self.default_width)
+    }
+
+    /// Number of columns from `start_col` on that fit in `width` characters
+    /// next to the row labels, at least one.
+    pub fn columns_in(&self, start_col: usize, width: usize) -> usize {
+        let mut used = self.default_width + self.width(start_col);
+        let mut columns = 1;
+        while used + self.width(start_col + columns) <= width {
+            used += self.width(start_col + columns);
+            columns += 1;
+        }
+        columns
+    }
+
+    /// Rows and columns shown when the view starts at column `start_col`.
+    pub fn visible(&self, start_col: usize) -> (usize, usize) {
+        match terminal_size() {
+            // leave a line for the column labels and one for the prompt
+            Some((width, height)) if self.auto => (
+                height.saturating_sub(2).max(1),
+                self.columns_in(start_col, width),
+            ),
+            _ => (self.rows, self.columns),
+        }
+    }
+
+    pub fn apply(&mut self, change: &ViewChange) {
+        match change {
+            ViewChange::Size(rows, columns) => {
+                self.rows = *rows;
+                self.columns = *columns;
+                self.auto = false;
+            }
+            ViewChange::Auto => self.auto = true,
+            ViewChange::Width(col, width) => {
+                self.widths.insert(*col, *width);
+            }
+            ViewChange::Formulas(shown) => self.formulas = *shown,
+        }
+    }
+}
+
+/// Right-aligns `text` in `width` characters, cutting it short with a `~`
+/// marker when it does not fit.
+pub fn fit_text(text: &str, width: usize) -> String {
+    if text.chars().count() > width {
+        let kept: String = text.chars().take(width.saturating_sub(1)).collect();
+        format!("{}~", kept)
+    } else {
+        format!("{:>width$}", text)
+    }
+}
+
+/// Reads a line from `stdin` without editing, `None` at the end of the input.
+fn read_plain_line(stdin: &io::Stdin) -> io::Result<Option<String>> {
+    let mut input = String::new();
+    match stdin.read_line(&mut input)? {
+        0 => Ok(None),
+        _ => Ok(Some(input)),
+    }
+}
+
+/// Size of the terminal in characters, if there is one.
+fn terminal_size() -> Option<(usize, usize)> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        crossterm::terminal::size()
+            .ok()
+            .map(|(width, height)| (width as usize, height as usize))
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        None
+    }
+}
+
+/// ANSI style of error cells: red.
+const ERROR_STYLE: &str = "31";
+/// ANSI style of negative values: yellow.
+const NEGATIVE_STYLE: &str = "33";
+/// ANSI style of the cells recalculated by the last command: reverse video.
+const RECOMPUTED_STYLE: &str = "7";
+
+/// Returns true if the grid may be colored: stdout is a terminal and `NO_COLOR` is not set.
+pub fn color_enabled() -> bool {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        false
+    }
+}
+
+/// Wraps `text` in the ANSI escape codes of `styles`, e.g. `["31", "7"]`.
+pub fn paint(text: &str, styles: &[&str]) -> String {
+    if styles.is_empty() {
+        return text.to_string();
+    }
+    format!("\x1b[{}m{}\x1b[0m", styles.join(";"), text)
+}
+
 /// Terminal interface for the spreadsheet application.
 ///
 /// This struct manages the user interface for the terminal version
@@ -23,26 +169,9 @@ pub struct Frontend {
     pub dimension: Value,
     pub backend: Backend,
     pub print_enabled: bool,
-}
-
-/// Converts a column number to an Excel-style column label.
-///
-/// # Arguments
-///
-/// * `j` - The column number (1-based index) to convert
-///
-/// # Returns
-///
-/// A String representation of the column (e.g., 1 -> "A", 27 -> "AA")
-pub fn column_decoder(mut j: usize) -> String {
-    let mut cc = Vec::new();
-    while j > 0 {
-        j -= 1;
-        cc.push((b'A' + (j % 26) as u8) as char);
-        j /= 26;
-    }
-    cc.reverse();
-    cc.into_iter().collect()
+    pub view: View,
+    /// Color errors, negative values and recalculated cells, see `color_enabled`
+    pub color: bool,
 }
 
 impl Frontend {
@@ -50,6 +179,7 @@ impl Frontend {
     ///
     /// Shows the current viewable area of the spreadsheet with row and column headers.
     /// If print_enabled is set to false, this function returns without printing the grid.
+    /// With `color` on, cells are styled as described in `cell_styles`.
     pub fn print_grid(&self) {
         if !self.print_enabled {
             return;
@@ -57,21 +187,22 @@ impl Frontend {
         let location = self.start.clone();
         let dimension = self.dimension.clone();
         if let (Value::Cell(start_x, start_y), Value::Cell(rows, cols)) = (location, dimension) {
-            let max_x = cmp::min(9 + start_x, rows);
-            let max_y = cmp::min(9 + start_y, cols);
+            let (view_rows, view_columns) = self.view.visible(start_y);
+            let max_x = cmp::min(view_rows - 1 + start_x, rows);
+            let max_y = cmp::min(view_columns - 1 + start_y, cols);
+            let label_width = self.view.default_width;
+            let recomputed = self.backend.recomputed();
             for i in start_x - 1..=max_x {
                 for j in start_y - 1..=max_y {
                     if i == start_x - 1 && j == start_y - 1 {
-                        print!("{:>12}", " ");
+                        print!("{}", fit_text(" ", label_width));
                     } else if i == start_x - 1 {
-                        print!("{:>12}", column_decoder(j));
+                        print!("{}", fit_text(&column_decoder(j), self.view.width(j)));
                     } else if j == start_y - 1 {
-                        print!("{:>12}", i);
+                        print!("{}", fit_text(&i.to_string(), label_width));
                     } else {
-                        match self.backend.get_grid().get_node_value(i, j) {
-                            Some(value) => print!("{:>12}", value),
-                            None => print!("{:>12}", "ERR"),
-                        }
+                        let text = fit_text(&self.cell_text(i, j), self.view.width(j));
+                        print!("{}", paint(&text, &self.cell_styles(i, j, &recomputed)));
                     }
                 }
                 println!();
@@ -81,32 +212,85 @@ impl Frontend {
         }
     }
 
-    pub fn init_frontend(rows: usize, columns: usize, path: &str) -> Self {
-        if path.is_empty() {
-            let backend = Backend::init_backend(rows, columns);
-            Frontend {
-                start: Value::Cell(1, 1),
-                dimension: Value::Cell(rows, columns),
-                backend,
-                print_enabled: true,
+    /// Text shown for a cell: its value in the cell's format, `ERR` for an error, or
+    /// nothing when blank. With `show_formulas on` it is the cell's formula instead.
+    pub fn cell_text(&self, row: usize, col: usize) -> String {
+        let node = &self.backend.get_grid().cells_vec[row][col];
+        if self.view.formulas {
+            return node.formula().unwrap_or_default();
+        }
+        node.display_value()
+    }
+
+    /// ANSI styles of a cell in the grid: red for an error, yellow for a negative value
+    /// and reverse video if it is among the `recomputed` cells of the last command.
+    /// Empty when `color` is off.
+    pub fn cell_styles(&self, row: usize, col: usize, recomputed: &[Value]) -> Vec<&'static str> {
+        let mut styles = Vec::new();
+        if !self.color {
+            return styles;
+        }
+        let node = &self.backend.get_grid().cells_vec[row][col];
+        if !self.view.formulas && node.function.is_some() {
+            if !node.valid {
+                styles.push(ERROR_STYLE);
+            } else if node.node_value < 0 {
+                styles.push(NEGATIVE_STYLE);
             }
+        }
+        if recomputed.contains(&Value::Cell(row, col)) {
+            styles.push(RECOMPUTED_STYLE);
+        }
+        styles
+    }
+
+    /// Starts a frontend on a new sheet, or on the sheet saved at `path` when it is not
+    /// empty. Fails if that file cannot be loaded, rather than opening an empty sheet.
+    pub fn init_frontend(rows: usize, columns: usize, path: &str) -> Result<Self, String> {
+        let backend = if path.is_empty() {
+            Backend::init_backend(rows, columns)
         } else {
-            let backend = match Backend::deserial(path) {
-                Ok(backend) => backend,
-                Err(e) => {
-                    eprintln!("Failed to deserialize backend: {}", e);
-                    Backend::init_backend(rows, columns)
-                }
-            };
-            let rows = backend.get_grid().get_row_size() - 1;
-            let columns = backend.get_grid().get_column_size() - 1;
-            Frontend {
-                start: Value::Cell(1, 1),
-                dimension: Value::Cell(rows, columns),
-                backend,
-                print_enabled: true,
-            }
+            Backend::load(path).map_err(|e| format!("Failed to open '{}': {}", path, e))?
+        };
+        let rows = backend.get_grid().get_row_size() - 1;
+        let columns = backend.get_grid().get_column_size() - 1;
+        Ok(Frontend {
+            start: Value::Cell(1, 1),
+            dimension: Value::Cell(rows, columns),
+            backend,
+            print_enabled: true,
+            view: View::default(),
+            color: color_enabled(),
+        })
+    }
+
+    /// Matches the view to the size of the backend grid.
+    ///
+    /// Structural edits (and undoing them) change the number of rows and columns,
+    /// so the dimension is refreshed and the view start kept inside the sheet.
+    pub fn sync_dimension(&mut self) {
+        let rows = self.backend.get_grid().get_row_size() - 1;
+        let columns = self.backend.get_grid().get_column_size() - 1;
+        self.dimension = Value::Cell(rows, columns);
+        self.start.assign_row(cmp::min(self.start.row(), rows));
+        self.start.assign_col(cmp::min(self.start.col(), columns));
+    }
+
+    /// Runs a command on the backend at the current sheet size.
+    ///
+    /// A command that does not parse comes back as `Status::Error` saying what is
+    /// wrong with it, e.g. `unknown function FOO at col 4`, rather than `UnrecognizedCmd`.
+    pub fn process_command(&mut self, command: &str) -> Status {
+        let (rows, columns) = (self.dimension.row(), self.dimension.col());
+        let status = self
+            .backend
+            .process_command(rows, columns, command.to_string());
+        if status == Status::UnrecognizedCmd
+            && let Err(error) = check(command, &columns, &rows)
+        {
+            return Status::Error(describe(&error));
         }
+        status
     }
 
     /// Starts the frontend interface.
@@ -126,35 +310,40 @@ impl Frontend {
     /// Handles navigation commands (up, down, left, right), display settings,
     /// and custom commands like ScrollTo and Web.
     pub fn execute_status(&mut self, status: &Status) {
+        // scroll commands move by a whole view
+        let (rows, columns) = self.view.visible(self.start.col());
         match status {
             Status::Left => {
-                if self.start.col() > 10 {
-                    self.start.assign_col(self.start.col() - 10);
+                if self.start.col() > columns {
+                    self.start.assign_col(self.start.col() - columns);
                 } else {
                     self.start.assign_col(1);
                 }
             }
             Status::Right => {
-                if (self.start.col() as isize) < (self.dimension.col() as isize) - 10 {
-                    self.start.assign_col(self.start.col() + 10);
+                if self.start.col() + columns < self.dimension.col() {
+                    self.start.assign_col(self.start.col() + columns);
                 } else {
-                    self.start.assign_col(self.dimension.col() - 9); //debug
+                    self.start
+                        .assign_col((self.dimension.col() + 1).saturating_sub(columns).max(1));
                 }
             }
             Status::Up => {
-                if self.start.row() > 10 {
-                    self.start.assign_row(self.start.row() - 10);
+                if self.start.row() > rows {
+                    self.start.assign_row(self.start.row() - rows);
                 } else {
                     self.start.assign_row(1);
                 }
             }
             Status::Down => {
-                if (self.start.row() as isize) < (self.dimension.row() as isize) - 10 {
-                    self.start.assign_row(self.start.row() + 10);
+                if self.start.row() + rows < self.dimension.row() {
+                    self.start.assign_row(self.start.row() + rows);
                 } else {
-                    self.start.assign_row(self.dimension.row() - 9);
+                    self.start
+                        .assign_row((self.dimension.row() + 1).saturating_sub(rows).max(1));
                 }
             }
+            Status::View(change) => self.view.apply(change),
             Status::PrintDisabled => {
                 self.print_enabled = false;
             }
@@ -165,6 +354,12 @@ impl Frontend {
                 self.start.assign_row(*row);
                 self.start.assign_col(*col);
             }
+            Status::Opened | Status::SwitchedSheet => {
+                let rows = self.backend.get_grid().get_row_size() - 1;
+                let columns = self.backend.get_grid().get_column_size() - 1;
+                self.start = Value::Cell(1, 1);
+                self.dimension = Value::Cell(rows, columns);
+            }
             Status::Web(path) => {
                 println!("Web path: {}", path);
                 let contents = fs::read_to_string(path).expect("Failed to read file");
@@ -214,41 +409,109 @@ impl Frontend {
     /// Prints the grid (if enabled) followed by a status message and prompt.
     pub fn display(&self, status: Status, elapsed_time: f64) {
         self.print_grid();
-        match status {
-            Status::Success => print!("[{:.2}] (ok) > ", elapsed_time),
-            Status::UnrecognizedCmd => print!("[{:.2}] (unrecognized command) > ", elapsed_time),
-            Status::CircularDependency => print!("[{:.2}] (cycle not allowed) > ", elapsed_time),
-            Status::PrintEnabled => print!("[{:.2}] (ok) > ", elapsed_time),
-            Status::PrintDisabled => print!("[{:.2}] (ok) > ", elapsed_time),
-            Status::ScrollTo(_, _) => print!("[{:.2}] (ok) > ", elapsed_time),
-            Status::Up => print!("[{:.2}] (ok) > ", elapsed_time),
-            Status::Down => print!("[{:.2}] (ok) > ", elapsed_time),
-            Status::Left => print!("[{:.2}] (ok) > ", elapsed_time),
-            Status::Right => print!("[{:.2}] (ok) > ", elapsed_time),
-            _ => (),
+        if let Status::Report(text) = &status {
+            println!("{}", text);
+        }
+        if let Some(message) = self.status_message(&status) {
+            let failed = matches!(
+                status,
+                Status::UnrecognizedCmd | Status::CircularDependency | Status::Error(_)
+            );
+            let message = if failed && self.color {
+                paint(&message, &[ERROR_STYLE])
+            } else {
+                message
+            };
+            print!("[{:.2}] ({}) > ", elapsed_time, message);
         }
         io::stdout().flush().unwrap();
     }
 
+    /// Short message shown after a command, e.g. `ok` or `cycle not allowed`.
+    ///
+    /// Returns `None` for statuses that print nothing, such as `Quit`.
+    pub fn status_message(&self, status: &Status) -> Option<String> {
+        let message = match status {
+            Status::Success
+            | Status::PrintEnabled
+            | Status::PrintDisabled
+            | Status::ScrollTo(_, _)
+            | Status::Up
+            | Status::Down
+            | Status::Left
+            | Status::Right
+            | Status::Opened
+            | Status::View(_)
+            | Status::Report(_) => "ok".to_string(),
+            Status::UnrecognizedCmd => "unrecognized command".to_string(),
+            Status::CircularDependency => "cycle not allowed".to_string(),
+            Status::SwitchedSheet => format!("sheet {}", self.backend.sheet_name()),
+            Status::UnsavedChanges(_) => "open cancelled".to_string(),
+            Status::Error(message) => message.clone(),
+            _ => return None,
+        };
+        Some(message)
+    }
+
+    /// Asks whether unsaved changes may be discarded before opening `path`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file the user asked to open
+    /// * `input` - Where the answer is read from (stdin in the REPL)
+    ///
+    /// Opens the file on a `y`/`yes` answer, otherwise hands the `UnsavedChanges`
+    /// status back so the prompt reports the open as cancelled.
+    pub fn confirm_open<R: BufRead>(&mut self, path: &str, input: &mut R) -> Status {
+        print!(
+            "Unsaved changes will be lost. Open '{}' anyway? (y/n) ",
+            path
+        );
+        io::stdout().flush().unwrap();
+        let mut answer = String::new();
+        if input.read_line(&mut answer).is_err() {
+            return Status::UnsavedChanges(path.to_string());
+        }
+        match answer.trim() {
+            "y" | "yes" => self.backend.open(path),
+            _ => Status::UnsavedChanges(path.to_string()),
+        }
+    }
+
     /// Main input loop for the terminal interface.
     ///
     /// Continuously reads commands from stdin, processes them through the backend,
     /// updates the display based on status, and measures execution time.
     /// Loop exits when a Quit status is received.
+    ///
+    /// On a terminal lines are read with an `Editor`, which adds line editing, history
+    /// and completion. Otherwise, as when input is piped in, plain lines are read and
+    /// the loop also ends at the end of the input.
     pub fn run_counter(&mut self) {
-        let mut input = String::new();
         let stdin = std::io::stdin();
+        #[cfg(not(target_arch = "wasm32"))]
+        let mut editor = (stdin.is_terminal() && io::stdout().is_terminal())
+            .then(|| Editor::new(Editor::default_history_path()));
 
         loop {
-            input.clear();
-
-            if stdin.read_line(&mut input).is_err() {
-                self.display(
-                    Status::UnrecognizedCmd,
-                    Duration::from_secs(0).as_secs_f64(),
-                );
-                continue;
-            }
+            #[cfg(not(target_arch = "wasm32"))]
+            let line = match editor.as_mut() {
+                Some(editor) => editor.read_line(),
+                None => read_plain_line(&stdin),
+            };
+            #[cfg(target_arch = "wasm32")]
+            let line = read_plain_line(&stdin);
+            let input = match line {
+                Ok(Some(input)) => input,
+                Ok(None) => break,
+                Err(_) => {
+                    self.display(
+                        Status::UnrecognizedCmd,
+                        Duration::from_secs(0).as_secs_f64(),
+                    );
+                    continue;
+                }
+            };
             let start_time = Instant::now();
             let command = input.trim().to_string();
             // let status = Status::Success;
@@ -256,13 +519,16 @@ impl Frontend {
             //     self.backend.serial("tester.json").expect("Failed to save file");
 
             // } else {
-            let status =
-                self.backend
-                    .process_command(self.dimension.row(), self.dimension.col(), command);
+            let mut status = self.process_command(&command);
             // }
+            if let Status::UnsavedChanges(path) = &status {
+                let path = path.clone();
+                status = self.confirm_open(&path, &mut stdin.lock());
+            }
             if status == Status::Quit {
                 break;
             }
+            self.sync_dimension();
             self.execute_status(&status);
             let elapsed_time = start_time.elapsed();
             self.display(status, elapsed_time.as_secs_f64());
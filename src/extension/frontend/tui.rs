@@ -0,0 +1,347 @@
+/// Full-screen terminal mode, started with `--tui`.
+///
+/// The sheet fills the terminal with a highlighted cursor cell moved by the
+/// arrow keys. The top line is a formula bar for the selected cell, Enter edits
+/// the cell in place, `:` runs any line-mode command and the bottom line shows
+/// the status of the last command. Drawing is split from the layout so that
+/// `screen` and `handle_key` can be used without a terminal.
+use crate::extension::backend::backend::Status;
+use crate::extension::common::{Value, column_decoder};
+use crate::extension::frontend::terminal::{Frontend, fit_text};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::{cursor, execute, queue, terminal};
+use std::cmp;
+use std::io::{self, Write};
+
+/// Lines taken by the formula bar, the column header and the status line
+const CHROME_LINES: usize = 3;
+
+/// Text being typed on the formula bar.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Prompt {
+    /// New contents for the cursor cell; empty text clears it
+    Edit(String),
+    /// A line-mode command typed after `:`
+    Command(String),
+    /// Waiting for `y` to open a file and lose the unsaved changes
+    ConfirmOpen(String),
+}
+
+/// Splits a screen line around the `width` characters from character `start`, which
+/// are drawn in reverse video for the cursor cell. `None` if the line ends before `start`.
+///
+/// Offsets count characters, not bytes, so cells such as `€1,234` are cut in the right place.
+pub fn split_cursor(line: &str, start: usize, width: usize) -> Option<(&str, &str, &str)> {
+    let byte = |chars: usize| {
+        line.char_indices()
+            .nth(chars)
+            .map_or(line.len(), |(i, _)| i)
+    };
+    if start >= line.chars().count() {
+        return None;
+    }
+    let (start, end) = (byte(start), byte(start + width));
+    Some((&line[..start], &line[start..end], &line[end..]))
+}
+
+/// State of the full-screen mode: the sheet, the cursor and the formula bar.
+pub struct Tui {
+    pub frontend: Frontend,
+    /// Selected cell
+    pub cursor: Value,
+    pub prompt: Option<Prompt>,
+    /// Message of the last command, shown on the status line
+    pub message: String,
+}
+
+impl Tui {
+    pub fn new(frontend: Frontend) -> Self {
+        Tui {
+            frontend,
+            cursor: Value::Cell(1, 1),
+            prompt: None,
+            message: "ok".to_string(),
+        }
+    }
+
+    /// Number of sheet rows and columns that fit on a `width` x `height` screen
+    /// from the current view start. Columns take the widths set with `set width`.
+    pub fn page_size(&self, width: usize, height: usize) -> (usize, usize) {
+        (
+            height.saturating_sub(CHROME_LINES).max(1),
+            self.frontend
+                .view
+                .columns_in(self.frontend.start.col(), width),
+        )
+    }
+
+    /// Moves the view start so that the cursor is on a `width` x `height` screen.
+    fn follow_cursor(&mut self, width: usize, height: usize) {
+        let (row, col) = (self.cursor.row(), self.cursor.col());
+        let rows = height.saturating_sub(CHROME_LINES).max(1);
+        let start = &mut self.frontend.start;
+        if row < start.row() {
+            start.assign_row(row);
+        } else if row >= start.row() + rows {
+            start.assign_row(row + 1 - rows);
+        }
+        if col < start.col() {
+            start.assign_col(col);
+        }
+        while col >= start.col() + self.frontend.view.columns_in(start.col(), width) {
+            start.assign_col(start.col() + 1);
+        }
+    }
+
+    /// Lines of the screen as plain text, scrolling the view to the cursor first.
+    pub fn screen(&mut self, width: usize, height: usize) -> Vec<String> {
+        self.follow_cursor(width, height);
+        let (rows, columns) = self.page_size(width, height);
+        let (start_row, start_col) = (self.frontend.start.row(), self.frontend.start.col());
+        let last_row = cmp::min(start_row + rows - 1, self.frontend.dimension.row());
+        let last_col = cmp::min(start_col + columns - 1, self.frontend.dimension.col());
+        let view = &self.frontend.view;
+
+        let mut lines = vec![self.formula_bar()];
+        let mut header = fit_text("", view.default_width);
+        for col in start_col..=last_col {
+            header.push_str(&fit_text(&column_decoder(col), view.width(col)));
+        }
+        lines.push(header);
+        for row in start_row..=last_row {
+            let mut line = fit_text(&row.to_string(), view.default_width);
+            for col in start_col..=last_col {
+                let text = self.frontend.cell_text(row, col);
+                line.push_str(&fit_text(&text, view.width(col)));
+            }
+            lines.push(line);
+        }
+        while lines.len() < height.saturating_sub(1) {
+            lines.push(String::new());
+        }
+        lines.push(format!(
+            "{} | {}",
+            self.frontend.backend.sheet_name(),
+            self.message
+        ));
+        lines
+            .into_iter()
+            .map(|line| line.chars().take(width).collect())
+            .collect()
+    }
+
+    /// Top line: the cursor cell with its formula, or the text being typed.
+    fn formula_bar(&self) -> String {
+        match &self.prompt {
+            Some(Prompt::Edit(text)) => format!("{}> {}", self.cursor, text),
+            Some(Prompt::Command(text)) => format!(":{}", text),
+            Some(Prompt::ConfirmOpen(path)) => {
+                format!(
+                    "Unsaved changes will be lost. Open '{}' anyway? (y/n) ",
+                    path
+                )
+            }
+            None => {
+                let node = &self.frontend.backend.get_grid().cells_vec[self.cursor.row()]
+                    [self.cursor.col()];
+                format!("{}: {}", self.cursor, node.formula().unwrap_or_default())
+            }
+        }
+    }
+
+    /// Moves the cursor by `rows` and `columns`, staying inside the sheet.
+    fn move_cursor(&mut self, rows: isize, columns: isize) {
+        let row =
+            (self.cursor.row() as isize + rows).clamp(1, self.frontend.dimension.row() as isize);
+        let col =
+            (self.cursor.col() as isize + columns).clamp(1, self.frontend.dimension.col() as isize);
+        self.cursor = Value::Cell(row as usize, col as usize);
+    }
+
+    /// Runs a command and reports its status on the status line.
+    fn run_command(&mut self, command: String) -> Status {
+        let status = self.frontend.process_command(&command);
+        self.show_status(&status);
+        status
+    }
+
+    /// Follows a command's status: moves the cursor or view, and sets the status line.
+    fn show_status(&mut self, status: &Status) {
+        self.frontend.sync_dimension();
+        match status {
+            Status::ScrollTo(row, col) => self.cursor = Value::Cell(*row, *col),
+            Status::Opened | Status::SwitchedSheet => {
+                self.cursor = Value::Cell(1, 1);
+                self.frontend.start = Value::Cell(1, 1);
+            }
+            Status::PrintEnabled | Status::PrintDisabled | Status::View(_) => {
+                self.frontend.execute_status(status)
+            }
+            _ => {}
+        }
+        self.move_cursor(0, 0);
+        self.message = match status {
+            Status::Report(text) => text.lines().collect::<Vec<&str>>().join("; "),
+            // ask on the formula bar before discarding the changes
+            Status::UnsavedChanges(path) => {
+                self.prompt = Some(Prompt::ConfirmOpen(path.clone()));
+                "unsaved changes: y opens anyway, any other key cancels".to_string()
+            }
+            _ => self.frontend.status_message(status).unwrap_or_default(),
+        };
+    }
+
+    /// Opens `path` on a `y` key, as `Frontend::confirm_open` does in line mode;
+    /// any other key cancels.
+    fn confirm_open(&mut self, path: &str, key: KeyEvent) -> bool {
+        if matches!(key.code, KeyCode::Char('y' | 'Y')) {
+            let status = self.frontend.backend.open(path);
+            self.show_status(&status);
+        } else {
+            self.message = "open cancelled".to_string();
+        }
+        true
+    }
+
+    /// Handles one key press. `page` is the number of rows and columns on screen,
+    /// used by Page Up and Page Down. Returns false once the user quits.
+    pub fn handle_key(&mut self, key: KeyEvent, page: (usize, usize)) -> bool {
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            return false;
+        }
+        let Some(prompt) = self.prompt.take() else {
+            return self.handle_move(key, page);
+        };
+        let (mut text, command) = match prompt {
+            Prompt::Edit(text) => (text, false),
+            Prompt::Command(text) => (text, true),
+            Prompt::ConfirmOpen(path) => return self.confirm_open(&path, key),
+        };
+        match key.code {
+            KeyCode::Esc => return true,
+            KeyCode::Enter if command => {
+                return self.run_command(text) != Status::Quit;
+            }
+            KeyCode::Enter => {
+                let status = if text.trim().is_empty() {
+                    self.run_command(format!("clear {}", self.cursor))
+                } else {
+                    self.run_command(format!("{}={}", self.cursor, text.trim()))
+                };
+                if status == Status::Success {
+                    self.move_cursor(1, 0);
+                } else {
+                    // keep the text so that a typo can be fixed
+                    self.prompt = Some(Prompt::Edit(text));
+                }
+                return true;
+            }
+            KeyCode::Backspace => {
+                text.pop();
+            }
+            KeyCode::Char(c) => text.push(c),
+            _ => {}
+        }
+        self.prompt = Some(if command {
+            Prompt::Command(text)
+        } else {
+            Prompt::Edit(text)
+        });
+        true
+    }
+
+    /// Keys used while no text is being typed.
+    fn handle_move(&mut self, key: KeyEvent, page: (usize, usize)) -> bool {
+        let (rows, columns) = (page.0 as isize, page.1 as isize);
+        match key.code {
+            KeyCode::Up => self.move_cursor(-1, 0),
+            KeyCode::Down => self.move_cursor(1, 0),
+            KeyCode::Left => self.move_cursor(0, -1),
+            KeyCode::Right | KeyCode::Tab => self.move_cursor(0, 1),
+            KeyCode::PageUp => self.move_cursor(-rows, 0),
+            KeyCode::PageDown => self.move_cursor(rows, 0),
+            KeyCode::Home => self.move_cursor(0, -columns),
+            KeyCode::End => self.move_cursor(0, columns),
+            KeyCode::Enter | KeyCode::F(2) => {
+                let node = &self.frontend.backend.get_grid().cells_vec[self.cursor.row()]
+                    [self.cursor.col()];
+                self.prompt = Some(Prompt::Edit(node.formula().unwrap_or_default()));
+            }
+            KeyCode::Delete => {
+                self.run_command(format!("clear {}", self.cursor));
+            }
+            KeyCode::Char(':') => self.prompt = Some(Prompt::Command(String::new())),
+            KeyCode::Char('q') => return false,
+            // typing over a cell starts a fresh edit
+            KeyCode::Char(c) => self.prompt = Some(Prompt::Edit(c.to_string())),
+            _ => {}
+        }
+        true
+    }
+
+    /// Draws the screen, with the cursor cell in reverse video.
+    fn draw(&mut self, out: &mut impl Write) -> io::Result<()> {
+        let (width, height) = terminal::size()?;
+        let lines = self.screen(width as usize, height as usize);
+        let view = &self.frontend.view;
+        let cursor_line = 2 + self.cursor.row() - self.frontend.start.row();
+        let cursor_start = view.default_width
+            + (self.frontend.start.col()..self.cursor.col())
+                .map(|col| view.width(col))
+                .sum::<usize>();
+        let cursor_width = view.width(self.cursor.col());
+        for (i, line) in lines.iter().enumerate() {
+            queue!(
+                out,
+                cursor::MoveTo(0, i as u16),
+                terminal::Clear(terminal::ClearType::CurrentLine)
+            )?;
+            match split_cursor(line, cursor_start, cursor_width) {
+                Some((before, cell, after)) if i == cursor_line => queue!(
+                    out,
+                    Print(before),
+                    SetAttribute(Attribute::Reverse),
+                    Print(cell),
+                    SetAttribute(Attribute::Reset),
+                    Print(after)
+                )?,
+                _ => queue!(out, Print(line))?,
+            }
+        }
+        if self.prompt.is_some() {
+            let column = cmp::min(lines[0].chars().count(), width.saturating_sub(1) as usize);
+            queue!(out, cursor::MoveTo(column as u16, 0), cursor::Show)?;
+        } else {
+            queue!(out, cursor::Hide)?;
+        }
+        out.flush()
+    }
+
+    /// Runs the full-screen mode until the user quits, restoring the terminal afterwards.
+    pub fn run(frontend: Frontend) -> io::Result<()> {
+        let mut tui = Tui::new(frontend);
+        let mut out = io::stdout();
+        terminal::enable_raw_mode()?;
+        execute!(out, terminal::EnterAlternateScreen)?;
+        let result = tui.event_loop(&mut out);
+        execute!(out, cursor::Show, terminal::LeaveAlternateScreen)?;
+        terminal::disable_raw_mode()?;
+        result
+    }
+
+    fn event_loop(&mut self, out: &mut impl Write) -> io::Result<()> {
+        loop {
+            self.draw(out)?;
+            if let Event::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+            {
+                let (width, height) = terminal::size()?;
+                let page = self.page_size(width as usize, height as usize);
+                if !self.handle_key(key, page) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
@@ -1,6 +1,7 @@
-use crate::extension::backend::backend::Backend;
 #[allow(unused_imports)]
 use crate::extension::backend::backend::Valgrid;
+use crate::extension::backend::backend::{Backend, Status};
+use crate::extension::common::number_to_rgb;
 #[allow(unused_imports)]
 use gloo_net::http::Request;
 #[allow(unused_imports)]
@@ -148,12 +149,6 @@ fn number_to_column_label(num: usize) -> String {
 
     result
 }
-fn number_to_rgb(n: u32) -> (u8, u8, u8) {
-    let r = (n >> 16) & 0xFF;
-    let g = (n >> 8) & 0xFF;
-    let b = n & 0xFF;
-    (r as u8, g as u8, b as u8)
-}
 
 #[derive(Clone, PartialEq)]
 struct SelectedCell {
@@ -167,6 +162,24 @@ struct CellRange {
     end: SelectedCell,
 }
 
+/// Label of a selected range with its corners ordered, e.g. `A1:C4`, as the parser expects.
+fn ordered_range_label(range: &CellRange) -> String {
+    format!(
+        "{}{}:{}{}",
+        number_to_column_label(range.start.col.min(range.end.col)),
+        range.start.row.min(range.end.row),
+        number_to_column_label(range.start.col.max(range.end.col)),
+        range.start.row.max(range.end.row)
+    )
+}
+
+/// Runs `command` against the sheet at its current size, which `resize` can change.
+fn run_command(backend: &mut Backend, command: String) -> Status {
+    let rows = backend.get_grid().get_row_size() - 1;
+    let columns = backend.get_grid().get_column_size() - 1;
+    backend.process_command(rows, columns, command)
+}
+
 #[function_component(App)]
 pub fn app() -> Html {
     let formula_input_ref = use_node_ref();
@@ -347,7 +360,7 @@ pub fn app() -> Html {
                 let mut backend_ref = backend.borrow_mut();
                 //web_sys::console::log_1(&format!("Selected cell row={}, col={} => {}", cell.row, cell.col, target_cell).into());
                 // web_sys::console::log_1(&format!("Command sent to process_command: {}", command).into());
-                let status = backend_ref.process_command(100_usize, 100_usize, command.clone());
+                let status = run_command(&mut backend_ref, command.clone());
                 match status {
                     crate::extension::backend::backend::Status::Success => {
                         status_message.set(format!("{} updated successfully", target_cell));
@@ -399,7 +412,7 @@ pub fn app() -> Html {
 
         Callback::from(move |_| {
             let mut backend_ref = backend.borrow_mut();
-            let status = backend_ref.process_command(100, 100, "undo".to_string());
+            let status = run_command(&mut backend_ref, "undo".to_string());
             if let crate::extension::backend::backend::Status::Success = status {
                 table.set(backend_ref.get_valgrid());
                 status_message.set("Undo successful".to_string());
@@ -416,7 +429,7 @@ pub fn app() -> Html {
 
         Callback::from(move |_| {
             let mut backend_ref = backend.borrow_mut();
-            let status = backend_ref.process_command(100, 100, "redo".to_string());
+            let status = run_command(&mut backend_ref, "redo".to_string());
             if let crate::extension::backend::backend::Status::Success = status {
                 table.set(backend_ref.get_valgrid());
                 status_message.set("Redo successful".to_string());
@@ -426,6 +439,120 @@ pub fn app() -> Html {
         })
     };
 
+    // the range picked with "Copy", pasted at the selected cell by "Paste"
+    let clipboard = use_state(|| None::<String>);
+
+    let on_copy = {
+        let selected_range = selected_range.clone();
+        let clipboard = clipboard.clone();
+        let status_message = status_message.clone();
+
+        Callback::from(move |_| {
+            if let Some(range) = &*selected_range {
+                let label = ordered_range_label(range);
+                status_message.set(format!("Copied {}", label));
+                clipboard.set(Some(label));
+            } else {
+                status_message.set("Select a range to copy".to_string());
+            }
+        })
+    };
+
+    // runs a range command such as `fill_down A1:A5` and refreshes the table
+    let run_range_command = {
+        let backend = backend.clone();
+        let table = table.clone();
+        let status_message = status_message.clone();
+
+        move |command: String| {
+            let mut backend_ref = backend.borrow_mut();
+            let status = run_command(&mut backend_ref, command.clone());
+            match status {
+                crate::extension::backend::backend::Status::Success => {
+                    table.set(backend_ref.get_valgrid());
+                    status_message.set(format!("{} done", command));
+                }
+                crate::extension::backend::backend::Status::CircularDependency => {
+                    status_message.set(format!("Cycle detected in {}", command));
+                }
+                crate::extension::backend::backend::Status::Error(message) => {
+                    status_message.set(message);
+                }
+                _ => status_message.set(("Unrecognized command").to_string()),
+            }
+        }
+    };
+
+    let on_paste = {
+        let selected_cell = selected_cell.clone();
+        let clipboard = clipboard.clone();
+        let run_range_command = run_range_command.clone();
+        let status_message = status_message.clone();
+
+        Callback::from(move |_| match (&*clipboard, &*selected_cell) {
+            (Some(source), Some(cell)) => run_range_command(format!(
+                "copy {} to {}{}",
+                source,
+                number_to_column_label(cell.col),
+                cell.row
+            )),
+            _ => status_message.set("Copy a range and select a cell first".to_string()),
+        })
+    };
+
+    let on_fill = |keyword: &'static str| {
+        let selected_range = selected_range.clone();
+        let run_range_command = run_range_command.clone();
+        let status_message = status_message.clone();
+
+        Callback::from(move |_| {
+            if let Some(range) = &*selected_range {
+                run_range_command(format!("{} {}", keyword, ordered_range_label(range)));
+            } else {
+                status_message.set("Select a range to fill".to_string());
+            }
+        })
+    };
+    let on_fill_down = on_fill("fill_down");
+    let on_fill_right = on_fill("fill_right");
+
+    let on_clear = {
+        let selected_range = selected_range.clone();
+        let selected_cell = selected_cell.clone();
+        let run_range_command = run_range_command.clone();
+        let status_message = status_message.clone();
+
+        Callback::from(move |_| match (&*selected_range, &*selected_cell) {
+            (Some(range), _) => run_range_command(format!("clear {}", ordered_range_label(range))),
+            (None, Some(cell)) => run_range_command(format!(
+                "clear {}{}",
+                number_to_column_label(cell.col),
+                cell.row
+            )),
+            _ => status_message.set("Select a cell or range to clear".to_string()),
+        })
+    };
+
+    // new size typed next to the "Resize" button
+    let new_rows = use_state(|| table.rows - 1);
+    let new_cols = use_state(|| table.columns - 1);
+    let on_new_size = |size: UseStateHandle<usize>| {
+        Callback::from(move |e: InputEvent| {
+            let input = e.target_unchecked_into::<web_sys::HtmlInputElement>();
+            if let Ok(value) = input.value().parse::<usize>() {
+                size.set(value);
+            }
+        })
+    };
+    let on_new_rows_change = on_new_size(new_rows.clone());
+    let on_new_cols_change = on_new_size(new_cols.clone());
+    let on_resize = {
+        let run_range_command = run_range_command.clone();
+        let new_rows = new_rows.clone();
+        let new_cols = new_cols.clone();
+        Callback::from(move |_| run_range_command(format!("resize {} {}", *new_rows, *new_cols)))
+    };
+
     let on_rows1_change = {
         let rows1 = rows1.clone();
         Callback::from(move |e: InputEvent| {
@@ -792,7 +919,16 @@ pub fn app() -> Html {
                 />
                 <button style="margin-right: 10px;" onclick={on_submit_formula}>{"Apply"}</button>
                 <button style="margin-right: 10px;" onclick={on_undo}>{"Undo"}</button>
-                <button onclick={on_redo}>{"Redo"}</button>
+                <button style="margin-right: 10px;" onclick={on_redo}>{"Redo"}</button>
+                <button style="margin-right: 10px;" onclick={on_copy}>{"Copy"}</button>
+                <button style="margin-right: 10px;" onclick={on_paste}>{"Paste"}</button>
+                <button style="margin-right: 10px;" onclick={on_fill_down}>{"Fill Down"}</button>
+                <button style="margin-right: 10px;" onclick={on_fill_right}>{"Fill Right"}</button>
+                <button style="margin-right: 10px;" onclick={on_clear}>{"Clear"}</button>
+                <input type="number" style="width: 60px;" value={(*new_rows).to_string()} oninput={on_new_rows_change} min="1"/>
+                {" x "}
+                <input type="number" style="width: 60px;" value={(*new_cols).to_string()} oninput={on_new_cols_change} min="1"/>
+                <button onclick={on_resize}>{"Resize"}</button>
 
             </div>
             <div class="status-bar">
@@ -801,15 +937,15 @@ pub fn app() -> Html {
             <div class="controls">
                 <div>
                     <label>{"Rows: "}</label>
-                    <input type="number" value={(*rows1).to_string()} oninput={on_rows1_change} min="1" max="100"/>
+                    <input type="number" value={(*rows1).to_string()} oninput={on_rows1_change} min="1" max={(table.rows - 1).to_string()}/>
                     {" to "}
-                    <input type="number" value={(*rows2).to_string()} oninput={on_rows2_change} min="1" max="100"/>
+                    <input type="number" value={(*rows2).to_string()} oninput={on_rows2_change} min="1" max={(table.rows - 1).to_string()}/>
                 </div>
                 <div>
                     <label>{"Columns: "}</label>
-                    <input type="number" value={(*cols1).to_string()} oninput={on_cols1_change} min="1" max="100"/>
+                    <input type="number" value={(*cols1).to_string()} oninput={on_cols1_change} min="1" max={(table.columns - 1).to_string()}/>
                     {" to "}
-                    <input type="number" value={(*cols2).to_string()} oninput={on_cols2_change} min="1" max="100"/>
+                    <input type="number" value={(*cols2).to_string()} oninput={on_cols2_change} min="1" max={(table.columns - 1).to_string()}/>
                 </div>
                 <div style="margin-left: 20px;">
                     <label>{"Chart: "}</label>
@@ -918,11 +1054,27 @@ pub fn app() -> Html {
                                     <th>{ row }</th>
                                     { for col_range.clone().map(|col| {
                                         // let cell_value = table.cells[row - 1][col - 1].to_string();
-                                        let cell_value = table.cells
+                                        let blank = table.blanks
                                             .get(row)
                                             .and_then(|r| r.get(col))
-                                            .map(|v| v.map_or("ERR".to_string(), |n| n.to_string()))
-                                            .unwrap_or_else(|| "ERR".to_string());
+                                            .copied()
+                                            .unwrap_or(false);
+                                        let format = table.formats
+                                            .get(row)
+                                            .and_then(|r| r.get(col))
+                                            .cloned()
+                                            .flatten();
+                                        let cell_value = if blank {
+                                            String::new()
+                                        } else {
+                                            table.cells
+                                                .get(row)
+                                                .and_then(|r| r.get(col))
+                                                .map(|v| v.map_or("ERR".to_string(), |n| {
+                                                    format.as_ref().map_or(n.to_string(), |format| format.apply(n))
+                                                }))
+                                                .unwrap_or_else(|| "ERR".to_string())
+                                        };
 
                                         let is_selected = selected_cell.as_ref()
                                             .map(|sc| sc.row == row && sc.col == col)
@@ -0,0 +1,379 @@
+/// Syntax, description and example of every command and function, as printed by `help`.
+pub struct Topic {
+    /// The keyword or function name looked up by `help NAME`.
+    pub name: &'static str,
+    pub syntax: &'static str,
+    pub about: &'static str,
+    pub example: &'static str,
+}
+
+const fn topic(
+    name: &'static str,
+    syntax: &'static str,
+    about: &'static str,
+    example: &'static str,
+) -> Topic {
+    Topic {
+        name,
+        syntax,
+        about,
+        example,
+    }
+}
+
+/// Commands in the order `help` lists them; assignments come first.
+pub const COMMAND_TOPICS: &[Topic] = &[
+    topic(
+        "=",
+        "CELL=VALUE | CELL=A OP B | CELL=FUNC(RANGE)",
+        "Set a cell to a number, a reference, one arithmetic operation (+ - * /) or a function.",
+        "C3=A1+B2",
+    ),
+    topic("w", "w", "Scroll the view up.", "w"),
+    topic("a", "a", "Scroll the view left.", "a"),
+    topic("s", "s", "Scroll the view down.", "s"),
+    topic("d", "d", "Scroll the view right.", "d"),
+    topic(
+        "scroll_to",
+        "scroll_to CELL",
+        "Move the view so that it starts at a cell.",
+        "scroll_to A10",
+    ),
+    topic(
+        "set",
+        "set view ROWSxCOLS | set view auto | set width COL WIDTH",
+        "Change how many rows and columns are shown, or the width of one column.",
+        "set width B 20",
+    ),
+    topic(
+        "show_formulas",
+        "show_formulas on|off",
+        "Show each cell's formula in the grid instead of its value.",
+        "show_formulas on",
+    ),
+    topic(
+        "enable_output",
+        "enable_output",
+        "Print the grid after every command.",
+        "enable_output",
+    ),
+    topic(
+        "disable_output",
+        "disable_output",
+        "Stop printing the grid after every command.",
+        "disable_output",
+    ),
+    topic("undo", "undo", "Undo the last change.", "undo"),
+    topic("redo", "redo", "Redo the last undone change.", "redo"),
+    topic(
+        "save",
+        "save FILE",
+        "Save the sheets, names and formats to a JSON file.",
+        "save sheet.json",
+    ),
+    topic(
+        "open",
+        "open FILE",
+        "Open a saved JSON file or a CSV file.",
+        "open sheet.json",
+    ),
+    topic("load", "load FILE", "Same as open.", "load data.csv"),
+    topic(
+        "copy",
+        "copy RANGE to CELL",
+        "Copy a block of cells; relative references shift with the copy.",
+        "copy A1:B2 to C5",
+    ),
+    topic(
+        "move",
+        "move RANGE to CELL",
+        "Move a block of cells and rewrite the formulas that read it.",
+        "move A1:C10 to E1",
+    ),
+    topic(
+        "sort",
+        "sort RANGE by COL [asc|desc], ...",
+        "Reorder the rows of a range by one or more key columns.",
+        "sort A2:D100 by B desc, A asc",
+    ),
+    topic(
+        "fill_down",
+        "fill_down RANGE",
+        "Copy the first row of a range down the rest of it.",
+        "fill_down A2:C10",
+    ),
+    topic(
+        "fill_right",
+        "fill_right RANGE",
+        "Copy the first column of a range across the rest of it.",
+        "fill_right A2:F2",
+    ),
+    topic(
+        "clear",
+        "clear RANGE",
+        "Empty cells and recalculate the cells that read them.",
+        "clear A1:C10",
+    ),
+    topic(
+        "format",
+        "format RANGE thousands|fixed N|percent|currency [SYMBOL]|scientific|date|general",
+        "Set how values are displayed; general removes the format.",
+        "format B2:B20 fixed 2",
+    ),
+    topic(
+        "insert_row",
+        "insert_row ROW",
+        "Insert an empty row before a row.",
+        "insert_row 5",
+    ),
+    topic(
+        "delete_row",
+        "delete_row ROW",
+        "Delete a row; references to it show #REF!.",
+        "delete_row 5",
+    ),
+    topic(
+        "insert_col",
+        "insert_col COL",
+        "Insert an empty column before a column.",
+        "insert_col C",
+    ),
+    topic(
+        "delete_col",
+        "delete_col COL",
+        "Delete a column; references to it show #REF!.",
+        "delete_col C",
+    ),
+    topic(
+        "resize",
+        "resize ROWS COLS",
+        "Grow or shrink the sheet.",
+        "resize 50 30",
+    ),
+    topic(
+        "add_sheet",
+        "add_sheet NAME",
+        "Add an empty sheet.",
+        "add_sheet Data",
+    ),
+    topic(
+        "switch_sheet",
+        "switch_sheet NAME",
+        "Make another sheet the active one.",
+        "switch_sheet Data",
+    ),
+    topic(
+        "rename_sheet",
+        "rename_sheet OLD NEW",
+        "Rename a sheet and the references to it.",
+        "rename_sheet Data Input",
+    ),
+    topic(
+        "delete_sheet",
+        "delete_sheet NAME",
+        "Delete a sheet.",
+        "delete_sheet Data",
+    ),
+    topic("sheets", "sheets", "List the sheets.", "sheets"),
+    topic(
+        "define",
+        "define NAME = RANGE|CELL|NUMBER",
+        "Name a range, a cell or a constant for use in formulas.",
+        "define Revenue = B2:B365",
+    ),
+    topic("names", "names", "List the defined names.", "names"),
+    topic(
+        "find",
+        "find TEXT",
+        "List the cells whose formula contains the text or whose value equals the number.",
+        "find SUM",
+    ),
+    topic(
+        "replace",
+        "replace OLD with NEW",
+        "Rewrite whole references in the formulas of the active sheet.",
+        "replace A1 with B1",
+    ),
+    topic(
+        "show",
+        "show CELL",
+        "Print a cell's formula, value, precedents and dependents.",
+        "show B3",
+    ),
+    topic(
+        "trace",
+        "trace CELL",
+        "Print the trees of everything a cell reads and everything that reads it.",
+        "trace B3",
+    ),
+    topic(
+        "chart",
+        "chart line|bar|scatter RANGE FILE [title=TEXT] [xlabel=TEXT] [ylabel=TEXT]",
+        "Render a chart of a row, a column or two columns to SVG or PNG.",
+        "chart line A1:A20 out.svg title=\"Sales\"",
+    ),
+    topic(
+        "plot",
+        "plot RANGE [bar|line]",
+        "Draw a row or column as a bar or line chart in the terminal.",
+        "plot A1:A30 line",
+    ),
+    topic(
+        "spark",
+        "spark RANGE",
+        "Draw a row or column as a one-line sparkline with its minimum, maximum and last value.",
+        "spark A1:A30",
+    ),
+    topic(
+        "export_img",
+        "export_img RANGE FILE [SCALE]",
+        "Write a range as an image, reading each value as 0xRRGGBB.",
+        "export_img A1:T20 out.png 4",
+    ),
+    topic(
+        "import_img",
+        "import_img FILE CELL",
+        "Load an image into the sheet, top-left pixel at the cell.",
+        "import_img picture.png A1",
+    ),
+    topic(
+        "export_md",
+        "export_md RANGE FILE [formulas]",
+        "Write a range as a Markdown table.",
+        "export_md A1:F20 out.md",
+    ),
+    topic(
+        "export_html",
+        "export_html RANGE FILE [formulas]",
+        "Write a range as an HTML table.",
+        "export_html A1:F20 out.html formulas",
+    ),
+    topic(
+        "web",
+        "web FILE",
+        "Open the web interface on a saved sheet.",
+        "web sheet.json",
+    ),
+    topic(
+        "web_start",
+        "web_start",
+        "Open the web interface on the current sheet.",
+        "web_start",
+    ),
+    topic(
+        "record",
+        "record start NAME [global] | record stop",
+        "Record the edits made to the sheet as a macro, kept with the sheet or, with global, in the user macro file.",
+        "record start totals",
+    ),
+    topic(
+        "play",
+        "play NAME [at CELL]",
+        "Replay a macro as one undo step, moving its relative references so that its top-left cell lands on CELL.",
+        "play totals at D1",
+    ),
+    topic(
+        "macros",
+        "macros",
+        "List the macros of the sheet and of the user macro file.",
+        "macros",
+    ),
+    topic(
+        "help",
+        "help [COMMAND|FUNCTION]",
+        "List every command and function, or describe one of them.",
+        "help SUM",
+    ),
+    topic("q", "q", "Quit.", "q"),
+];
+
+/// Functions that can be used in formulas.
+pub const FUNCTION_TOPICS: &[Topic] = &[
+    topic(
+        "SUM",
+        "SUM(RANGE|NAME)",
+        "Sum of the values in a range.",
+        "B2=SUM(A1:A10)",
+    ),
+    topic(
+        "AVG",
+        "AVG(RANGE|NAME)",
+        "Average of the values in a range, as a whole number.",
+        "D4=AVG(B1:B5)",
+    ),
+    topic(
+        "MIN",
+        "MIN(RANGE|NAME)",
+        "Smallest value in a range.",
+        "C1=MIN(A:A)",
+    ),
+    topic(
+        "MAX",
+        "MAX(RANGE|NAME)",
+        "Largest value in a range.",
+        "C2=MAX(2:2)",
+    ),
+    topic(
+        "STDEV",
+        "STDEV(RANGE|NAME)",
+        "Standard deviation of the values in a range, rounded.",
+        "E1=STDEV(Data!A1:A100)",
+    ),
+    topic(
+        "SLEEP",
+        "SLEEP(CELL|NUMBER)",
+        "Wait for a number of seconds, then take that number as the value.",
+        "A1=SLEEP(2)",
+    ),
+];
+
+/// Width of the syntax column in the `help` listing.
+const LISTING_WIDTH: usize = 36;
+
+/// Finds the topic of a command or function; function names may be typed in any case.
+pub fn find_topic(name: &str) -> Option<&'static Topic> {
+    COMMAND_TOPICS
+        .iter()
+        .find(|topic| topic.name == name)
+        .or_else(|| {
+            FUNCTION_TOPICS
+                .iter()
+                .find(|topic| topic.name.eq_ignore_ascii_case(name))
+        })
+}
+
+/// Text printed by `help`: the syntax and an example of everything when no name is
+/// given, otherwise the description of one command or function.
+///
+/// Returns `None` for an unknown name.
+pub fn help_text(name: Option<&str>) -> Option<String> {
+    if let Some(name) = name {
+        let topic = find_topic(name)?;
+        return Some(format!(
+            "{}\n  {}\n  Example: {}",
+            topic.syntax, topic.about, topic.example
+        ));
+    }
+    // longer syntaxes get their example on a line of its own
+    let list = |topics: &[Topic]| {
+        topics
+            .iter()
+            .map(|topic| {
+                if topic.syntax.len() <= LISTING_WIDTH {
+                    format!("  {:<LISTING_WIDTH$}  e.g. {}", topic.syntax, topic.example)
+                } else {
+                    format!(
+                        "  {}\n  {:LISTING_WIDTH$}  e.g. {}",
+                        topic.syntax, "", topic.example
+                    )
+                }
+            })
+            .collect::<Vec<String>>()
+    };
+    let mut lines = vec!["Commands:".to_string()];
+    lines.extend(list(COMMAND_TOPICS));
+    lines.push("Functions:".to_string());
+    lines.extend(list(FUNCTION_TOPICS));
+    lines.push("Type help NAME for details, e.g. help sort or help SUM.".to_string());
+    Some(lines.join("\n"))
+}
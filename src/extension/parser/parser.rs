@@ -8,14 +8,115 @@
 /// - Supporting arithmetic, sleep, and range-based operations like SUM, AVG, etc.
 use crate::extension::common::Operation;
 use crate::extension::common::Value;
+use crate::extension::common::{Anchor, Anchors};
+use crate::extension::common::{ChartKind, ChartSpec, NumberFormat, SortKey, ViewChange};
+use crate::extension::common::{MAX_COLUMNS, MAX_ROWS, MAX_SCALE};
+use crate::extension::parser::help::find_topic;
 
-fn is_cell(exp: &str, columns: &usize, rows: &usize) -> Option<Value> {
+/// Keywords that start a command, as offered by tab completion in the terminal.
+pub const COMMANDS: &[&str] = &[
+    "add_sheet",
+    "chart",
+    "clear",
+    "copy",
+    "define",
+    "delete_col",
+    "delete_row",
+    "delete_sheet",
+    "disable_output",
+    "enable_output",
+    "export_html",
+    "export_img",
+    "export_md",
+    "fill_down",
+    "fill_right",
+    "find",
+    "format",
+    "help",
+    "import_img",
+    "insert_col",
+    "insert_row",
+    "load",
+    "macros",
+    "move",
+    "names",
+    "open",
+    "play",
+    "plot",
+    "record",
+    "redo",
+    "rename_sheet",
+    "replace",
+    "resize",
+    "save",
+    "scroll_to",
+    "set",
+    "sheets",
+    "show",
+    "show_formulas",
+    "sort",
+    "spark",
+    "switch_sheet",
+    "trace",
+    "undo",
+    "web",
+    "web_start",
+];
+
+/// Commands whose arguments are file names, sheet names or free text rather than cells.
+pub const TEXT_COMMANDS: &[&str] = &[
+    "add_sheet",
+    "delete_sheet",
+    "switch_sheet",
+    "rename_sheet",
+    "save",
+    "open",
+    "load",
+    "web",
+    "find",
+    "replace",
+    "resize",
+    "help",
+];
+
+/// Functions that can be used in formulas.
+pub const FUNCTIONS: &[&str] = &["AVG", "MAX", "MIN", "SLEEP", "STDEV", "SUM"];
+
+/// Removes the `$` markers of an absolute reference (`$A$1`, `A$1`, `$A1`), returning
+/// the plain reference and which of its parts the markers anchored.
+///
+/// Returns `None` if a `$` appears anywhere else.
+pub(super) fn strip_anchors(exp: &str) -> Option<(String, Anchor)> {
+    let rest = exp.strip_prefix('$').unwrap_or(exp);
+    let letters = rest
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(rest.len());
+    let (col, row) = rest.split_at(letters);
+    let anchor = Anchor {
+        row: row.starts_with('$'),
+        col: exp.starts_with('$'),
+    };
+    let row = row.strip_prefix('$').unwrap_or(row);
+    if row.contains('$') {
+        return None;
+    }
+    Some((format!("{}{}", col, row), anchor))
+}
+
+pub(super) fn is_cell(exp: &str, columns: &usize, rows: &usize) -> Option<Value> {
+    is_anchored_cell(exp, columns, rows).map(|(cell, _)| cell)
+}
+
+/// Parses a cell like `is_cell`, also returning which of its parts are absolute.
+fn is_anchored_cell(exp: &str, columns: &usize, rows: &usize) -> Option<(Value, Anchor)> {
+    let (exp, anchor) = strip_anchors(exp)?;
+    let exp = &exp;
     let mut col = 0;
     let mut row = 0;
 
     let chars: Vec<char> = exp.chars().collect();
     let mut i = 0;
-    while i < 3 {
+    while i < 3 && i < chars.len() {
         if chars[i].is_alphabetic() {
             col = col * 26 + (chars[i] as u8 - b'A') as usize + 1;
         } else {
@@ -34,13 +135,13 @@ fn is_cell(exp: &str, columns: &usize, rows: &usize) -> Option<Value> {
         }
         i += 1;
     }
-    if row > *rows || col > *columns {
+    if row == 0 || row > *rows || col > *columns {
         return None;
     }
-    Some(Value::Cell(row, col))
+    Some((Value::Cell(row, col), anchor))
 }
 
-fn is_const(exp: &str) -> Option<Value> {
+pub(super) fn is_const(exp: &str) -> Option<Value> {
     // let mut ans = 0;
     // for c in exp.chars() {
     //     if c.is_numeric() {
@@ -56,20 +157,402 @@ fn is_const(exp: &str) -> Option<Value> {
     }
 }
 
-fn is_cell_or_const(exp: &str, rows: &usize, columns: &usize) -> Option<Value> {
+/// Returns true for a usable sheet name: letters, digits and underscores, starting with a letter.
+pub fn is_sheet_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Parses a reference to another sheet such as `Data!B3`.
+///
+/// The size of the other sheet is not known here, so the cell is only checked
+/// against the largest sheet; the backend checks it against the real one.
+fn is_sheet_cell(exp: &str) -> Option<(Value, Anchor)> {
+    let (sheet, cell) = exp.split_once('!')?;
+    if !is_sheet_name(sheet) {
+        return None;
+    }
+    let (cell, anchor) = is_anchored_cell(cell, &MAX_COLUMNS, &MAX_ROWS)?;
+    Some((Value::SheetRef(sheet.to_string(), Box::new(cell)), anchor))
+}
+
+/// Functions that cannot be used as names.
+const RESERVED_NAMES: [&str; 6] = ["SUM", "AVG", "MIN", "MAX", "STDEV", "SLEEP"];
+
+/// Returns true if `exp` is shaped like a cell (`B7`, `$AA$10`), inside the sheet or not.
+pub(super) fn looks_like_cell(exp: &str) -> bool {
+    let Some((exp, _)) = strip_anchors(exp) else {
+        return false;
+    };
+    let letters = exp.chars().take_while(|c| c.is_ascii_uppercase()).count();
+    let digits = &exp[letters..];
+    (1..=3).contains(&letters) && !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Returns true for a name usable with `define`: it follows the rules for sheet
+/// names but must not read as a cell reference, even one outside the sheet like `B0`,
+/// or a function.
+pub fn is_name(name: &str) -> bool {
+    is_sheet_name(name) && !looks_like_cell(name) && !RESERVED_NAMES.contains(&name)
+}
+
+/// Parses an operand of a formula along with the anchors of a cell reference.
+fn is_cell_or_const(exp: &str, rows: &usize, columns: &usize) -> Option<(Value, Anchor)> {
     if let Some(constant) = is_const(exp) {
-        Some(constant)
-    } else if let Some(cell) = is_cell(exp, rows, columns) {
+        Some((constant, Anchor::default()))
+    } else if exp.contains('!') {
+        is_sheet_cell(exp)
+    } else if let Some(cell) = is_anchored_cell(exp, rows, columns) {
         return Some(cell);
+    } else if is_name(exp) {
+        Some((Value::Name(exp.to_string()), Anchor::default()))
     } else {
         return None;
     }
 }
 
+/// Parses a range such as `A1:C5` into its top-left and bottom-right cells.
+///
+/// Returns `None` if either corner is not a cell inside the sheet or the corners are reversed.
+pub fn parse_range(range: &str, rows: &usize, columns: &usize) -> Option<(Value, Value)> {
+    let (start, end) = range.trim().split_once(':')?;
+    let start = is_cell(start.trim(), columns, rows)?;
+    let end = is_cell(end.trim(), columns, rows)?;
+    if start.row() > end.row() || start.col() > end.col() {
+        return None;
+    }
+    Some((start, end))
+}
+
+/// Parses a single cell reference such as `B7`, checking it lies inside the sheet.
+pub fn parse_cell(cell: &str, rows: &usize, columns: &usize) -> Option<Value> {
+    is_cell(cell.trim(), columns, rows)
+}
+
+/// Splits command arguments on whitespace, keeping double-quoted text together.
+///
+/// The quotes themselves are dropped, so `title="Q1 sales"` becomes `title=Q1 sales`.
+/// Returns `None` if a quote is left open.
+pub fn split_args(args: &str) -> Option<Vec<String>> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut started = false;
+    for c in args.chars() {
+        if c == '"' {
+            quoted = !quoted;
+            started = true;
+        } else if c.is_whitespace() && !quoted {
+            if started {
+                tokens.push(std::mem::take(&mut current));
+                started = false;
+            }
+        } else {
+            current.push(c);
+            started = true;
+        }
+    }
+    if quoted {
+        return None;
+    }
+    if started {
+        tokens.push(current);
+    }
+    Some(tokens)
+}
+
+/// Parses `chart <line|bar|scatter> <range> <file> [title=..] [xlabel=..] [ylabel=..]`.
+fn parse_chart(args: &str, rows: &usize, columns: &usize) -> Option<Value> {
+    let tokens = split_args(args)?;
+    if tokens.len() < 3 {
+        return None;
+    }
+    let kind = match tokens[0].as_str() {
+        "line" => ChartKind::Line,
+        "bar" => ChartKind::Bar,
+        "scatter" => ChartKind::Scatter,
+        _ => return None,
+    };
+    let (start, end) = parse_range(&tokens[1], rows, columns)?;
+    let mut spec = ChartSpec {
+        kind,
+        path: tokens[2].clone(),
+        title: String::new(),
+        x_label: String::new(),
+        y_label: String::new(),
+    };
+    for option in &tokens[3..] {
+        let (key, value) = option.split_once('=')?;
+        match key {
+            "title" => spec.title = value.to_string(),
+            "xlabel" => spec.x_label = value.to_string(),
+            "ylabel" => spec.y_label = value.to_string(),
+            _ => return None,
+        }
+    }
+    Some(Value::Oper(
+        Some(Box::new(start)),
+        Some(Box::new(end)),
+        Operation::Chart(spec),
+    ))
+}
+
+/// Parses `export_img <range> <file> [scale]`.
+fn parse_export_image(args: &str, rows: &usize, columns: &usize) -> Option<Value> {
+    let tokens = split_args(args)?;
+    let scale = match tokens.len() {
+        2 => 1,
+        3 => tokens[2].parse::<u32>().ok()?,
+        _ => return None,
+    };
+    if !(1..=MAX_SCALE).contains(&scale) {
+        return None;
+    }
+    let (start, end) = parse_range(&tokens[0], rows, columns)?;
+    Some(Value::Oper(
+        Some(Box::new(start)),
+        Some(Box::new(end)),
+        Operation::ExportImage(tokens[1].clone(), scale),
+    ))
+}
+
+/// Parses `<range> <file> [formulas]` for the table exports; `html` picks HTML over Markdown.
+fn parse_export_table(args: &str, html: bool, rows: &usize, columns: &usize) -> Option<Value> {
+    let tokens = split_args(args)?;
+    let formulas = match tokens.len() {
+        2 => false,
+        3 if tokens[2] == "formulas" => true,
+        _ => return None,
+    };
+    let (start, end) = parse_range(&tokens[0], rows, columns)?;
+    let path = tokens[1].clone();
+    let operation = if html {
+        Operation::ExportHtml(path, formulas)
+    } else {
+        Operation::ExportMarkdown(path, formulas)
+    };
+    Some(Value::Oper(
+        Some(Box::new(start)),
+        Some(Box::new(end)),
+        operation,
+    ))
+}
+
+/// Parses a range such as `A1:C5`, or a single cell taken as a one-cell range.
+fn parse_block(block: &str, rows: &usize, columns: &usize) -> Option<(Value, Value)> {
+    if block.contains(':') {
+        parse_range(block, rows, columns)
+    } else {
+        let cell = parse_cell(block, rows, columns)?;
+        Some((cell.clone(), cell))
+    }
+}
+
+/// Parses the format of the `format` command; `general` removes the format.
+fn parse_number_format(tokens: &[&str]) -> Option<Option<NumberFormat>> {
+    let format = match tokens {
+        ["general"] => return Some(None),
+        ["thousands"] => NumberFormat::Thousands,
+        ["fixed", decimals] => {
+            let decimals = decimals.parse::<usize>().ok()?;
+            (decimals <= 10).then_some(NumberFormat::Fixed(decimals))?
+        }
+        ["percent"] => NumberFormat::Percent,
+        ["currency"] => NumberFormat::Currency("$".to_string()),
+        ["currency", symbol] => NumberFormat::Currency(symbol.to_string()),
+        ["scientific"] => NumberFormat::Scientific,
+        ["date"] => NumberFormat::Date,
+        _ => return None,
+    };
+    Some(Some(format))
+}
+
+/// Converts column letters such as `AB` into a column number (A = 1).
+pub fn column_number(letters: &str) -> Option<usize> {
+    if letters.is_empty() || letters.len() > 3 {
+        return None;
+    }
+    letters.chars().try_fold(0, |col, c| {
+        c.is_ascii_uppercase()
+            .then(|| col * 26 + (c as u8 - b'A') as usize + 1)
+    })
+}
+
+/// Parses `<range> by <col> [asc|desc], ...` for the `sort` command.
+///
+/// Every key column must lie inside the range.
+fn parse_sort(args: &str, rows: &usize, columns: &usize) -> Option<Value> {
+    let (range, keys) = args.split_once(" by ")?;
+    let (start, end) = parse_range(range, rows, columns)?;
+    let keys = keys
+        .split(',')
+        .map(|key| {
+            let mut words = key.split_whitespace();
+            let col = column_number(words.next()?)?;
+            let descending = match words.next() {
+                None | Some("asc") => false,
+                Some("desc") => true,
+                Some(_) => return None,
+            };
+            if words.next().is_some() || col < start.col() || col > end.col() {
+                return None;
+            }
+            Some(SortKey { col, descending })
+        })
+        .collect::<Option<Vec<SortKey>>>()?;
+    Some(Value::Oper(
+        Some(Box::new(start)),
+        Some(Box::new(end)),
+        Operation::Sort(keys),
+    ))
+}
+
+/// Maps the name of a range function to its operation.
+fn range_operation(name: &str) -> Option<Operation> {
+    match name {
+        "SUM" => Some(Operation::Sum),
+        "AVG" => Some(Operation::Avg),
+        "STDEV" => Some(Operation::Std),
+        "MIN" => Some(Operation::Min),
+        "MAX" => Some(Operation::Max),
+        _ => None,
+    }
+}
+
+/// Parses the corners of a range over whole columns (`A:C`) or whole rows (`2:4`).
+///
+/// Returns `None` unless both corners are columns or both are rows, in order and inside the sheet.
+pub(super) fn parse_lines(
+    start: &str,
+    end: &str,
+    rows: &usize,
+    columns: &usize,
+) -> Option<(Value, Value)> {
+    anchored_lines(start, end, rows, columns).map(|(lines, _)| lines)
+}
+
+/// Parses whole columns or rows like `parse_lines`, also returning the anchors of the corners.
+///
+/// A whole row such as `$2` can only be anchored on its row.
+fn anchored_lines(
+    start: &str,
+    end: &str,
+    rows: &usize,
+    columns: &usize,
+) -> Option<((Value, Value), Anchors)> {
+    let ((start, first_anchor), (end, last_anchor)) =
+        (strip_anchors(start.trim())?, strip_anchors(end.trim())?);
+    if let (Some(first), Some(last)) = (column_number(&start), column_number(&end)) {
+        return (first <= last && last <= *columns).then_some((
+            (Value::Column(first), Value::Column(last)),
+            [first_anchor, last_anchor],
+        ));
+    }
+    let (first, last) = (start.parse::<usize>().ok()?, end.parse::<usize>().ok()?);
+    let row_anchor = |anchor: Anchor| Anchor {
+        row: anchor.row || anchor.col,
+        col: false,
+    };
+    (1 <= first && first <= last && last <= *rows).then_some((
+        (Value::Row(first), Value::Row(last)),
+        [row_anchor(first_anchor), row_anchor(last_anchor)],
+    ))
+}
+
+/// Parses `<name> = <range|cell|constant>` for the `define` command.
+///
+/// The definition is kept in the boxes of the returned `Value::Oper`: both corners
+/// of a range (a single cell is a one-cell range), or a constant and `None`.
+/// A range may be on another sheet, as in `Data!B2:B365`.
+fn parse_define(args: &str, rows: &usize, columns: &usize) -> Option<Value> {
+    let (name, definition) = args.split_once('=')?;
+    let (name, definition) = (name.trim(), definition.trim());
+    if !is_name(name) {
+        return None;
+    }
+    let operation = Operation::Define(name.to_string());
+    if let Some(constant) = is_const(definition) {
+        return Some(Value::Oper(Some(Box::new(constant)), None, operation));
+    }
+    let (start, end) = match definition.split_once('!') {
+        Some((sheet, block)) if is_sheet_name(sheet) => {
+            let (start, end) = parse_block(block, &MAX_ROWS, &MAX_COLUMNS)?;
+            (
+                Value::SheetRef(sheet.to_string(), Box::new(start)),
+                Value::SheetRef(sheet.to_string(), Box::new(end)),
+            )
+        }
+        Some(_) => return None,
+        None => parse_block(definition, rows, columns)?,
+    };
+    Some(Value::Oper(
+        Some(Box::new(start)),
+        Some(Box::new(end)),
+        operation,
+    ))
+}
+
+/// Parses the target of `insert_row`/`delete_row` (a row number) or
+/// `insert_col`/`delete_col` (column letters).
+///
+/// Inserting may also target the position just past the last row or column.
+fn parse_line(args: &str, row: bool, insert: bool, rows: &usize, columns: &usize) -> Option<usize> {
+    let (at, size) = if row {
+        (args.trim().parse::<usize>().ok()?, *rows)
+    } else {
+        (column_number(args.trim())?, *columns)
+    };
+    let last = if insert { size + 1 } else { size };
+    (1..=last).contains(&at).then_some(at)
+}
+
+/// Parses `view <rows>x<columns>`, `view auto` or `width <col> <width>` for the `set` command.
+fn parse_set(args: &str, columns: &usize) -> Option<ViewChange> {
+    let tokens: Vec<&str> = args.split_whitespace().collect();
+    match tokens.as_slice() {
+        ["view", "auto"] => Some(ViewChange::Auto),
+        ["view", size] => {
+            let (rows, cols) = size.split_once('x')?;
+            let (rows, cols) = (rows.parse::<usize>().ok()?, cols.parse::<usize>().ok()?);
+            (rows > 0 && cols > 0).then_some(ViewChange::Size(rows, cols))
+        }
+        ["width", col, width] => {
+            let col = column_number(col)?;
+            let width = width.parse::<usize>().ok()?;
+            (col <= *columns && width > 0).then_some(ViewChange::Width(col, width))
+        }
+        _ => None,
+    }
+}
+
 pub fn validate(
     cmd: &str,
     rows: &usize,
     columns: &usize,
+) -> Option<(Option<Value>, Option<Value>)> {
+    validate_with(cmd, rows, columns, &mut Details::default())
+}
+
+/// Why a command was rejected: the cause and, when it points at one place, the
+/// 1-based character column of the command where the problem starts.
+pub type ParseError = (String, Option<usize>);
+
+/// What `validate_with` finds out about an assignment besides its values.
+#[derive(Debug, Default)]
+pub struct Details {
+    /// Anchors of the operands of the formula
+    pub anchors: Anchors,
+    /// The first problem found, if the assignment was rejected
+    pub error: Option<ParseError>,
+}
+
+/// Parses a command like `validate`, also filling in `details` when the command
+/// assigns a formula to a cell.
+pub fn validate_with(
+    cmd: &str,
+    rows: &usize,
+    columns: &usize,
+    details: &mut Details,
 ) -> Option<(Option<Value>, Option<Value>)> {
     match cmd.trim() {
         "undo" => return Some((None, Some(Value::Oper(None, None, Operation::Undo)))),
@@ -124,6 +607,323 @@ pub fn validate(
         ));
     }
 
+    if cmd.trim().starts_with("open ") || cmd.trim().starts_with("load ") {
+        // both keywords are four letters long, so the path starts at the same offset
+        let file_name = cmd.trim()["open ".len()..].trim().to_string();
+        if file_name.is_empty() {
+            return None;
+        }
+        return Some((
+            None,
+            Some(Value::Oper(None, None, Operation::Open(file_name))),
+        ));
+    }
+
+    if cmd.trim().starts_with("chart ") {
+        // validate receives (columns, rows), see Backend::process_command
+        let chart = parse_chart(&cmd.trim()["chart ".len()..], columns, rows)?;
+        return Some((None, Some(chart)));
+    }
+
+    if let Some(args) = cmd.trim().strip_prefix("plot ") {
+        let tokens: Vec<&str> = args.split_whitespace().collect();
+        let (range, kind) = match tokens.as_slice() {
+            [range] | [range, "bar"] => (range, ChartKind::Bar),
+            [range, "line"] => (range, ChartKind::Line),
+            _ => return None,
+        };
+        let (start, end) = parse_range(range, columns, rows)?;
+        return Some((
+            None,
+            Some(Value::Oper(
+                Some(Box::new(start)),
+                Some(Box::new(end)),
+                Operation::Plot(kind),
+            )),
+        ));
+    }
+    if let Some(range) = cmd.trim().strip_prefix("spark ") {
+        let (start, end) = parse_range(range, columns, rows)?;
+        return Some((
+            None,
+            Some(Value::Oper(
+                Some(Box::new(start)),
+                Some(Box::new(end)),
+                Operation::Spark,
+            )),
+        ));
+    }
+
+    if cmd.trim().starts_with("export_img ") {
+        let export = parse_export_image(&cmd.trim()["export_img ".len()..], columns, rows)?;
+        return Some((None, Some(export)));
+    }
+
+    if cmd.trim().starts_with("export_md ") {
+        let export = parse_export_table(&cmd.trim()["export_md ".len()..], false, columns, rows)?;
+        return Some((None, Some(export)));
+    }
+
+    if cmd.trim().starts_with("export_html ") {
+        let export = parse_export_table(&cmd.trim()["export_html ".len()..], true, columns, rows)?;
+        return Some((None, Some(export)));
+    }
+
+    if cmd.trim().starts_with("import_img ") {
+        let tokens = split_args(&cmd.trim()["import_img ".len()..])?;
+        let [path, cell] = tokens.as_slice() else {
+            return None;
+        };
+        let cell = is_cell(cell, rows, columns)?;
+        return Some((
+            Some(cell),
+            Some(Value::Oper(
+                None,
+                None,
+                Operation::ImportImage(path.clone()),
+            )),
+        ));
+    }
+
+    for (keyword, operation) in [("copy ", Operation::Copy), ("move ", Operation::Move)] {
+        if let Some(args) = cmd.trim().strip_prefix(keyword) {
+            let tokens = split_args(args)?;
+            let [block, to, target] = tokens.as_slice() else {
+                return None;
+            };
+            if to != "to" {
+                return None;
+            }
+            let (start, end) = parse_block(block, columns, rows)?;
+            let target = is_cell(target, rows, columns)?;
+            return Some((
+                Some(target),
+                Some(Value::Oper(
+                    Some(Box::new(start)),
+                    Some(Box::new(end)),
+                    operation,
+                )),
+            ));
+        }
+    }
+
+    if cmd.trim() == "sheets" {
+        return Some((None, Some(Value::Oper(None, None, Operation::ListSheets))));
+    }
+    for keyword in [
+        "add_sheet ",
+        "delete_sheet ",
+        "switch_sheet ",
+        "rename_sheet ",
+    ] {
+        if let Some(args) = cmd.trim().strip_prefix(keyword) {
+            let names: Vec<&str> = args.split_whitespace().collect();
+            if !names.iter().all(|name| is_sheet_name(name)) {
+                return None;
+            }
+            let operation = match (keyword, names.as_slice()) {
+                ("add_sheet ", [name]) => Operation::AddSheet(name.to_string()),
+                ("delete_sheet ", [name]) => Operation::DeleteSheet(name.to_string()),
+                ("switch_sheet ", [name]) => Operation::SwitchSheet(name.to_string()),
+                ("rename_sheet ", [old, new]) => {
+                    Operation::RenameSheet(old.to_string(), new.to_string())
+                }
+                _ => return None,
+            };
+            return Some((None, Some(Value::Oper(None, None, operation))));
+        }
+    }
+
+    if cmd.trim().starts_with("sort ") {
+        let sort = parse_sort(&cmd.trim()["sort ".len()..], columns, rows)?;
+        return Some((None, Some(sort)));
+    }
+
+    if let Some(block) = cmd.trim().strip_prefix("clear ") {
+        let (start, end) = parse_block(block, columns, rows)?;
+        return Some((
+            None,
+            Some(Value::Oper(
+                Some(Box::new(start)),
+                Some(Box::new(end)),
+                Operation::Clear,
+            )),
+        ));
+    }
+
+    if let Some(args) = cmd.trim().strip_prefix("format ") {
+        let tokens: Vec<&str> = args.split_whitespace().collect();
+        let (block, spec) = tokens.split_first()?;
+        let (start, end) = parse_block(block, columns, rows)?;
+        let format = parse_number_format(spec)?;
+        return Some((
+            None,
+            Some(Value::Oper(
+                Some(Box::new(start)),
+                Some(Box::new(end)),
+                Operation::Format(format),
+            )),
+        ));
+    }
+
+    for (keyword, operation) in [
+        ("fill_down ", Operation::FillDown),
+        ("fill_right ", Operation::FillRight),
+    ] {
+        if let Some(range) = cmd.trim().strip_prefix(keyword) {
+            let (start, end) = parse_range(range, columns, rows)?;
+            return Some((
+                None,
+                Some(Value::Oper(
+                    Some(Box::new(start)),
+                    Some(Box::new(end)),
+                    operation,
+                )),
+            ));
+        }
+    }
+
+    if let Some(args) = cmd.trim().strip_prefix("resize ") {
+        let size: Vec<usize> = args
+            .split_whitespace()
+            .map(|n| n.parse::<usize>().ok())
+            .collect::<Option<Vec<usize>>>()?;
+        let [rows, columns] = size.as_slice() else {
+            return None;
+        };
+        return Some((
+            None,
+            Some(Value::Oper(None, None, Operation::Resize(*rows, *columns))),
+        ));
+    }
+
+    for (keyword, row, insert) in [
+        ("insert_row ", true, true),
+        ("delete_row ", true, false),
+        ("insert_col ", false, true),
+        ("delete_col ", false, false),
+    ] {
+        if let Some(args) = cmd.trim().strip_prefix(keyword) {
+            let at = parse_line(args, row, insert, columns, rows)?;
+            let operation = match (row, insert) {
+                (true, true) => Operation::InsertRow(at),
+                (true, false) => Operation::DeleteRow(at),
+                (false, true) => Operation::InsertCol(at),
+                (false, false) => Operation::DeleteCol(at),
+            };
+            return Some((None, Some(Value::Oper(None, None, operation))));
+        }
+    }
+
+    if cmd.trim() == "names" {
+        return Some((None, Some(Value::Oper(None, None, Operation::ListNames))));
+    }
+    if let Some(args) = cmd.trim().strip_prefix("define ") {
+        let define = parse_define(args, columns, rows)?;
+        return Some((None, Some(define)));
+    }
+
+    if let Some(text) = cmd.trim().strip_prefix("find ") {
+        let text = text.trim();
+        if text.is_empty() {
+            return None;
+        }
+        return Some((
+            None,
+            Some(Value::Oper(None, None, Operation::Find(text.to_string()))),
+        ));
+    }
+    if let Some(args) = cmd.trim().strip_prefix("replace ") {
+        let (old, new) = args.split_once(" with ")?;
+        let (old, new) = (old.trim(), new.trim());
+        if old.is_empty() || new.is_empty() {
+            return None;
+        }
+        return Some((
+            None,
+            Some(Value::Oper(
+                None,
+                None,
+                Operation::Replace(old.to_string(), new.to_string()),
+            )),
+        ));
+    }
+
+    for (keyword, operation) in [("show ", Operation::Show), ("trace ", Operation::Trace)] {
+        if let Some(cell) = cmd.trim().strip_prefix(keyword) {
+            let cell = is_cell(cell.trim(), rows, columns)?;
+            return Some((Some(cell), Some(Value::Oper(None, None, operation))));
+        }
+    }
+
+    if let Some(args) = cmd.trim().strip_prefix("show_formulas ") {
+        let shown = match args.trim() {
+            "on" => true,
+            "off" => false,
+            _ => return None,
+        };
+        return Some((
+            None,
+            Some(Value::Oper(
+                None,
+                None,
+                Operation::View(ViewChange::Formulas(shown)),
+            )),
+        ));
+    }
+
+    if cmd.trim() == "help" {
+        return Some((None, Some(Value::Oper(None, None, Operation::Help(None)))));
+    }
+    if let Some(name) = cmd.trim().strip_prefix("help ") {
+        let topic = find_topic(name.trim())?;
+        return Some((
+            None,
+            Some(Value::Oper(
+                None,
+                None,
+                Operation::Help(Some(topic.name.to_string())),
+            )),
+        ));
+    }
+
+    if cmd.trim() == "macros" {
+        return Some((None, Some(Value::Oper(None, None, Operation::ListMacros))));
+    }
+    if let Some(args) = cmd.trim().strip_prefix("record ") {
+        let operation = match args.split_whitespace().collect::<Vec<_>>().as_slice() {
+            ["start", name] if is_sheet_name(name) => {
+                Operation::RecordStart(name.to_string(), false)
+            }
+            ["start", name, "global"] if is_sheet_name(name) => {
+                Operation::RecordStart(name.to_string(), true)
+            }
+            ["stop"] => Operation::RecordStop,
+            _ => return None,
+        };
+        return Some((None, Some(Value::Oper(None, None, operation))));
+    }
+    if let Some(args) = cmd.trim().strip_prefix("play ") {
+        let (name, cell) = match args.split_whitespace().collect::<Vec<_>>().as_slice() {
+            [name] => (*name, None),
+            [name, "at", cell] => (*name, Some(is_cell(cell, rows, columns)?)),
+            _ => return None,
+        };
+        if !is_sheet_name(name) {
+            return None;
+        }
+        return Some((
+            cell,
+            Some(Value::Oper(None, None, Operation::Play(name.to_string()))),
+        ));
+    }
+
+    if let Some(args) = cmd.trim().strip_prefix("set ") {
+        // `rows` holds the number of columns here, see the chart command
+        let change = parse_set(args, rows)?;
+        return Some((None, Some(Value::Oper(None, None, Operation::View(change)))));
+    }
+
     if cmd.trim().starts_with("web ") {
         let file_name = cmd.trim()["web ".len()..].trim().to_string();
         return Some((
@@ -132,17 +932,197 @@ pub fn validate(
         ));
     }
 
-    let Some((cell, exp)) = cmd.split_once('=') else {
+    let (cell, func, anchors) = parse_formula(cmd, rows, columns, &mut details.error)?;
+    details.anchors = anchors;
+    Some((cell, func))
+}
+
+/// Parses `cmd` like `validate`, or explains why it is not a complete command.
+///
+/// For an assignment `validate` leaves out the target cell or the function it could
+/// not read instead of rejecting the whole command, so those are errors here.
+pub fn check(
+    cmd: &str,
+    rows: &usize,
+    columns: &usize,
+) -> Result<(Option<Value>, Option<Value>), ParseError> {
+    let mut details = Details::default();
+    let complete = |parsed: &(Option<Value>, Option<Value>)| match parsed {
+        (_, None) => false,
+        (None, Some(Value::Oper(_, _, op))) => !op.is_formula(),
+        _ => true,
+    };
+    match validate_with(cmd, rows, columns, &mut details) {
+        Some(parsed) if complete(&parsed) => Ok(parsed),
+        _ => Err(details
+            .error
+            .unwrap_or_else(|| command_error(cmd, rows, columns))),
+    }
+}
+
+/// Writes a `ParseError` the way the frontends show it, e.g. `unknown function FOO at col 4`.
+pub fn describe((cause, column): &ParseError) -> String {
+    match column {
+        Some(column) => format!("{} at col {}", cause, column),
+        None => cause.clone(),
+    }
+}
+
+/// Column of the character at byte `offset` of `cmd`, or `None` if `offset` is not
+/// on a character boundary.
+fn column(cmd: &str, offset: usize) -> Option<usize> {
+    Some(cmd.get(..offset)?.chars().count() + 1)
+}
+
+/// Trims `text`, which starts at byte `offset` of the command, and returns the
+/// offset of what is left along with it.
+fn trimmed(text: &str, offset: usize) -> (usize, &str) {
+    (offset + text.len() - text.trim_start().len(), text.trim())
+}
+
+/// Keeps the first problem found in an assignment.
+fn reject(error: &mut Option<ParseError>, cause: String, column: Option<usize>) {
+    error.get_or_insert((cause, column));
+}
+
+/// Explains why `token`, at byte `offset` of `cmd`, is not a cell of the sheet.
+///
+/// Takes the sheet size in the same order as `validate`.
+fn cell_error(cmd: &str, token: &str, offset: usize, rows: &usize, columns: &usize) -> ParseError {
+    if token.is_empty() {
+        ("expected a cell".to_string(), column(cmd, offset))
+    } else if looks_like_cell(token) {
+        // `rows` holds the number of columns here, see Backend::process_command
+        (
+            format!("{} is outside sheet {}x{}", token, columns, rows),
+            None,
+        )
+    } else {
+        (
+            format!("expected a cell, found '{}'", token),
+            column(cmd, offset),
+        )
+    }
+}
+
+/// Parses an operand of a formula, at byte `offset` of `cmd`, recording why it is
+/// not a number, a cell or a name.
+fn operand(
+    cmd: &str,
+    token: &str,
+    offset: usize,
+    rows: &usize,
+    columns: &usize,
+    error: &mut Option<ParseError>,
+) -> Option<(Value, Anchor)> {
+    if let Some(operand) = is_cell_or_const(token, rows, columns) {
+        return Some(operand);
+    }
+    let (cause, at) = match token.split_once('!') {
+        Some((sheet, _)) if !is_sheet_name(sheet) => (
+            format!("invalid sheet name '{}'", sheet),
+            column(cmd, offset),
+        ),
+        Some((sheet, cell)) => {
+            cell_error(cmd, cell, offset + sheet.len() + 1, &MAX_COLUMNS, &MAX_ROWS)
+        }
+        None if looks_like_cell(token) => cell_error(cmd, token, offset, rows, columns),
+        None if token.is_empty() => ("missing value".to_string(), column(cmd, offset)),
+        None => (
+            format!("expected a number, cell or name, found '{}'", token),
+            column(cmd, offset),
+        ),
+    };
+    reject(error, cause, at);
+    None
+}
+
+/// Explains a rejected command that is not an assignment: an unknown keyword, a cell
+/// outside the sheet or a reversed range among its arguments, or else its syntax.
+fn command_error(cmd: &str, rows: &usize, columns: &usize) -> ParseError {
+    let Some(keyword) = cmd.split_whitespace().next() else {
+        return ("empty command".to_string(), None);
+    };
+    let args = cmd.trim()[keyword.len()..].trim();
+    if !COMMANDS.contains(&keyword) && !["w", "a", "s", "d", "q"].contains(&keyword) {
+        let cause = if cmd.contains('=') {
+            "invalid formula".to_string()
+        } else {
+            format!("unknown command {}, type help for a list", keyword)
+        };
+        return (cause, None);
+    }
+    if keyword == "help" {
+        return (format!("no help for {}, type help for a list", args), None);
+    }
+    if !TEXT_COMMANDS.contains(&keyword) {
+        let mut offset = 0;
+        for word in cmd.split(' ') {
+            let at = offset;
+            offset += word.len() + 1;
+            let mut parts = word.split(|c: char| ",:=".contains(c));
+            if let Some(part) =
+                parts.find(|part| looks_like_cell(part) && is_cell(part, rows, columns).is_none())
+            {
+                return cell_error(cmd, part, at, rows, columns);
+            }
+            if let Some((start, end)) = word.split_once(':')
+                && let (Some(start), Some(end)) =
+                    (is_cell(start, rows, columns), is_cell(end, rows, columns))
+                && (start.row() > end.row() || start.col() > end.col())
+            {
+                return (format!("range {} is reversed", word), column(cmd, at));
+            }
+        }
+    }
+    match find_topic(keyword) {
+        Some(topic) => (format!("usage: {}", topic.syntax), None),
+        None => (format!("invalid arguments for {}", keyword), None),
+    }
+}
+
+/// Parses an assignment such as `B1=$A$1+A2` into its cell, its formula and the
+/// anchors of the formula's operands, in the same order as the boxes of `Value::Oper`.
+///
+/// The first problem found is kept in `error`, with its column in `cmd`.
+fn parse_formula(
+    cmd: &str,
+    rows: &usize,
+    columns: &usize,
+    error: &mut Option<ParseError>,
+) -> Option<(Option<Value>, Option<Value>, Anchors)> {
+    let Some((target, exp)) = cmd.split_once('=') else {
         // eprintln!("Could not find a valid exp being assigned to a valid cell");
         return None;
     };
 
-    let cell = String::from(cell).trim().to_string();
-    let cell = is_cell(&cell, rows, columns);
+    let (target_at, target) = trimmed(target, 0);
+    let cell = is_cell(target, rows, columns);
+    if cell.is_none() {
+        let (cause, at) = cell_error(cmd, target, target_at, rows, columns);
+        reject(error, cause, at);
+    }
+    // byte offset of `exp` in `cmd`
+    let exp_at = cmd.len() - exp.len();
+    let formula = |op1: Value, op2: Value, operation: Operation| {
+        Some(Value::Oper(
+            Some(Box::new(op1)),
+            Some(Box::new(op2)),
+            operation,
+        ))
+    };
 
     let Some((operation, range)) = exp.split_once('(') else {
         // basic math operations or constant (0-4)
-        let val = (String::from(exp)).trim().to_string();
+        let (val_at, val) = trimmed(exp, exp_at);
+        if val.is_empty() {
+            reject(
+                error,
+                "missing value after =".to_string(),
+                column(cmd, exp_at),
+            );
+            return None;
+        }
         let operators = ["+", "-", "*", "/"];
         for (i, c) in val.chars().enumerate() {
             if i == 0 && c == '-' {
@@ -150,144 +1130,169 @@ pub fn validate(
             }
             if operators.contains(&c.to_string().as_str()) {
                 let op1_str = val[..i].trim();
-                let op2_str = val[i + 1..].trim();
-                let op1 = is_cell_or_const(op1_str, rows, columns)?;
-                let op2 = is_cell_or_const(op2_str, rows, columns)?;
-
-                match c {
-                    '+' => {
-                        return Some((
-                            cell,
-                            Some(Value::Oper(
-                                Some(Box::new(op1)),
-                                Some(Box::new(op2)),
-                                Operation::Add,
-                            )),
-                        ));
-                    }
-                    '-' => {
-                        return Some((
-                            cell,
-                            Some(Value::Oper(
-                                Some(Box::new(op1)),
-                                Some(Box::new(op2)),
-                                Operation::Sub,
-                            )),
-                        ));
-                    }
-                    '*' => {
-                        return Some((
-                            cell,
-                            Some(Value::Oper(
-                                Some(Box::new(op1)),
-                                Some(Box::new(op2)),
-                                Operation::Mul,
-                            )),
-                        ));
-                    }
-                    '/' => {
-                        return Some((
-                            cell,
-                            Some(Value::Oper(
-                                Some(Box::new(op1)),
-                                Some(Box::new(op2)),
-                                Operation::Div,
-                            )),
-                        ));
-                    }
-                    _ => {
-                        // eprintln!("Invalid operation");
-                        return None;
-                    } //This case is not possible, just for compilation
+                let (op2_at, op2_str) = trimmed(&val[i + 1..], val_at + i + 1);
+                if op1_str.is_empty() || op2_str.is_empty() {
+                    let side = if op1_str.is_empty() {
+                        "before"
+                    } else {
+                        "after"
+                    };
+                    let cause = format!("missing operand {} {}", side, c);
+                    reject(error, cause, column(cmd, val_at + i));
+                    return None;
                 }
+                let (op1, anchor1) = operand(cmd, op1_str, val_at, rows, columns, error)?;
+                let (op2, anchor2) = operand(cmd, op2_str, op2_at, rows, columns, error)?;
+                let operation = match c {
+                    '+' => Operation::Add,
+                    '-' => Operation::Sub,
+                    '*' => Operation::Mul,
+                    '/' => Operation::Div,
+                    //This case is not possible, just for compilation
+                    _ => return None,
+                };
+                return Some((cell, formula(op1, op2, operation), [anchor1, anchor2]));
             }
         }
 
-        let val = is_cell_or_const(&val, rows, columns)?; //for the moment, assuming the RHS to be a constant or cell
-        // println!("{} {}", cmd, cmd); //---------------debugger
+        let (val, anchor) = operand(cmd, val, val_at, rows, columns, error)?; //for the moment, assuming the RHS to be a constant or cell
         return Some((
             cell,
-            Some(Value::Oper(
-                Some(Box::new(val)),
-                Some(Box::new(Value::Const(0))),
-                Operation::Cons,
-            )),
+            formula(val, Value::Const(0), Operation::Cons),
+            [anchor, Anchor::default()],
         ));
     };
-    let range = range.strip_suffix(')')?.to_string(); //removing the closing bracket
+    let (name_at, name) = trimmed(operation, exp_at);
+    if !FUNCTIONS.contains(&operation) {
+        let cause = if name.is_empty() {
+            "missing function name".to_string()
+        } else if name != operation && FUNCTIONS.contains(&name) {
+            format!("unexpected space around {}", name)
+        } else if FUNCTIONS.contains(&name.to_ascii_uppercase().as_str()) {
+            format!(
+                "unknown function {} (did you mean {}?)",
+                name,
+                name.to_ascii_uppercase()
+            )
+        } else {
+            format!("unknown function {}", name)
+        };
+        reject(error, cause, column(cmd, name_at));
+    }
+    // byte offset of the function's arguments in `cmd`
+    let range_at = exp_at + operation.len() + 1;
+    let Some(range) = range.strip_suffix(')') else {
+        reject(
+            error,
+            "missing )".to_string(),
+            column(cmd, range_at + range.len()),
+        );
+        return None;
+    };
+    let range = range.to_string(); //removing the closing bracket
 
+    // a named range, as in SUM(Revenue); SLEEP takes a single value instead
+    if operation != "SLEEP" && is_name(range.trim()) {
+        let name = Value::Name(range.trim().to_string());
+        let Some(oper) = range_operation(operation) else {
+            return Some((cell, None, Anchors::default()));
+        };
+        return Some((cell, formula(name.clone(), name, oper), Anchors::default()));
+    }
+    let (args_at, args) = trimmed(&range, range_at);
     let Some((start, end)) = range.split_once(':') else {
-        // SLEEP (the keyword 'SLEEP' is not checked for, it is taken fro granted)
-        let val = range;
-        let val = is_cell_or_const(&val, rows, columns);
-        if let Some(val) = val {
+        // SLEEP, the only function taking a single value
+        if operation != "SLEEP" {
+            let cause = if args.is_empty() {
+                format!("missing argument of {}", name)
+            } else {
+                format!("{} takes a range such as A1:A10, found '{}'", name, args)
+            };
+            reject(error, cause, column(cmd, args_at));
+            return Some((cell, None, Anchors::default()));
+        }
+        if let Some((val, anchor)) = operand(cmd, &range, range_at, rows, columns, error) {
             return Some((
                 cell,
-                Some(Value::Oper(
-                    Some(Box::new(val)),
-                    Some(Box::new(Value::Const(0))),
-                    Operation::Slp,
-                )),
+                formula(val, Value::Const(0), Operation::Slp),
+                [anchor, Anchor::default()],
             ));
         }
-        return Some((cell, None));
+        return Some((cell, None, Anchors::default()));
+    };
+    let end_at = range_at + start.len() + 1;
+    // validate receives (columns, rows), see Backend::process_command
+    if let Some(((start, end), anchors)) = anchored_lines(start, end, columns, rows) {
+        let Some(oper) = range_operation(operation) else {
+            let cause = format!("{} takes a single value, found '{}'", name, args);
+            reject(error, cause, column(cmd, args_at));
+            return Some((cell, None, Anchors::default()));
+        };
+        return Some((cell, formula(start, end, oper), anchors));
+    }
+    let whole_lines = |corner: &str| {
+        strip_anchors(corner.trim()).is_some_and(|(corner, _)| {
+            column_number(&corner).is_some() || corner.parse::<usize>().is_ok()
+        })
     };
-    let start = String::from(start);
-    let end = String::from(end);
-    let start = is_cell(&start, rows, columns)?;
-    let end = is_cell(&end, rows, columns)?;
+    if whole_lines(start) && whole_lines(end) {
+        let cause = format!(
+            "{} is not a range of rows or columns inside sheet {}x{}",
+            args, columns, rows
+        );
+        reject(error, cause, None);
+        return None;
+    }
+    // a range on another sheet names it once, as in SUM(Data!A1:A9)
+    let (sheet, start, start_at) = match start.split_once('!') {
+        Some((sheet, start)) if is_sheet_name(sheet) => {
+            (Some(sheet.to_string()), start, range_at + sheet.len() + 1)
+        }
+        Some((sheet, _)) => {
+            let cause = format!("invalid sheet name '{}'", sheet);
+            reject(error, cause, column(cmd, range_at));
+            return None;
+        }
+        None => (None, start, range_at),
+    };
+    let (rows, columns) = match sheet {
+        Some(_) => (&MAX_COLUMNS, &MAX_ROWS),
+        None => (rows, columns),
+    };
+    let corner = |token: &str, at: usize, error: &mut Option<ParseError>| {
+        let corner = is_anchored_cell(token, rows, columns);
+        if corner.is_none() {
+            let (cause, at) = cell_error(cmd, token, at, rows, columns);
+            reject(error, cause, at);
+        }
+        corner
+    };
+    let (start, start_anchor) = corner(start, start_at, error)?;
+    let (end, end_anchor) = corner(end, end_at, error)?;
     if let (Value::Cell(r, c), Value::Cell(r2, c2)) = (&start, &end) {
         if r > r2 || c > c2 {
             // eprintln!("Invalid range, start is greater than end");
+            let cause = format!("range {} is reversed", args);
+            reject(error, cause, column(cmd, args_at));
             return None;
         }
     } else {
         return None;
     }
-    match operation {
-        "SUM" => Some((
-            cell,
-            Some(Value::Oper(
-                Some(Box::new(start)),
-                Some(Box::new(end)),
-                Operation::Sum,
-            )),
-        )),
-        "AVG" => Some((
-            cell,
-            Some(Value::Oper(
-                Some(Box::new(start)),
-                Some(Box::new(end)),
-                Operation::Avg,
-            )),
-        )),
-        "STDEV" => Some((
-            cell,
-            Some(Value::Oper(
-                Some(Box::new(start)),
-                Some(Box::new(end)),
-                Operation::Std,
-            )),
-        )),
-        "MIN" => Some((
-            cell,
-            Some(Value::Oper(
-                Some(Box::new(start)),
-                Some(Box::new(end)),
-                Operation::Min,
-            )),
-        )),
-        "MAX" => Some((
-            cell,
-            Some(Value::Oper(
-                Some(Box::new(start)),
-                Some(Box::new(end)),
-                Operation::Max,
-            )),
-        )),
-        _ => {
-            // eprintln!("Invalid operation");
-            Some((cell, None))
+    let (start, end) = match sheet {
+        Some(sheet) => (
+            Value::SheetRef(sheet.clone(), Box::new(start)),
+            Value::SheetRef(sheet, Box::new(end)),
+        ),
+        None => (start, end),
+    };
+    match range_operation(operation) {
+        Some(oper) => Some((cell, formula(start, end, oper), [start_anchor, end_anchor])),
+        // eprintln!("Invalid operation");
+        None => {
+            let cause = format!("{} takes a single value, found '{}'", name, args);
+            reject(error, cause, column(cmd, args_at));
+            Some((cell, None, Anchors::default()))
         }
     }
 }
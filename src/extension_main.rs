@@ -1,13 +1,22 @@
 pub mod extension;
+pub mod script;
 #[allow(unused_imports)]
 use crate::extension::backend::backend::*;
 #[allow(unused_imports)]
+use crate::extension::frontend::script::run_script;
+#[allow(unused_imports)]
 use crate::extension::frontend::terminal::Frontend;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::extension::frontend::tui::Tui;
 #[allow(unused_imports)]
 use crate::extension::frontend::web::start_web_app;
 #[allow(unused_imports)]
+use crate::script::split_script_args;
+#[allow(unused_imports)]
 use std::env;
 #[allow(unused_imports)]
+use std::io::{self, IsTerminal};
+#[allow(unused_imports)]
 use std::process::{Command, Stdio};
 #[allow(unused_imports)]
 use std::thread;
@@ -16,10 +25,23 @@ use std::thread;
 ///
 /// By default starts terminal spreadsheet. If the user enters 'web' command,
 /// it will launch the web interface in a separate thread while keeping
-/// the terminal interface running.
+/// the terminal interface running. With `--tui` the terminal interface runs
+/// full screen instead of line by line.
 fn main() {
     #[cfg(not(target_arch = "wasm32"))]
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
+    #[cfg(not(target_arch = "wasm32"))]
+    let tui = args.iter().any(|arg| arg == "--tui");
+    #[cfg(not(target_arch = "wasm32"))]
+    args.retain(|arg| arg != "--tui");
+    #[cfg(not(target_arch = "wasm32"))]
+    let (args, script) = match split_script_args(&args) {
+        Ok(split) => split,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
 
     // Default rows and columns if not specified
     #[cfg(not(target_arch = "wasm32"))]
@@ -52,9 +74,48 @@ fn main() {
         return;
     }
     #[cfg(not(target_arch = "wasm32"))]
+    if let Some(options) = script {
+        let mut backend = if path.is_empty() {
+            Backend::init_backend(rows, columns)
+        } else {
+            match Backend::load(&path) {
+                Ok(backend) => backend,
+                Err(e) => {
+                    eprintln!("Failed to open '{}': {}", path, e);
+                    std::process::exit(1);
+                }
+            }
+        };
+        let result = std::fs::read_to_string(&options.path)
+            .map_err(|e| format!("Failed to read script '{}': {}", options.path, e))
+            .and_then(|text| run_script(&mut backend, &text, &options));
+        match result {
+            Ok(output) => print!("{}", output),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    #[cfg(not(target_arch = "wasm32"))]
     let start_time = std::time::Instant::now();
     #[cfg(not(target_arch = "wasm32"))]
-    let mut frontend = Frontend::init_frontend(rows, columns, &path);
+    let mut frontend = match Frontend::init_frontend(rows, columns, &path) {
+        Ok(frontend) => frontend,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    #[cfg(not(target_arch = "wasm32"))]
+    if tui && io::stdin().is_terminal() && io::stdout().is_terminal() {
+        if let Err(e) = Tui::run(frontend) {
+            eprintln!("Terminal error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
     #[cfg(not(target_arch = "wasm32"))]
     let elapsed_time = start_time.elapsed();
     #[cfg(not(target_arch = "wasm32"))]
@@ -1,2 +1,4 @@
 pub mod extension;
+/// Command line options and the command loop of `--script` mode, shared by both binaries.
+pub mod script;
 pub mod terminal;
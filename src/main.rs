@@ -1,8 +1,11 @@
 // use project::terminal::backend::*;
 // use project::terminal::spreadsheet::*;
 use std::env;
+pub mod script;
 pub mod terminal;
+use crate::script::split_script_args;
 use crate::terminal::backend::*;
+use crate::terminal::script::run_script;
 use crate::terminal::spreadsheet::*;
 use std::io::{self, BufRead, Write};
 use std::time::Instant;
@@ -10,13 +13,34 @@ fn main() {
     let mut start_x = 1;
     let mut start_y = 1;
     let mut is_disabled = false;
-    let args: Vec<String> = env::args().collect();
+    let mut view = View::default();
+    let (args, script) = match split_script_args(&env::args().collect::<Vec<String>>()) {
+        Ok(split) => split,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
     // part of first processing
     if !process_first(args.len(), &args, &mut is_disabled) {
         return;
     }
     let r = args[1].parse::<usize>().unwrap();
     let c = args[2].parse::<usize>().unwrap();
+    if let Some(options) = script {
+        let mut grid = generate_grid(r, c);
+        let result = std::fs::read_to_string(&options.path)
+            .map_err(|e| format!("Failed to read script '{}': {}", options.path, e))
+            .and_then(|text| run_script(&text, r, c, &mut grid, &options));
+        match result {
+            Ok(output) => print!("{}", output),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
     let start = Instant::now();
     // init frontend
     let mut grid = generate_grid(r, c);
@@ -42,7 +66,7 @@ fn main() {
             command.pop();
         }
         if command.is_empty() {
-            print_grid(start_x, start_y, r, c, &mut grid);
+            print_grid_with_view(start_x, start_y, r, c, &mut grid, &view);
             print!("[0.0] (unrecognized cmd) > ");
             io::stdout().flush().unwrap();
             continue;
@@ -50,7 +74,7 @@ fn main() {
 
         let start = Instant::now();
         // , &graph // debug
-        let status = process_command(
+        let status = process_command_with_view(
             &command,
             &mut start_x,
             &mut start_y,
@@ -58,6 +82,7 @@ fn main() {
             c,
             &mut is_disabled,
             &mut grid,
+            &mut view,
         );
         let duration = start.elapsed();
 
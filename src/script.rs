@@ -0,0 +1,101 @@
+/// Format of the values written once the script has run.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    /// One line per row, comma separated, `ERR` for invalid cells and empty for blank ones
+    Csv,
+    /// An object mapping each range label to its rows, `null` for invalid or blank cells
+    Json,
+}
+
+/// Options collected from the `--script` family of command line flags.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptOptions {
+    /// File holding one command per line
+    pub path: String,
+    /// Keep running after a command fails instead of stopping
+    pub keep_going: bool,
+    /// Format of the values written to stdout
+    pub format: OutputFormat,
+    /// Ranges to write, e.g. `A1:C3`; the whole sheet when empty
+    pub ranges: Vec<String>,
+}
+
+/// Splits the command line into positional arguments and script options.
+///
+/// # Arguments
+///
+/// * `args` - All command line arguments, including the program name
+///
+/// # Returns
+///
+/// The remaining positional arguments and, if `--script FILE` was given, the
+/// script options. Also understands `--keep-going`, `--format csv|json` and
+/// repeated `--range A1:B2`. Fails if a flag is missing its value.
+pub fn split_script_args(args: &[String]) -> Result<(Vec<String>, Option<ScriptOptions>), String> {
+    let mut positional = Vec::new();
+    let mut path = None;
+    let mut keep_going = false;
+    let mut format = OutputFormat::Csv;
+    let mut ranges = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--script" => path = Some(iter.next().ok_or("--script needs a file")?.clone()),
+            "--keep-going" => keep_going = true,
+            "--format" => {
+                format = match iter.next().map(|f| f.as_str()) {
+                    Some("csv") => OutputFormat::Csv,
+                    Some("json") => OutputFormat::Json,
+                    _ => return Err("--format must be csv or json".to_string()),
+                }
+            }
+            "--range" => ranges.push(iter.next().ok_or("--range needs a range")?.clone()),
+            _ => positional.push(arg.clone()),
+        }
+    }
+    let options = path.map(|path| ScriptOptions {
+        path,
+        keep_going,
+        format,
+        ranges,
+    });
+    Ok((positional, options))
+}
+
+/// Feeds every command of `script` to `run`, skipping blank lines and `#` comments.
+///
+/// # Arguments
+///
+/// * `script` - The contents of the command file
+/// * `options` - Whether to keep going after a failed command
+/// * `run` - Applies one command; `Ok(false)` stops the script (`q`) and
+///   `Err(reason)` reports a failure
+///
+/// # Returns
+///
+/// The first failure as `line N: COMMAND: REASON` when `keep_going` is off.
+/// With `keep_going` on, failures are reported on stderr and skipped.
+pub fn run_commands(
+    script: &str,
+    options: &ScriptOptions,
+    mut run: impl FnMut(&str) -> Result<bool, String>,
+) -> Result<(), String> {
+    for (number, line) in script.lines().enumerate() {
+        let command = line.trim();
+        if command.is_empty() || command.starts_with('#') {
+            continue;
+        }
+        match run(command) {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(reason) => {
+                let message = format!("line {}: {}: {}", number + 1, command, reason);
+                if !options.keep_going {
+                    return Err(message);
+                }
+                eprintln!("{}", message);
+            }
+        }
+    }
+    Ok(())
+}
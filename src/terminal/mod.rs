@@ -13,5 +13,6 @@ pub mod backend;
 pub mod functions;
 pub mod graph;
 pub mod parser;
+pub mod script;
 pub mod spreadsheet;
 pub mod types;
@@ -0,0 +1,170 @@
+/// Script (batch) mode for the terminal spreadsheet.
+///
+/// Runs a file of commands without printing the grid after each one, then
+/// writes the final values of the sheet, or of selected ranges, to stdout as
+/// CSV or JSON so the engine can be used as a calculator in pipelines.
+use crate::script::{OutputFormat, ScriptOptions, run_commands};
+use crate::terminal::functions::Value;
+use crate::terminal::graph::Node;
+use crate::terminal::parser::{is_cell, validate};
+use crate::terminal::spreadsheet::{column_decoder, process_command};
+use crate::terminal::types::Coordinates;
+use serde_json::{Map, json};
+use std::collections::HashSet;
+
+/// Parses a range such as `A1:C3` into its corner coordinates.
+///
+/// # Returns
+///
+/// * `Some((start, end))` - If both corners are inside the sheet and ordered
+/// * `None` - Otherwise
+pub fn parse_range(range: &str, r: usize, c: usize) -> Option<(Coordinates, Coordinates)> {
+    let (start, end) = range.trim().split_once(':')?;
+    let corner = |cell: &str| {
+        let cell = cell.trim();
+        // is_cell expects a row number after the column letters
+        if !cell.ends_with(|ch: char| ch.is_ascii_digit()) {
+            return None;
+        }
+        match is_cell(cell, &r, &c)? {
+            Value::Cell(col, row) if row > 0 => Some(Coordinates { row, col }),
+            _ => None,
+        }
+    };
+    let (start, end) = (corner(start)?, corner(end)?);
+    if start.row > end.row || start.col > end.col {
+        return None;
+    }
+    Some((start, end))
+}
+
+/// Describes why a command failed, or `None` if its status code means success.
+fn failure(status: i32) -> Option<&'static str> {
+    match status {
+        1 => None,
+        2 => Some("invalid range"),
+        4 => Some("invalid row/column"),
+        5 => Some("cycle not allowed"),
+        _ => Some("unrecognized command"),
+    }
+}
+
+/// Runs `script` against `grid` and renders the requested ranges.
+///
+/// # Arguments
+///
+/// * `script` - The contents of the command file
+/// * `r` - The number of rows in the grid
+/// * `c` - The number of columns in the grid
+/// * `grid` - The spreadsheet grid the commands are applied to
+/// * `options` - Error handling, output format and ranges
+///
+/// # Returns
+///
+/// The rendered output, or the first error when `keep_going` is off.
+/// With `keep_going` on, failures are reported on stderr and skipped.
+/// Cells no command assigned are blank and written as empty fields or `null`.
+pub fn run_script(
+    script: &str,
+    r: usize,
+    c: usize,
+    grid: &mut Vec<Vec<Node>>,
+    options: &ScriptOptions,
+) -> Result<String, String> {
+    let mut start_x = 1;
+    let mut start_y = 1;
+    let mut assigned = HashSet::new();
+    run_commands(script, options, |command| {
+        // display commands have nothing to act on without a grid on screen
+        if matches!(
+            command,
+            "enable_output" | "disable_output" | "w" | "a" | "s" | "d"
+        ) || command.starts_with("scroll_to ")
+        {
+            return Ok(true);
+        }
+        let mut is_disabled = true;
+        let status = process_command(
+            command,
+            &mut start_x,
+            &mut start_y,
+            r,
+            c,
+            &mut is_disabled,
+            grid,
+        );
+        if status == 1
+            && let Some((Some(Value::Cell(col, row)), _)) = validate(command, &r, &c)
+        {
+            assigned.insert((row, col));
+        }
+        if status == 0 {
+            return Ok(false);
+        }
+        failure(status).map_or(Ok(true), |reason| Err(reason.to_string()))
+    })?;
+
+    let ranges = if options.ranges.is_empty() {
+        vec![format!("A1:{}{}", column_decoder(c), r)]
+    } else {
+        options.ranges.clone()
+    };
+    let mut parsed = Vec::new();
+    for range in &ranges {
+        parsed.push(parse_range(range, r, c).ok_or(format!("invalid range: {}", range))?);
+    }
+
+    let value = |row: i32, col: i32| {
+        let node = &grid[row as usize][col as usize];
+        if !assigned.contains(&(row, col)) {
+            Some(None)
+        } else if node.valid {
+            Some(Some(node.node_value))
+        } else {
+            None
+        }
+    };
+    match options.format {
+        OutputFormat::Csv => {
+            let mut out = String::new();
+            for (start, end) in &parsed {
+                if !out.is_empty() {
+                    out.push('\n');
+                }
+                for row in start.row..=end.row {
+                    let line: Vec<String> = (start.col..=end.col)
+                        .map(|col| match value(row, col) {
+                            Some(Some(v)) => v.to_string(),
+                            Some(None) => String::new(),
+                            None => "ERR".to_string(),
+                        })
+                        .collect();
+                    out.push_str(&line.join(","));
+                    out.push('\n');
+                }
+            }
+            Ok(out)
+        }
+        OutputFormat::Json => {
+            let mut object = Map::new();
+            for (start, end) in &parsed {
+                let rows: Vec<Vec<Option<i32>>> = (start.row..=end.row)
+                    .map(|row| {
+                        (start.col..=end.col)
+                            .map(|col| value(row, col).flatten())
+                            .collect()
+                    })
+                    .collect();
+                let label = format!(
+                    "{}{}:{}{}",
+                    column_decoder(start.col as usize),
+                    start.row,
+                    column_decoder(end.col as usize),
+                    end.row
+                );
+                object.insert(label, json!(rows));
+            }
+            Ok(serde_json::to_string(&object).unwrap_or_default() + "\n")
+        }
+    }
+}
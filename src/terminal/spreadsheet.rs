@@ -11,6 +11,7 @@ use crate::terminal::graph::Node;
 use crate::terminal::parser;
 use crate::terminal::types::Coordinates;
 use std::cmp;
+use std::collections::HashMap;
 use std::io;
 use std::io::Write;
 
@@ -38,6 +39,82 @@ pub fn column_decoder(mut j: usize) -> String {
     cc.into_iter().collect()
 }
 
+/// Size of the printed grid and the width of its columns.
+///
+/// The default is the original 10x10 window of 12-character cells; `set view 25x8`,
+/// `set view auto` and `set width B 20` change it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct View {
+    /// Rows shown, also the step of `w` and `s`
+    pub rows: usize,
+    /// Columns shown, also the step of `a` and `d`
+    pub columns: usize,
+    /// Fit the rows and columns to the terminal instead
+    pub auto: bool,
+    /// Width of the row labels and of the columns missing from `widths`
+    pub default_width: usize,
+    pub widths: HashMap<usize, usize>,
+}
+
+impl Default for View {
+    fn default() -> Self {
+        View {
+            rows: 10,
+            columns: 10,
+            auto: false,
+            default_width: 12,
+            widths: HashMap::new(),
+        }
+    }
+}
+
+impl View {
+    /// Width of column `j`.
+    pub fn width(&self, j: usize) -> usize {
+        self.widths.get(&j).copied().unwrap_or(self.default_width)
+    }
+
+    /// Rows and columns shown when the view starts at column `start_y`.
+    pub fn visible(&self, start_y: usize) -> (usize, usize) {
+        let Some((width, height)) = terminal_size().filter(|_| self.auto) else {
+            return (self.rows, self.columns);
+        };
+        let mut used = self.default_width + self.width(start_y);
+        let mut columns = 1;
+        while used + self.width(start_y + columns) <= width {
+            used += self.width(start_y + columns);
+            columns += 1;
+        }
+        // leave a line for the column labels and one for the prompt
+        (height.saturating_sub(2).max(1), columns)
+    }
+}
+
+/// Size of the terminal in characters, if there is one.
+fn terminal_size() -> Option<(usize, usize)> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        crossterm::terminal::size()
+            .ok()
+            .map(|(width, height)| (width as usize, height as usize))
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        None
+    }
+}
+
+/// Right-aligns `text` in `width` characters, cutting it short with a `~` marker
+/// when it does not fit.
+pub fn fit_text(text: &str, width: usize) -> String {
+    if text.chars().count() > width {
+        let kept: String = text.chars().take(width.saturating_sub(1)).collect();
+        format!("{}~", kept)
+    } else {
+        format!("{:>width$}", text)
+    }
+}
+
 /// Prints the current state of the spreadsheet grid to the terminal.
 ///
 /// # Arguments
@@ -48,21 +125,38 @@ pub fn column_decoder(mut j: usize) -> String {
 /// * `c` - The number of columns in the grid
 /// * `grid` - The spreadsheet grid containing the cells
 pub fn print_grid(start_x: usize, start_y: usize, r: usize, c: usize, grid: &mut [Vec<Node>]) {
-    let max_x = cmp::min(9 + start_x, r);
-    let max_y = cmp::min(9 + start_y, c);
+    print_grid_with_view(start_x, start_y, r, c, grid, &View::default());
+}
+
+/// Prints the grid like `print_grid`, with the size and column widths of `view`.
+pub fn print_grid_with_view(
+    start_x: usize,
+    start_y: usize,
+    r: usize,
+    c: usize,
+    grid: &mut [Vec<Node>],
+    view: &View,
+) {
+    let (rows, columns) = view.visible(start_y);
+    let max_x = cmp::min(rows - 1 + start_x, r);
+    let max_y = cmp::min(columns - 1 + start_y, c);
+    let label_width = view.default_width;
 
     for (i, _row) in grid.iter().enumerate().take(max_x + 1).skip(start_x - 1) {
         for j in start_y - 1..=max_y {
             if i == start_x - 1 && j == start_y - 1 {
-                print!("{:>12}", " ");
+                print!("{}", fit_text(" ", label_width));
             } else if i == start_x - 1 && j != start_y - 1 {
-                print!("{:>12}", column_decoder(j));
+                print!("{}", fit_text(&column_decoder(j), view.width(j)));
             } else if j == start_y - 1 {
-                print!("{:>12}", i);
+                print!("{}", fit_text(&i.to_string(), label_width));
             } else if grid[i][j].valid {
-                print!("{:>12}", grid[i][j].node_value);
+                print!(
+                    "{}",
+                    fit_text(&grid[i][j].node_value.to_string(), view.width(j))
+                );
             } else {
-                print!("{:>12}", "ERR");
+                print!("{}", fit_text("ERR", view.width(j)));
             }
         }
         println!();
@@ -92,6 +186,45 @@ fn is_number(str: &str) -> bool {
     !str.is_empty() && str.chars().all(|c| c.is_ascii_digit())
 }
 
+/// Applies `set view <rows>x<columns>`, `set view auto` or `set width <col> <width>`
+/// to `view`. Returns false, leaving the view alone, if the command is invalid.
+fn apply_set(args: &str, c: usize, view: &mut View) -> bool {
+    let tokens: Vec<&str> = args.split_whitespace().collect();
+    match tokens.as_slice() {
+        ["view", "auto"] => view.auto = true,
+        ["view", size] => {
+            let Some((rows, columns)) = size.split_once('x') else {
+                return false;
+            };
+            match (rows.parse::<usize>(), columns.parse::<usize>()) {
+                (Ok(rows), Ok(columns)) if rows > 0 && columns > 0 => {
+                    view.rows = rows;
+                    view.columns = columns;
+                    view.auto = false;
+                }
+                _ => return false,
+            }
+        }
+        ["width", col, width] => {
+            if !col.chars().all(|ch| ch.is_ascii_uppercase()) {
+                return false;
+            }
+            // the column is checked through the parser's cell rules, using row 1
+            let Some(Value::Cell(j, _)) = parser::is_cell(&format!("{}1", col), &1, &c) else {
+                return false;
+            };
+            match width.parse::<usize>() {
+                Ok(width) if width > 0 => {
+                    view.widths.insert(j as usize, width);
+                }
+                _ => return false,
+            }
+        }
+        _ => return false,
+    }
+    true
+}
+
 /// Processes a single command and updates the spreadsheet state accordingly.
 ///
 /// # Arguments
@@ -107,7 +240,6 @@ fn is_number(str: &str) -> bool {
 /// # Returns
 ///
 /// An integer status code indicating success or specific failure modes
-#[allow(unreachable_code)]
 pub fn process_command(
     command: &str,
     start_x: &mut usize,
@@ -117,44 +249,82 @@ pub fn process_command(
     is_disabled: &mut bool,
     grid: &mut Vec<Vec<Node>>,
 ) -> i32 {
+    process_command_with_view(
+        command,
+        start_x,
+        start_y,
+        r,
+        c,
+        is_disabled,
+        grid,
+        &mut View::default(),
+    )
+}
+
+/// Processes a command like `process_command`, printing the grid with `view`
+/// and scrolling by its size. `set` commands change the view.
+#[allow(unreachable_code)]
+#[allow(clippy::too_many_arguments)]
+pub fn process_command_with_view(
+    command: &str,
+    start_x: &mut usize,
+    start_y: &mut usize,
+    r: usize,
+    c: usize,
+    is_disabled: &mut bool,
+    grid: &mut Vec<Vec<Node>>,
+    view: &mut View,
+) -> i32 {
+    let (rows, columns) = view.visible(*start_y);
     match command {
         "q" => return 0,
         "w" => {
-            *start_x = if *start_x > 10 { *start_x - 10 } else { 1 };
+            *start_x = if *start_x > rows { *start_x - rows } else { 1 };
             if !(*is_disabled) {
-                print_grid(*start_x, *start_y, r, c, grid);
+                print_grid_with_view(*start_x, *start_y, r, c, grid, view);
             }
             return 1;
         }
         "s" => {
-            if *start_x + 10 <= r {
-                *start_x += 10;
-                *start_x = cmp::min(*start_x, r - 9);
+            if *start_x + rows <= r {
+                *start_x += rows;
+                *start_x = cmp::min(*start_x, r + 1 - rows);
             }
             if !(*is_disabled) {
-                print_grid(*start_x, *start_y, r, c, grid);
+                print_grid_with_view(*start_x, *start_y, r, c, grid, view);
             }
             return 1;
         }
         "a" => {
-            *start_y = if *start_y > 10 { *start_y - 10 } else { 1 };
+            *start_y = if *start_y > columns {
+                *start_y - columns
+            } else {
+                1
+            };
             if !(*is_disabled) {
-                print_grid(*start_x, *start_y, r, c, grid);
+                print_grid_with_view(*start_x, *start_y, r, c, grid, view);
             }
             return 1;
         }
         "d" => {
-            if *start_y + 10 <= c {
-                *start_y += 10;
-                *start_y = cmp::min(*start_y, c - 9);
+            if *start_y + columns <= c {
+                *start_y += columns;
+                *start_y = cmp::min(*start_y, c + 1 - columns);
             }
             if !(*is_disabled) {
-                print_grid(*start_x, *start_y, r, c, grid);
+                print_grid_with_view(*start_x, *start_y, r, c, grid, view);
             }
             return 1;
         }
         _ => {}
     }
+    if let Some(args) = command.strip_prefix("set ") {
+        let status = if apply_set(args, c, view) { 1 } else { 3 };
+        if !(*is_disabled) {
+            print_grid_with_view(*start_x, *start_y, r, c, grid, view);
+        }
+        return status;
+    }
 
     // let mut function: isize = -1;
     // work with enums only not functions
@@ -168,7 +338,7 @@ pub fn process_command(
                     *start_x = row as usize;
                     *start_y = col as usize;
                     if !(*is_disabled) {
-                        print_grid(*start_x, *start_y, r, c, grid);
+                        print_grid_with_view(*start_x, *start_y, r, c, grid, view);
                     }
                     return 1;
                 }
@@ -206,7 +376,7 @@ pub fn process_command(
                 ),
                 _ => {
                     if !(*is_disabled) {
-                        print_grid(*start_x, *start_y, r, c, grid);
+                        print_grid_with_view(*start_x, *start_y, r, c, grid, view);
                     }
                     return 3; // Invalid operands
                 }
@@ -216,7 +386,7 @@ pub fn process_command(
             let status = getting_things_updated(grid, target_cell, value1, value2, operation);
 
             if !(*is_disabled) {
-                print_grid(*start_x, *start_y, r, c, grid);
+                print_grid_with_view(*start_x, *start_y, r, c, grid, view);
             }
 
             return status;
@@ -226,7 +396,7 @@ pub fn process_command(
             match op {
                 Operation::EnableOutput => {
                     *is_disabled = false;
-                    print_grid(*start_x, *start_y, r, c, grid);
+                    print_grid_with_view(*start_x, *start_y, r, c, grid, view);
                     return 1;
                 }
                 Operation::DisableOutput => {
@@ -235,7 +405,7 @@ pub fn process_command(
                 }
                 _ => {
                     if !(*is_disabled) {
-                        print_grid(*start_x, *start_y, r, c, grid);
+                        print_grid_with_view(*start_x, *start_y, r, c, grid, view);
                     }
                     return 3; // Invalid operands
                 }
@@ -243,7 +413,7 @@ pub fn process_command(
         }
         _ => {
             if !(*is_disabled) {
-                print_grid(*start_x, *start_y, r, c, grid);
+                print_grid_with_view(*start_x, *start_y, r, c, grid, view);
             }
             return 3; // Invalid operands
         }
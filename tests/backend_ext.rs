@@ -277,6 +277,25 @@ fn test_circular_dependency() {
     assert_eq!(status, Status::CircularDependency);
 }
 
+#[test]
+fn test_diamond_is_not_circular() {
+    let mut backend = Backend::init_backend(10, 10);
+    // B1 and C1 both read A1 and D1 reads both, so D1 is reached twice from A1
+    for cmd in ["A1=1", "B1=A1+1", "C1=A1*2", "D1=B1+C1"] {
+        assert_eq!(
+            backend.process_command(10, 10, cmd.to_string()),
+            Status::Success
+        );
+    }
+    let status = backend.process_command(10, 10, "A1=E1+5".to_string());
+
+    assert_eq!(status, Status::Success);
+    assert_eq!(backend.get_node_value(Value::Cell(1, 4)), Some(16));
+    // closing the loop is still refused
+    let status = backend.process_command(10, 10, "A1=D1+1".to_string());
+    assert_eq!(status, Status::CircularDependency);
+}
+
 #[test]
 fn test_undo_redo() {
     let mut backend = Backend::init_backend(10, 10);
@@ -346,3 +365,60 @@ fn test_min_max_functions() {
     assert_eq!(backend.get_node_value(Value::Cell(1, 2)), Some(2)); // MIN
     assert_eq!(backend.get_node_value(Value::Cell(2, 2)), Some(15)); // MAX
 }
+
+#[test]
+fn test_open_json() {
+    let mut saved = Backend::init_backend(5, 5);
+    saved.process_command(5, 5, "A1=7".to_string());
+    saved.process_command(5, 5, "B1=A1*3".to_string());
+    let temp_file = "test_open_json.json";
+    assert!(saved.serial(temp_file).is_ok());
+
+    let mut backend = Backend::init_backend(10, 10);
+    let status = backend.process_command(10, 10, format!("open {}", temp_file));
+    assert_eq!(status, Status::Opened);
+    assert_eq!(backend.get_grid().get_row_size(), 6);
+    assert_eq!(backend.get_node_value(Value::Cell(1, 2)), Some(21));
+    assert!(!backend.has_unsaved_changes());
+
+    let _ = fs::remove_file(temp_file);
+}
+
+#[test]
+fn test_open_csv() {
+    let temp_file = "test_open_csv.csv";
+    fs::write(temp_file, "1,2,3\n4,,=SUM(A1:C1)\n").unwrap();
+
+    let mut backend = Backend::init_backend(10, 10);
+    let status = backend.process_command(10, 10, format!("load {}", temp_file));
+    assert_eq!(status, Status::Opened);
+    assert_eq!(backend.get_grid().get_row_size(), 3);
+    assert_eq!(backend.get_grid().get_column_size(), 4);
+    assert_eq!(backend.get_node_value(Value::Cell(2, 1)), Some(4));
+    assert_eq!(backend.get_node_value(Value::Cell(2, 3)), Some(6));
+
+    let _ = fs::remove_file(temp_file);
+}
+
+#[test]
+fn test_open_reports_errors() {
+    let mut backend = Backend::init_backend(10, 10);
+    backend.process_command(10, 10, "A1=5".to_string());
+
+    // Unsaved changes must be confirmed first
+    let status = backend.process_command(10, 10, "open missing.json".to_string());
+    assert_eq!(status, Status::UnsavedChanges("missing.json".to_string()));
+
+    // A failed open keeps the current sheet and explains why
+    let status = backend.open("missing.json");
+    assert!(matches!(status, Status::Error(_)));
+    assert_eq!(backend.get_node_value(Value::Cell(1, 1)), Some(5));
+
+    let status = backend.open("sheet.xlsx");
+    assert_eq!(
+        status,
+        Status::Error("Unsupported file format: sheet.xlsx".to_string())
+    );
+
+    assert!(Backend::from_csv_text("1,abc\n").is_err());
+}
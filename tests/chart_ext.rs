@@ -0,0 +1,82 @@
+use project::extension::backend::backend::*;
+use project::extension::backend::chart::*;
+use project::extension::common::{ChartKind, ChartSpec, Operation, Value};
+use project::extension::parser::parser;
+use std::fs;
+
+fn sample_backend() -> Backend {
+    let mut backend = Backend::init_backend(5, 5);
+    for cmd in ["A1=1", "A2=4", "A3=9", "B1=10", "B2=20", "B3=1/0"] {
+        backend.process_command(5, 5, cmd.to_string());
+    }
+    backend
+}
+
+#[test]
+fn test_validate_chart_command() {
+    let result = parser::validate(
+        "chart scatter A1:B3 out.svg title=\"Growth rate\" ylabel=Units",
+        &5,
+        &5,
+    );
+    let expected = ChartSpec {
+        kind: ChartKind::Scatter,
+        path: "out.svg".to_string(),
+        title: "Growth rate".to_string(),
+        x_label: String::new(),
+        y_label: "Units".to_string(),
+    };
+    assert_eq!(
+        result,
+        Some((
+            None,
+            Some(Value::Oper(
+                Some(Box::new(Value::Cell(1, 1))),
+                Some(Box::new(Value::Cell(3, 2))),
+                Operation::Chart(expected),
+            ))
+        ))
+    );
+
+    assert!(parser::validate("chart pie A1:A3 out.svg", &5, &5).is_none());
+    assert!(parser::validate("chart line A1:A3", &5, &5).is_none());
+    assert!(parser::validate("chart line A1:A3 out.svg colour=red", &5, &5).is_none());
+    assert!(parser::validate("chart line A1:A3 out.svg title=\"open", &5, &5).is_none());
+}
+
+#[test]
+fn test_chart_points() {
+    let backend = sample_backend();
+    let grid = backend.get_grid();
+
+    let column = chart_points(grid, &Value::Cell(1, 1), &Value::Cell(3, 1)).unwrap();
+    assert_eq!(column, vec![(1.0, 1.0), (2.0, 4.0), (3.0, 9.0)]);
+
+    let row = chart_points(grid, &Value::Cell(2, 1), &Value::Cell(2, 2)).unwrap();
+    assert_eq!(row, vec![(1.0, 4.0), (2.0, 20.0)]);
+
+    // The B3 error is skipped
+    let pairs = chart_points(grid, &Value::Cell(1, 1), &Value::Cell(3, 2)).unwrap();
+    assert_eq!(pairs, vec![(1.0, 10.0), (4.0, 20.0)]);
+
+    assert!(chart_points(grid, &Value::Cell(1, 1), &Value::Cell(3, 3)).is_err());
+    assert!(chart_points(grid, &Value::Cell(3, 2), &Value::Cell(3, 2)).is_err());
+}
+
+#[test]
+fn test_chart_command_writes_file() {
+    let mut backend = sample_backend();
+    let path = "test_chart_command.svg";
+    let status = backend.process_command(5, 5, format!("chart line A1:A3 {} title=Squares", path));
+    assert_eq!(status, Status::Success);
+    let svg = fs::read_to_string(path).unwrap();
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.contains("Squares"));
+    let _ = fs::remove_file(path);
+
+    let status = backend.process_command(5, 5, "chart bar A1:A3 chart.gif".to_string());
+    assert_eq!(
+        status,
+        Status::Error("Unsupported chart format: chart.gif".to_string())
+    );
+}
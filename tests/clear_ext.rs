@@ -0,0 +1,117 @@
+use project::extension::backend::backend::*;
+use project::extension::common::{Operation, Value};
+use project::extension::parser::parser;
+
+fn run(backend: &mut Backend, cmd: &str) -> Status {
+    let rows = backend.get_grid().get_row_size() - 1;
+    let columns = backend.get_grid().get_column_size() - 1;
+    backend.process_command(rows, columns, cmd.to_string())
+}
+
+fn formula(backend: &Backend, row: usize, col: usize) -> Option<String> {
+    backend.get_grid().cells_vec[row][col].formula()
+}
+
+fn value(backend: &Backend, row: usize, col: usize) -> Option<isize> {
+    backend.get_node_value(Value::Cell(row, col))
+}
+
+fn blank(backend: &Backend, row: usize, col: usize) -> bool {
+    backend.get_grid().cells_vec[row][col].is_blank()
+}
+
+#[test]
+fn test_validate_clear() {
+    assert_eq!(
+        parser::validate("clear A1:C3", &10, &10),
+        Some((
+            None,
+            Some(Value::Oper(
+                Some(Box::new(Value::Cell(1, 1))),
+                Some(Box::new(Value::Cell(3, 3))),
+                Operation::Clear,
+            ))
+        ))
+    );
+    assert_eq!(
+        parser::validate("clear B2", &10, &10),
+        Some((
+            None,
+            Some(Value::Oper(
+                Some(Box::new(Value::Cell(2, 2))),
+                Some(Box::new(Value::Cell(2, 2))),
+                Operation::Clear,
+            ))
+        ))
+    );
+    assert!(parser::validate("clear C3:A1", &10, &10).is_none());
+    assert!(parser::validate("clear", &10, &10).is_none());
+}
+
+#[test]
+fn test_blank_is_not_zero() {
+    let mut backend = Backend::init_backend(3, 3);
+    assert!(blank(&backend, 1, 1));
+    run(&mut backend, "A1=0");
+    assert!(!blank(&backend, 1, 1));
+    assert_eq!(value(&backend, 1, 1), Some(0));
+
+    let valgrid = backend.get_valgrid();
+    assert!(!valgrid.blanks[1][1]);
+    assert!(valgrid.blanks[1][2]);
+    assert_eq!(valgrid.cells[1][2], Some(0));
+}
+
+#[test]
+fn test_clear_recalculates_dependents() {
+    let mut backend = Backend::init_backend(5, 5);
+    run(&mut backend, "A1=5");
+    run(&mut backend, "B1=A1+1");
+    run(&mut backend, "C1=SUM(A1:B1)");
+    assert_eq!(value(&backend, 1, 3), Some(11));
+
+    assert_eq!(run(&mut backend, "clear A1"), Status::Success);
+    assert!(blank(&backend, 1, 1));
+    assert_eq!(formula(&backend, 1, 1), None);
+    assert_eq!(value(&backend, 1, 2), Some(1));
+    assert_eq!(value(&backend, 1, 3), Some(1));
+    // readers of a cleared cell keep reading it
+    run(&mut backend, "A1=7");
+    assert_eq!(value(&backend, 1, 3), Some(15));
+}
+
+#[test]
+fn test_clear_breaks_edges() {
+    let mut backend = Backend::init_backend(5, 5);
+    run(&mut backend, "A1=5");
+    run(&mut backend, "B1=A1+1");
+    run(&mut backend, "B2=SUM(A:A)");
+    run(&mut backend, "B3=B1*2");
+    assert_eq!(run(&mut backend, "clear B1:B2"), Status::Success);
+    assert!(blank(&backend, 1, 2));
+    assert!(blank(&backend, 2, 2));
+    assert_eq!(value(&backend, 3, 2), Some(0));
+    assert!(backend.get_grid().cells_vec[1][1].dependents.is_empty());
+    assert!(backend.get_grid().line_readers.is_empty());
+
+    run(&mut backend, "A1=50");
+    assert_eq!(value(&backend, 1, 2), Some(0));
+    // the old formula no longer stands in the way of a new one
+    assert_eq!(run(&mut backend, "A2=B1+1"), Status::Success);
+}
+
+#[test]
+fn test_clear_undo_and_errors() {
+    let mut backend = Backend::init_backend(5, 5);
+    run(&mut backend, "A1=0");
+    run(&mut backend, "A2=3/A1");
+    assert_eq!(value(&backend, 2, 1), None);
+    assert_eq!(run(&mut backend, "clear A1:A2"), Status::Success);
+    assert_eq!(value(&backend, 2, 1), Some(0));
+    assert!(blank(&backend, 2, 1));
+
+    assert_eq!(run(&mut backend, "undo"), Status::Success);
+    assert_eq!(formula(&backend, 2, 1), Some("3/A1".to_string()));
+    assert_eq!(value(&backend, 2, 1), None);
+    assert!(!blank(&backend, 1, 1));
+}
@@ -0,0 +1,73 @@
+use project::extension::backend::backend::*;
+use project::extension::common::{Value, ViewChange};
+use project::extension::frontend::terminal::*;
+
+fn run(backend: &mut Backend, cmd: &str) -> Status {
+    let rows = backend.get_grid().get_row_size() - 1;
+    let columns = backend.get_grid().get_column_size() - 1;
+    backend.process_command(rows, columns, cmd.to_string())
+}
+
+fn cells(cells: &[(usize, usize)]) -> Vec<Value> {
+    cells
+        .iter()
+        .map(|&(row, col)| Value::Cell(row, col))
+        .collect()
+}
+
+#[test]
+fn test_recomputed_cells() {
+    let mut backend = Backend::init_backend(5, 5);
+    for cmd in ["A1=1", "B1=A1+1", "C1=B1*2", "D1=5"] {
+        run(&mut backend, cmd);
+    }
+    assert_eq!(backend.recomputed(), cells(&[(1, 4)]));
+    run(&mut backend, "A1=3");
+    assert_eq!(backend.recomputed(), cells(&[(1, 1), (1, 2), (1, 3)]));
+    // commands that change no value clear the list
+    run(&mut backend, "s");
+    assert!(backend.recomputed().is_empty());
+    run(&mut backend, "clear B1");
+    assert_eq!(backend.recomputed(), cells(&[(1, 2), (1, 3)]));
+    assert_eq!(run(&mut backend, "C1=C1+1"), Status::CircularDependency);
+    assert!(backend.recomputed().is_empty());
+}
+
+#[test]
+fn test_recomputed_on_other_sheets_are_left_out() {
+    let mut backend = Backend::init_backend(5, 5);
+    run(&mut backend, "add_sheet Data");
+    run(&mut backend, "switch_sheet Data");
+    run(&mut backend, "A1=Sheet1!A1*2");
+    run(&mut backend, "switch_sheet Sheet1");
+    run(&mut backend, "A1=4");
+    assert_eq!(backend.recomputed(), cells(&[(1, 1)]));
+}
+
+#[test]
+fn test_cell_styles() {
+    let mut frontend = Frontend::init_frontend(5, 5, "").unwrap();
+    frontend.color = true;
+    for cmd in ["A1=0", "A2=-3", "A3=7", "B1=A3/A1"] {
+        frontend.process_command(cmd);
+    }
+    let recomputed = frontend.backend.recomputed();
+    assert_eq!(frontend.cell_styles(1, 2, &recomputed), ["31", "7"]);
+    assert!(frontend.cell_styles(1, 1, &recomputed).is_empty());
+    assert_eq!(frontend.cell_styles(2, 1, &recomputed), ["33"]);
+    // an empty cell is neither an error nor negative
+    assert!(frontend.cell_styles(4, 4, &recomputed).is_empty());
+
+    frontend.execute_status(&Status::View(ViewChange::Formulas(true)));
+    assert_eq!(frontend.cell_styles(1, 2, &recomputed), ["7"]);
+
+    frontend.color = false;
+    assert!(frontend.cell_styles(1, 2, &recomputed).is_empty());
+}
+
+#[test]
+fn test_paint() {
+    assert_eq!(paint("ERR", &["31"]), "\x1b[31mERR\x1b[0m");
+    assert_eq!(paint("  -3", &["33", "7"]), "\x1b[33;7m  -3\x1b[0m");
+    assert_eq!(paint("5", &[]), "5");
+}
@@ -0,0 +1,178 @@
+use project::extension::backend::backend::*;
+use project::extension::common::{Anchor, Anchors, Operation, Value};
+use project::extension::parser::parser;
+
+fn run(backend: &mut Backend, cmd: &str) -> Status {
+    let rows = backend.get_grid().get_row_size() - 1;
+    let columns = backend.get_grid().get_column_size() - 1;
+    backend.process_command(rows, columns, cmd.to_string())
+}
+
+/// Anchors of the operands of an assignment on a 5x5 sheet.
+fn anchors(cmd: &str) -> Anchors {
+    let mut details = parser::Details::default();
+    parser::validate_with(cmd, &5, &5, &mut details).unwrap();
+    details.anchors
+}
+
+fn formula(backend: &Backend, row: usize, col: usize) -> Option<String> {
+    backend.get_grid().cells_vec[row][col].formula()
+}
+
+#[test]
+fn test_validate_absolute_references() {
+    assert_eq!(
+        parser::validate("B1=$A$1+A2", &5, &5),
+        Some((
+            Some(Value::Cell(1, 2)),
+            Some(Value::Oper(
+                Some(Box::new(Value::Cell(1, 1))),
+                Some(Box::new(Value::Cell(2, 1))),
+                Operation::Add,
+            ))
+        ))
+    );
+    let fixed = Anchor {
+        row: true,
+        col: true,
+    };
+    assert_eq!(anchors("B1=$A$1+A2"), [fixed, Anchor::default()]);
+    assert_eq!(
+        anchors("B1=SUM(A$1:$B3)"),
+        [
+            Anchor {
+                row: true,
+                col: false
+            },
+            Anchor {
+                row: false,
+                col: true
+            }
+        ]
+    );
+    assert!(anchors("B1=-5*$C2")[1].col);
+    assert!(anchors("B1=SUM(Data!$A$1:A9)")[0].row);
+    assert_eq!(
+        anchors("B1=MAX($3:4)"),
+        [
+            Anchor {
+                row: true,
+                col: false
+            },
+            Anchor::default()
+        ]
+    );
+    assert!(parser::validate("B1=A$$1", &5, &5).is_none());
+    assert!(parser::validate("B1=A1$", &5, &5).is_none());
+}
+
+#[test]
+fn test_validate_copy_and_fill() {
+    assert_eq!(
+        parser::validate("copy A1:B2 to C5", &5, &5),
+        Some((
+            Some(Value::Cell(5, 3)),
+            Some(Value::Oper(
+                Some(Box::new(Value::Cell(1, 1))),
+                Some(Box::new(Value::Cell(2, 2))),
+                Operation::Copy,
+            ))
+        ))
+    );
+    assert_eq!(
+        parser::validate("fill_down A1:A4", &5, &5),
+        Some((
+            None,
+            Some(Value::Oper(
+                Some(Box::new(Value::Cell(1, 1))),
+                Some(Box::new(Value::Cell(4, 1))),
+                Operation::FillDown,
+            ))
+        ))
+    );
+    assert!(parser::validate("copy A1 to", &5, &5).is_none());
+    assert!(parser::validate("copy A1:B2 C5", &5, &5).is_none());
+    assert!(parser::validate("fill_right A1", &5, &5).is_none());
+}
+
+#[test]
+fn test_fill_down_shifts_relative_references() {
+    let mut backend = Backend::init_backend(5, 3);
+    run(&mut backend, "A1=1");
+    run(&mut backend, "A2=A1+1");
+    run(&mut backend, "B2=A2*$C$1");
+    run(&mut backend, "C1=10");
+    assert_eq!(run(&mut backend, "fill_down A2:B5"), Status::Success);
+
+    assert_eq!(formula(&backend, 5, 1), Some("A4+1".to_string()));
+    assert_eq!(formula(&backend, 4, 2), Some("A4*$C$1".to_string()));
+    assert_eq!(backend.get_node_value(Value::Cell(5, 1)), Some(5));
+    assert_eq!(backend.get_node_value(Value::Cell(5, 2)), Some(50));
+
+    // the copies are live formulas
+    run(&mut backend, "C1=2");
+    assert_eq!(backend.get_node_value(Value::Cell(3, 2)), Some(6));
+
+    // the whole fill is one undo step
+    run(&mut backend, "undo");
+    run(&mut backend, "undo");
+    assert_eq!(formula(&backend, 5, 1), None);
+    assert_eq!(backend.get_node_value(Value::Cell(5, 2)), Some(0));
+}
+
+#[test]
+fn test_fill_right_with_mixed_anchors() {
+    let mut backend = Backend::init_backend(3, 4);
+    run(&mut backend, "A1=4");
+    run(&mut backend, "A2=SUM($A1:A1)");
+    assert_eq!(run(&mut backend, "fill_right A2:D2"), Status::Success);
+    assert_eq!(formula(&backend, 2, 3), Some("SUM($A1:C1)".to_string()));
+    run(&mut backend, "B1=1");
+    run(&mut backend, "C1=2");
+    assert_eq!(backend.get_node_value(Value::Cell(2, 3)), Some(7));
+}
+
+#[test]
+fn test_copy_range() {
+    let mut backend = Backend::init_backend(6, 6);
+    run(&mut backend, "A1=3");
+    run(&mut backend, "B1=A1*2");
+    run(&mut backend, "A2=7");
+    assert_eq!(run(&mut backend, "copy A1:B2 to C5"), Status::Success);
+
+    assert_eq!(backend.get_node_value(Value::Cell(5, 3)), Some(3));
+    assert_eq!(formula(&backend, 5, 4), Some("C5*2".to_string()));
+    assert_eq!(backend.get_node_value(Value::Cell(5, 4)), Some(6));
+    assert_eq!(backend.get_node_value(Value::Cell(6, 3)), Some(7));
+
+    // references that would leave the sheet become #REF!
+    run(&mut backend, "F1=A1+1");
+    assert_eq!(run(&mut backend, "copy F1 to A1"), Status::Success);
+    assert_eq!(formula(&backend, 1, 1), Some("#REF!+1".to_string()));
+    assert_eq!(backend.get_node_value(Value::Cell(1, 1)), None);
+
+    // blank cells overwrite what was there
+    run(&mut backend, "E3=9");
+    run(&mut backend, "copy D3 to E3");
+    assert_eq!(formula(&backend, 3, 5), None);
+    assert_eq!(backend.get_node_value(Value::Cell(3, 5)), Some(0));
+}
+
+#[test]
+fn test_copy_errors_leave_sheet_unchanged() {
+    let mut backend = Backend::init_backend(5, 5);
+    run(&mut backend, "A1=1");
+    run(&mut backend, "B1=A1+1");
+    assert!(matches!(
+        run(&mut backend, "copy A1:B1 to E1"),
+        Status::Error(_)
+    ));
+    // D1 reads the cell to its right, so pasted into A1 it reads B1, which reads A1
+    run(&mut backend, "D1=E1+0");
+    assert_eq!(
+        run(&mut backend, "copy D1 to A1"),
+        Status::CircularDependency
+    );
+    assert_eq!(formula(&backend, 1, 1), Some("1".to_string()));
+    assert_eq!(backend.get_node_value(Value::Cell(1, 2)), Some(2));
+}
@@ -0,0 +1,159 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use project::extension::frontend::editor::*;
+
+fn press(editor: &mut Editor, code: KeyCode) -> Edit {
+    editor.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+}
+
+fn ctrl(editor: &mut Editor, c: char) -> Edit {
+    editor.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL))
+}
+
+fn type_text(editor: &mut Editor, text: &str) {
+    for c in text.chars() {
+        press(editor, KeyCode::Char(c));
+    }
+}
+
+fn line(editor: &Editor) -> String {
+    editor.line.iter().collect()
+}
+
+fn editor_with(history: &[&str]) -> Editor {
+    let mut editor = Editor::new(None);
+    for line in history {
+        editor.add_history(line);
+    }
+    editor
+}
+
+#[test]
+fn test_line_editing() {
+    let mut editor = Editor::new(None);
+    type_text(&mut editor, "B1=A1+2");
+    press(&mut editor, KeyCode::Left);
+    press(&mut editor, KeyCode::Left);
+    press(&mut editor, KeyCode::Backspace);
+    type_text(&mut editor, "*");
+    assert_eq!(line(&editor), "B1=A*+2");
+    press(&mut editor, KeyCode::Home);
+    press(&mut editor, KeyCode::Delete);
+    type_text(&mut editor, "C");
+    assert_eq!(editor.display(), ("C1=A*+2".to_string(), 1));
+    ctrl(&mut editor, 'k');
+    assert_eq!(line(&editor), "C");
+    type_text(&mut editor, "1=sort A1 B1");
+    ctrl(&mut editor, 'w');
+    assert_eq!(line(&editor), "C1=sort A1 ");
+    assert_eq!(
+        press(&mut editor, KeyCode::Enter),
+        Edit::Submit("C1=sort A1 ".to_string())
+    );
+    assert!(editor.line.is_empty());
+    assert_eq!(ctrl(&mut editor, 'd'), Edit::Eof);
+}
+
+#[test]
+fn test_history_recall() {
+    let mut editor = editor_with(&["A1=1", "B1=2", "B1=2", ""]);
+    assert_eq!(editor.history, ["A1=1", "B1=2"]);
+    type_text(&mut editor, "C1=");
+    press(&mut editor, KeyCode::Up);
+    assert_eq!(line(&editor), "B1=2");
+    press(&mut editor, KeyCode::Up);
+    press(&mut editor, KeyCode::Up);
+    assert_eq!(line(&editor), "A1=1");
+    press(&mut editor, KeyCode::Down);
+    assert_eq!(line(&editor), "B1=2");
+    // going past the newest entry gives back the line being typed
+    press(&mut editor, KeyCode::Down);
+    assert_eq!(line(&editor), "C1=");
+    assert_eq!(editor.cursor, 3);
+}
+
+#[test]
+fn test_reverse_search() {
+    let mut editor = editor_with(&["A1=SUM(B1:B9)", "save one.json", "A2=SUM(C1:C9)"]);
+    ctrl(&mut editor, 'r');
+    type_text(&mut editor, "SUM");
+    assert_eq!(editor.display().0, "(reverse-i-search)`SUM': A2=SUM(C1:C9)");
+    ctrl(&mut editor, 'r');
+    assert_eq!(editor.display().0, "(reverse-i-search)`SUM': A1=SUM(B1:B9)");
+    // moving the cursor ends the search on the found line
+    press(&mut editor, KeyCode::Left);
+    assert_eq!(editor.display(), ("A1=SUM(B1:B9)".to_string(), 12));
+
+    press(&mut editor, KeyCode::Home);
+    ctrl(&mut editor, 'k');
+    ctrl(&mut editor, 'r');
+    type_text(&mut editor, "json");
+    assert_eq!(
+        press(&mut editor, KeyCode::Enter),
+        Edit::Submit("save one.json".to_string())
+    );
+    ctrl(&mut editor, 'r');
+    type_text(&mut editor, "A");
+    press(&mut editor, KeyCode::Esc);
+    assert_eq!(editor.display(), (String::new(), 0));
+}
+
+#[test]
+fn test_tab_completion() {
+    assert_eq!(completions("sc"), (0, vec!["scroll_to ".to_string()]));
+    assert_eq!(
+        completions("B1=A1+s"),
+        (
+            6,
+            vec![
+                "SLEEP(".to_string(),
+                "STDEV(".to_string(),
+                "SUM(".to_string()
+            ]
+        )
+    );
+    assert_eq!(completions("find su"), (5, Vec::<String>::new()));
+
+    let mut editor = Editor::new(None);
+    type_text(&mut editor, "sc");
+    assert_eq!(press(&mut editor, KeyCode::Tab), Edit::Continue);
+    assert_eq!(line(&editor), "scroll_to ");
+
+    let mut editor = Editor::new(None);
+    type_text(&mut editor, "in");
+    press(&mut editor, KeyCode::Tab);
+    assert_eq!(line(&editor), "insert_");
+    assert_eq!(
+        press(&mut editor, KeyCode::Tab),
+        Edit::Candidates(vec!["insert_col ".to_string(), "insert_row ".to_string()])
+    );
+
+    let mut editor = Editor::new(None);
+    type_text(&mut editor, "C1=av");
+    press(&mut editor, KeyCode::Tab);
+    type_text(&mut editor, "A1:A5)");
+    assert_eq!(line(&editor), "C1=AVG(A1:A5)");
+}
+
+#[test]
+fn test_history_file() {
+    let path = std::env::temp_dir().join("test_editor_history");
+    let _ = std::fs::remove_file(&path);
+    let mut editor = Editor::new(Some(path.clone()));
+    editor.add_history("A1=5");
+    editor.add_history("show A1");
+
+    let mut editor = Editor::new(Some(path.clone()));
+    assert_eq!(editor.history, ["A1=5", "show A1"]);
+    press(&mut editor, KeyCode::Up);
+    assert_eq!(line(&editor), "show A1");
+
+    // only the newest commands are kept
+    for i in 0..1005 {
+        editor.add_history(&format!("B1={}", i));
+    }
+    let editor = Editor::new(Some(path.clone()));
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(editor.history.len(), 1000);
+    assert_eq!(editor.history[0], "B1=5");
+    assert_eq!(editor.history[999], "B1=1004");
+}
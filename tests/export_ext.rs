@@ -0,0 +1,161 @@
+use project::extension::backend::backend::*;
+use project::extension::backend::export::*;
+use project::extension::common::{Operation, Value};
+use project::extension::parser::parser;
+use std::fs;
+
+fn sample_backend() -> Backend {
+    let mut backend = Backend::init_backend(3, 3);
+    for cmd in ["A1=5", "B1=A1+3", "A2=SUM(A1:B1)", "B2=1/0"] {
+        backend.process_command(3, 3, cmd.to_string());
+    }
+    backend
+}
+
+#[test]
+fn test_formula_display() {
+    let cell = |r, c| Some(Box::new(Value::Cell(r, c)));
+    let constant = |v| Some(Box::new(Value::Const(v)));
+    assert_eq!(Value::Cell(12, 28).to_string(), "AB12");
+    assert_eq!(Value::Const(-7).to_string(), "-7");
+    assert_eq!(
+        Value::Oper(cell(1, 1), constant(3), Operation::Add).to_string(),
+        "A1+3"
+    );
+    assert_eq!(
+        Value::Oper(constant(10), cell(4, 4), Operation::Div).to_string(),
+        "10/D4"
+    );
+    assert_eq!(
+        Value::Oper(cell(1, 1), cell(5, 2), Operation::Sum).to_string(),
+        "SUM(A1:B5)"
+    );
+    assert_eq!(
+        Value::Oper(cell(2, 3), constant(0), Operation::Cons).to_string(),
+        "C2"
+    );
+    assert_eq!(
+        Value::Oper(constant(2), constant(0), Operation::Slp).to_string(),
+        "SLEEP(2)"
+    );
+}
+
+#[test]
+fn test_validate_export_commands() {
+    let range = (
+        Some(Box::new(Value::Cell(1, 1))),
+        Some(Box::new(Value::Cell(2, 2))),
+    );
+    assert_eq!(
+        parser::validate("export_md A1:B2 out.md", &3, &3),
+        Some((
+            None,
+            Some(Value::Oper(
+                range.0.clone(),
+                range.1.clone(),
+                Operation::ExportMarkdown("out.md".to_string(), false),
+            ))
+        ))
+    );
+    assert_eq!(
+        parser::validate("export_html A1:B2 out.html formulas", &3, &3),
+        Some((
+            None,
+            Some(Value::Oper(
+                range.0,
+                range.1,
+                Operation::ExportHtml("out.html".to_string(), true),
+            ))
+        ))
+    );
+    assert!(parser::validate("export_md A1:B2 out.md values", &3, &3).is_none());
+    assert!(parser::validate("export_html A1:D2 out.html", &3, &3).is_none());
+}
+
+#[test]
+fn test_range_to_markdown() {
+    let backend = sample_backend();
+    let start = Value::Cell(1, 1);
+    let end = Value::Cell(2, 2);
+    assert_eq!(
+        range_to_markdown(backend.get_grid(), &start, &end, false),
+        "|   | A | B |\n|---|---:|---:|\n| 1 | 5 | 8 |\n| 2 | 13 | **ERR** |\n"
+    );
+    assert_eq!(
+        range_to_markdown(backend.get_grid(), &start, &end, true),
+        "|   | A | B |\n|---|---:|---:|\n| 1 | 5 | 8 `=A1+3` |\n| 2 | 13 `=SUM(A1:B1)` | **ERR** `=1/0` |\n"
+    );
+}
+
+#[test]
+fn test_range_to_html() {
+    let backend = sample_backend();
+    let html = range_to_html(
+        backend.get_grid(),
+        &Value::Cell(1, 2),
+        &Value::Cell(2, 2),
+        true,
+    );
+    assert!(html.starts_with("<table>\n  <thead>\n    <tr><th></th><th>B</th></tr>"));
+    assert!(html.contains("<tr><th>1</th><td>8 <code>=A1+3</code></td></tr>"));
+    assert!(html.contains(
+        "<tr><th>2</th><td class=\"error\" style=\"color: red\">ERR <code>=1/0</code></td></tr>"
+    ));
+}
+
+#[test]
+fn test_export_commands_write_files() {
+    let mut backend = sample_backend();
+    let path = "test_export_commands.md";
+    let status = backend.process_command(3, 3, format!("export_md A1:A2 {}", path));
+    assert_eq!(status, Status::Success);
+    assert_eq!(
+        fs::read_to_string(path).unwrap(),
+        "|   | A |\n|---|---:|\n| 1 | 5 |\n| 2 | 13 |\n"
+    );
+    let _ = fs::remove_file(path);
+
+    let status =
+        backend.process_command(3, 3, "export_html A1:A2 missing_dir/out.html".to_string());
+    assert!(matches!(status, Status::Error(_)));
+}
+
+#[test]
+fn test_range_to_csv_and_json() {
+    let valgrid = sample_backend().get_valgrid();
+    let (start, end) = (Value::Cell(1, 1), Value::Cell(2, 2));
+    assert_eq!(range_label(&start, &end), "A1:B2");
+    assert_eq!(range_to_csv(&valgrid, &start, &end), "5,8\n13,ERR\n");
+    assert_eq!(
+        ranges_to_json(&valgrid, &[(start, end)]),
+        "{\"A1:B2\":[[5,8],[13,null]]}"
+    );
+}
+
+#[test]
+fn test_blank_cells_export_empty() {
+    let backend = sample_backend();
+    let valgrid = backend.get_valgrid();
+    let (start, end) = (Value::Cell(1, 1), Value::Cell(1, 3));
+    assert_eq!(range_to_csv(&valgrid, &start, &end), "5,8,\n");
+    assert_eq!(
+        ranges_to_json(&valgrid, &[(start, end)]),
+        "{\"A1:C1\":[[5,8,null]]}"
+    );
+    assert_eq!(
+        range_to_markdown(
+            backend.get_grid(),
+            &Value::Cell(2, 2),
+            &Value::Cell(2, 3),
+            false
+        ),
+        "|   | B | C |\n|---|---:|---:|\n| 2 | **ERR** |  |\n"
+    );
+    let html = range_to_html(
+        backend.get_grid(),
+        &Value::Cell(3, 1),
+        &Value::Cell(3, 1),
+        true,
+    );
+    assert!(html.contains("<tr><th>3</th><td></td></tr>"));
+}
@@ -0,0 +1,136 @@
+use project::extension::backend::backend::*;
+use project::extension::backend::search::*;
+use project::extension::common::{Operation, Value};
+use project::extension::parser::parser;
+
+fn run(backend: &mut Backend, cmd: &str) -> Status {
+    let rows = backend.get_grid().get_row_size() - 1;
+    let columns = backend.get_grid().get_column_size() - 1;
+    backend.process_command(rows, columns, cmd.to_string())
+}
+
+fn formula(backend: &Backend, row: usize, col: usize) -> Option<String> {
+    backend.get_grid().cells_vec[row][col].formula()
+}
+
+fn value(backend: &Backend, row: usize, col: usize) -> Option<isize> {
+    backend.get_node_value(Value::Cell(row, col))
+}
+
+/// Sheet with A1=3, A10=4, B1=A1+A10, B2=SUM(A1:A10) and C1=$A$1*2
+fn sheet() -> Backend {
+    let mut backend = Backend::init_backend(10, 5);
+    for cmd in ["A1=3", "A10=4", "B1=A1+A10", "B2=SUM(A1:A10)", "C1=$A$1*2"] {
+        assert_eq!(run(&mut backend, cmd), Status::Success);
+    }
+    backend
+}
+
+#[test]
+fn test_validate_find_and_replace() {
+    assert_eq!(
+        parser::validate("find SUM", &10, &10),
+        Some((
+            None,
+            Some(Value::Oper(None, None, Operation::Find("SUM".to_string())))
+        ))
+    );
+    assert_eq!(
+        parser::validate("replace A1 with B1", &10, &10),
+        Some((
+            None,
+            Some(Value::Oper(
+                None,
+                None,
+                Operation::Replace("A1".to_string(), "B1".to_string())
+            ))
+        ))
+    );
+    assert!(parser::validate("find ", &10, &10).is_none());
+    assert!(parser::validate("replace A1 B1", &10, &10).is_none());
+}
+
+#[test]
+fn test_whole_word_matching() {
+    assert!(formula_contains("A1+A10", "A1"));
+    assert!(!formula_contains("A10+B1", "A1"));
+    assert!(!formula_contains("Data!A1", "A1"));
+    assert!(formula_contains("$A$1*2", "A1"));
+    assert!(formula_contains("SUM(A1:A10)", "A1:A10"));
+    assert_eq!(
+        replace_in_formula("A1+A10", "A1", "B1"),
+        Some("B1+A10".to_string())
+    );
+    assert_eq!(
+        replace_in_formula("$A$1*2", "A1", "B3"),
+        Some("$B$3*2".to_string())
+    );
+    assert_eq!(replace_in_formula("A10*2", "A1", "B3"), None);
+}
+
+#[test]
+fn test_find_by_value_and_formula() {
+    let mut backend = sheet();
+    assert_eq!(
+        run(&mut backend, "find A10"),
+        Status::Report("B1: A1+A10 = 7\nB2: SUM(A1:A10) = 7".to_string())
+    );
+    assert_eq!(
+        run(&mut backend, "find 7"),
+        Status::Report("B1: A1+A10 = 7\nB2: SUM(A1:A10) = 7".to_string())
+    );
+    assert_eq!(
+        run(&mut backend, "find 3"),
+        Status::Report("A1: 3".to_string())
+    );
+    // blank cells never match
+    assert_eq!(
+        run(&mut backend, "find 0"),
+        Status::Report("No matches".to_string())
+    );
+}
+
+#[test]
+fn test_replace_is_reference_aware() {
+    let mut backend = sheet();
+    run(&mut backend, "A2=5");
+    assert_eq!(
+        run(&mut backend, "replace A1 with A2"),
+        Status::Report("Replaced in B1, C1, B2".to_string())
+    );
+    assert_eq!(formula(&backend, 1, 2), Some("A2+A10".to_string()));
+    assert_eq!(formula(&backend, 1, 3), Some("$A$2*2".to_string()));
+    // A10, the other corner of the range, is left alone
+    assert_eq!(formula(&backend, 2, 2), Some("SUM(A2:A10)".to_string()));
+    assert_eq!(value(&backend, 1, 2), Some(9));
+    assert_eq!(value(&backend, 1, 3), Some(10));
+    assert_eq!(value(&backend, 2, 2), Some(9));
+
+    // the whole batch is one undo step
+    assert_eq!(run(&mut backend, "undo"), Status::Success);
+    assert_eq!(formula(&backend, 1, 2), Some("A1+A10".to_string()));
+    assert_eq!(formula(&backend, 1, 3), Some("$A$1*2".to_string()));
+    assert_eq!(value(&backend, 1, 2), Some(7));
+}
+
+#[test]
+fn test_failed_replace_changes_nothing() {
+    let mut backend = sheet();
+    assert_eq!(
+        run(&mut backend, "replace A10 with B2"),
+        Status::CircularDependency
+    );
+    assert_eq!(formula(&backend, 1, 2), Some("A1+A10".to_string()));
+    assert_eq!(
+        run(&mut backend, "replace A1 with 1+"),
+        Status::Error("Replacing gives an invalid formula in B1: 1++A10".to_string())
+    );
+    assert_eq!(
+        run(&mut backend, "replace A1 with Cost"),
+        Status::Error("Unknown name Cost in B1".to_string())
+    );
+    assert_eq!(
+        run(&mut backend, "replace Z9 with A1"),
+        Status::Report("No matches".to_string())
+    );
+}
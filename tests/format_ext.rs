@@ -0,0 +1,130 @@
+use project::extension::backend::backend::*;
+use project::extension::common::{NumberFormat, Operation, Value};
+use project::extension::frontend::terminal::Frontend;
+use project::extension::parser::parser;
+
+fn run(backend: &mut Backend, cmd: &str) -> Status {
+    let rows = backend.get_grid().get_row_size() - 1;
+    let columns = backend.get_grid().get_column_size() - 1;
+    backend.process_command(rows, columns, cmd.to_string())
+}
+
+fn format(backend: &Backend, row: usize, col: usize) -> Option<NumberFormat> {
+    backend.get_grid().cells_vec[row][col].format.clone()
+}
+
+#[test]
+fn test_validate_format() {
+    let format = |start, end, format| {
+        Some((
+            None,
+            Some(Value::Oper(
+                Some(Box::new(start)),
+                Some(Box::new(end)),
+                Operation::Format(format),
+            )),
+        ))
+    };
+    assert_eq!(
+        parser::validate("format A1:B3 fixed 2", &10, &10),
+        format(
+            Value::Cell(1, 1),
+            Value::Cell(3, 2),
+            Some(NumberFormat::Fixed(2))
+        )
+    );
+    assert_eq!(
+        parser::validate("format C2 currency €", &10, &10),
+        format(
+            Value::Cell(2, 3),
+            Value::Cell(2, 3),
+            Some(NumberFormat::Currency("€".to_string()))
+        )
+    );
+    assert_eq!(
+        parser::validate("format C2 general", &10, &10),
+        format(Value::Cell(2, 3), Value::Cell(2, 3), None)
+    );
+    assert!(parser::validate("format C2", &10, &10).is_none());
+    assert!(parser::validate("format C2 fixed", &10, &10).is_none());
+    assert!(parser::validate("format C2 fixed 11", &10, &10).is_none());
+    assert!(parser::validate("format C2 money", &10, &10).is_none());
+}
+
+#[test]
+fn test_number_formats() {
+    assert_eq!(NumberFormat::Thousands.apply(1234567), "1,234,567");
+    assert_eq!(NumberFormat::Thousands.apply(-1000), "-1,000");
+    assert_eq!(NumberFormat::Thousands.apply(999), "999");
+    assert_eq!(NumberFormat::Fixed(2).apply(-5), "-5.00");
+    assert_eq!(NumberFormat::Fixed(0).apply(7), "7");
+    assert_eq!(NumberFormat::Percent.apply(25), "25%");
+    assert_eq!(
+        NumberFormat::Currency("$".to_string()).apply(-1234),
+        "-$1,234"
+    );
+    assert_eq!(NumberFormat::Scientific.apply(1234567), "1.23E+06");
+    assert_eq!(NumberFormat::Scientific.apply(-999999), "-1.00E+06");
+    assert_eq!(NumberFormat::Scientific.apply(0), "0.00E+00");
+    assert_eq!(NumberFormat::Date.apply(45000), "2023-03-15");
+    assert_eq!(NumberFormat::Date.apply(25569), "1970-01-01");
+    assert_eq!(NumberFormat::Date.apply(36585), "2000-02-29");
+}
+
+#[test]
+fn test_format_keeps_values() {
+    let mut backend = Backend::init_backend(5, 5);
+    run(&mut backend, "A1=1500");
+    run(&mut backend, "A2=A1*2");
+    assert_eq!(run(&mut backend, "format A1:A2 thousands"), Status::Success);
+    assert_eq!(format(&backend, 2, 1), Some(NumberFormat::Thousands));
+    assert_eq!(backend.get_node_value(Value::Cell(2, 1)), Some(3000));
+    assert_eq!(format(&backend, 3, 1), None);
+
+    // clearing a cell keeps its format for the next value
+    run(&mut backend, "clear A1");
+    run(&mut backend, "A1=2000");
+    assert_eq!(format(&backend, 1, 1), Some(NumberFormat::Thousands));
+
+    assert_eq!(run(&mut backend, "undo"), Status::Success);
+    assert_eq!(run(&mut backend, "undo"), Status::Success);
+    assert_eq!(run(&mut backend, "undo"), Status::Success);
+    assert_eq!(format(&backend, 1, 1), None);
+    assert_eq!(run(&mut backend, "format A1 general"), Status::Success);
+}
+
+#[test]
+fn test_formats_are_displayed() {
+    let mut frontend = Frontend::init_frontend(5, 5, "").unwrap();
+    for cmd in ["A1=1500", "A2=A1/0", "format A1:A3 currency", "B1=45000"] {
+        frontend.backend.process_command(5, 5, cmd.to_string());
+    }
+    frontend
+        .backend
+        .process_command(5, 5, "format B1 date".to_string());
+    assert_eq!(frontend.cell_text(1, 1), "$1,500");
+    assert_eq!(frontend.cell_text(2, 1), "ERR");
+    assert_eq!(frontend.cell_text(3, 1), "");
+    assert_eq!(frontend.cell_text(1, 2), "2023-03-15");
+
+    let valgrid = frontend.backend.get_valgrid();
+    assert_eq!(valgrid.cells[1][2], Some(45000));
+    assert_eq!(valgrid.formats[1][2], Some(NumberFormat::Date));
+    assert_eq!(valgrid.formats[1][3], None);
+}
+
+#[test]
+fn test_formats_round_trip() {
+    let mut backend = Backend::init_backend(5, 5);
+    run(&mut backend, "A1=25");
+    run(&mut backend, "format A1 percent");
+    run(&mut backend, "insert_row 1");
+    assert_eq!(format(&backend, 2, 1), Some(NumberFormat::Percent));
+
+    let temp_file = "test_formats_round_trip.json";
+    assert!(backend.serial(temp_file).is_ok());
+    let loaded = Backend::deserial(temp_file).unwrap();
+    std::fs::remove_file(temp_file).unwrap();
+    assert_eq!(format(&loaded, 2, 1), Some(NumberFormat::Percent));
+    assert_eq!(loaded.get_node_value(Value::Cell(2, 1)), Some(25));
+}
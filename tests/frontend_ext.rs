@@ -24,7 +24,7 @@ mod frontend_tests {
     #[test]
     fn test_frontend_initialization() {
         // Test initialization with empty path
-        let frontend = Frontend::init_frontend(10, 20, "");
+        let frontend = Frontend::init_frontend(10, 20, "").unwrap();
 
         // Check that default values are set correctly
         assert_eq!(frontend.start, Value::Cell(1, 1));
@@ -41,7 +41,7 @@ mod frontend_tests {
     #[test]
     fn test_frontend_from_file() {
         // Test initialization with empty path
-        let frontend = Frontend::init_frontend(11, 11, "gradient.json");
+        let frontend = Frontend::init_frontend(11, 11, "gradient.json").unwrap();
 
         // Check that default values are set correctly
         assert_eq!(frontend.start, Value::Cell(1, 1));
@@ -56,10 +56,17 @@ mod frontend_tests {
         // This part is more complex and might need mocking, so we'll skip it for now
     }
 
+    #[test]
+    fn test_frontend_from_missing_file() {
+        // A sheet that cannot be loaded is an error, not an empty grid
+        let result = Frontend::init_frontend(5, 5, "no_such_sheet.json");
+        assert!(result.is_err_and(|e| e.starts_with("Failed to open 'no_such_sheet.json'")));
+    }
+
     #[test]
     fn test_execute_status() {
         // Test navigation commands
-        let mut frontend = Frontend::init_frontend(100, 100, "");
+        let mut frontend = Frontend::init_frontend(100, 100, "").unwrap();
 
         // Test Left command
         frontend.start = Value::Cell(50, 50);
@@ -116,7 +123,7 @@ mod frontend_tests {
 
     #[test]
     fn test_print_grid_enabled() {
-        let mut frontend = Frontend::init_frontend(10, 10, "");
+        let mut frontend = Frontend::init_frontend(10, 10, "").unwrap();
 
         // Directly access and modify grid nodes through public field
         frontend.backend.grid.cells_vec[1][1].node_value = 100;
@@ -141,7 +148,7 @@ mod frontend_tests {
     }
     #[test]
     fn test_print_grid_err() {
-        let mut frontend = Frontend::init_frontend(10, 10, "");
+        let mut frontend = Frontend::init_frontend(10, 10, "").unwrap();
 
         // Directly access and modify grid nodes through public field
         frontend.backend.grid.cells_vec[1][1].node_value = 100;
@@ -167,7 +174,7 @@ mod frontend_tests {
 
     #[test]
     fn test_print_grid_disabled() {
-        let mut frontend = Frontend::init_frontend(10, 10, "");
+        let mut frontend = Frontend::init_frontend(10, 10, "").unwrap();
 
         // Set up some test data directly
         frontend.backend.grid.cells_vec[1][1].node_value = 100;
@@ -185,7 +192,7 @@ mod frontend_tests {
 
     #[test]
     fn test_display() {
-        let mut frontend = Frontend::init_frontend(10, 10, "");
+        let mut frontend = Frontend::init_frontend(10, 10, "").unwrap();
 
         // Set up a test cell
         frontend.backend.grid.cells_vec[1][1].node_value = 100;
@@ -281,7 +288,7 @@ mod frontend_tests {
     #[test]
     fn test_invalid_location_dimension() {
         // Test handling of invalid location/dimension values
-        let mut frontend = Frontend::init_frontend(10, 10, "");
+        let mut frontend = Frontend::init_frontend(10, 10, "").unwrap();
 
         // Set invalid values that don't match the Cell pattern
         frontend.start = Value::Const(42); // Using Const instead of Number
@@ -304,7 +311,7 @@ mod frontend_tests {
 
     #[test]
     fn test_command_processing() {
-        let mut frontend = Frontend::init_frontend(10, 10, "");
+        let mut frontend = Frontend::init_frontend(10, 10, "").unwrap();
 
         // Test that commands are properly passed to backend
         // You might need to mock backend.process_command to verify this
@@ -322,7 +329,7 @@ mod frontend_tests {
 
     #[test]
     fn test_dimension_boundaries() {
-        let mut frontend = Frontend::init_frontend(200, 200, "");
+        let mut frontend = Frontend::init_frontend(200, 200, "").unwrap();
 
         // Test that print_grid correctly handles cells at boundaries
         frontend.start = Value::Cell(1, 1);
@@ -337,7 +344,7 @@ mod frontend_tests {
 
     #[test]
     fn test_print_grid_formatting() {
-        let mut frontend = Frontend::init_frontend(10, 10, "");
+        let mut frontend = Frontend::init_frontend(10, 10, "").unwrap();
 
         // Set up a variety of cell values to test formatting
         frontend.backend.grid.cells_vec[1][1].node_value = 12345; // Large number
@@ -356,7 +363,7 @@ mod frontend_tests {
 
     #[test]
     fn test_execute_status_chain() {
-        let mut frontend = Frontend::init_frontend(100, 100, "");
+        let mut frontend = Frontend::init_frontend(100, 100, "").unwrap();
 
         // Test complex sequence of status commands
         frontend.start = Value::Cell(50, 50);
@@ -377,6 +384,32 @@ mod frontend_tests {
         assert_eq!(frontend.print_enabled, true);
     }
 
+    #[test]
+    fn test_confirm_open() {
+        let temp_file = "test_confirm_open.json";
+        let saved = Backend::init_backend(4, 6);
+        saved.serial(temp_file).unwrap();
+
+        let mut frontend = Frontend::init_frontend(10, 10, "").unwrap();
+        frontend.backend.process_command(10, 10, "A1=1".to_string());
+
+        // Declining keeps the current sheet
+        let mut answer = std::io::Cursor::new("n\n");
+        let status = frontend.confirm_open(temp_file, &mut answer);
+        assert_eq!(status, Status::UnsavedChanges(temp_file.to_string()));
+        assert_eq!(frontend.backend.get_grid().get_row_size(), 11);
+
+        // Accepting opens the file and resizes the view
+        let mut answer = std::io::Cursor::new("y\n");
+        let status = frontend.confirm_open(temp_file, &mut answer);
+        assert_eq!(status, Status::Opened);
+        frontend.execute_status(&status);
+        assert_eq!(frontend.dimension, Value::Cell(4, 6));
+        assert_eq!(frontend.start, Value::Cell(1, 1));
+
+        let _ = std::fs::remove_file(temp_file);
+    }
+
     // Note: run_counter and display are harder to test in isolation
     // as they deal with stdin/stdout and would require more complex mocking
 }
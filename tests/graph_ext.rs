@@ -263,6 +263,29 @@ mod node_tests {
         assert!(!grid.get_node(3, 3).visited);
     }
 
+    #[test]
+    fn test_has_cycle_diamond() {
+        let mut grid = create_test_grid(5, 5);
+
+        // A1 -> B2 -> D4 and A1 -> C3 -> D4 reach D4 twice without a cycle
+        let a1 = Value::Cell(1, 1);
+        let b2 = Value::Cell(2, 2);
+        let c3 = Value::Cell(3, 3);
+        let d4 = Value::Cell(4, 4);
+
+        grid.get_node(1, 1).add_dep(b2.clone());
+        grid.get_node(1, 1).add_dep(c3.clone());
+        grid.get_node(2, 2).add_dep(d4.clone());
+        grid.get_node(3, 3).add_dep(d4.clone());
+
+        assert!(!has_cycle(&mut grid, a1.clone()));
+        assert!(!grid.get_node(4, 4).visited);
+
+        // closing the loop back to A1 is a cycle
+        grid.get_node(4, 4).add_dep(a1.clone());
+        assert!(has_cycle(&mut grid, a1.clone()));
+    }
+
     #[test]
     fn test_has_cycle_with_cycle() {
         let mut grid = create_test_grid(5, 5);
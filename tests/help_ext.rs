@@ -0,0 +1,139 @@
+use project::extension::backend::backend::*;
+use project::extension::common::{Operation, Value};
+use project::extension::frontend::terminal::Frontend;
+use project::extension::parser::help::*;
+use project::extension::parser::parser::{self, COMMANDS, FUNCTIONS, describe};
+
+fn run(backend: &mut Backend, cmd: &str) -> Status {
+    let rows = backend.get_grid().get_row_size() - 1;
+    let columns = backend.get_grid().get_column_size() - 1;
+    backend.process_command(rows, columns, cmd.to_string())
+}
+
+/// Explains `cmd` on a sheet of 20 rows and 5 columns.
+fn explain_20x5(cmd: &str) -> String {
+    describe(&parser::check(cmd, &5, &20).unwrap_err())
+}
+
+#[test]
+fn test_validate_help() {
+    let help = |name: Option<&str>| {
+        Some((
+            None,
+            Some(Value::Oper(
+                None,
+                None,
+                Operation::Help(name.map(str::to_string)),
+            )),
+        ))
+    };
+    assert_eq!(parser::validate("help", &10, &10), help(None));
+    assert_eq!(parser::validate("help sort", &10, &10), help(Some("sort")));
+    assert_eq!(parser::validate("help sum", &10, &10), help(Some("SUM")));
+    assert!(parser::validate("help bogus", &10, &10).is_none());
+}
+
+#[test]
+fn test_every_command_has_help() {
+    let listing = help_text(None).unwrap();
+    for name in COMMANDS
+        .iter()
+        .chain(FUNCTIONS)
+        .chain(&["w", "a", "s", "d", "q"])
+    {
+        let topic = find_topic(name).unwrap_or_else(|| panic!("no help for {}", name));
+        assert!(listing.contains(topic.syntax), "{} is not listed", name);
+        assert!(listing.contains(topic.example), "{} has no example", name);
+    }
+    assert_eq!(
+        help_text(Some("fill_down")).unwrap(),
+        "fill_down RANGE\n  Copy the first row of a range down the rest of it.\n  Example: fill_down A2:C10"
+    );
+}
+
+#[test]
+fn test_help_reports() {
+    let mut backend = Backend::init_backend(5, 5);
+    let Status::Report(text) = run(&mut backend, "help") else {
+        panic!("help returns a report");
+    };
+    assert!(text.starts_with("Commands:"));
+    assert!(text.contains("\nFunctions:\n"));
+    let Status::Report(text) = run(&mut backend, "help STDEV") else {
+        panic!("help returns a report");
+    };
+    assert!(text.starts_with("STDEV(RANGE|NAME)\n"));
+    assert_eq!(run(&mut backend, "help nothing"), Status::UnrecognizedCmd);
+}
+
+#[test]
+fn test_explain_formulas() {
+    assert_eq!(
+        explain_20x5("A1=FOO(B1:B2)"),
+        "unknown function FOO at col 4"
+    );
+    assert_eq!(
+        explain_20x5("A1=sum(B1:B2)"),
+        "unknown function sum (did you mean SUM?) at col 4"
+    );
+    assert_eq!(explain_20x5("A1=B0+1"), "B0 is outside sheet 20x5");
+    assert_eq!(explain_20x5("F1=3"), "F1 is outside sheet 20x5");
+    assert_eq!(explain_20x5("A1=B1+"), "missing operand after + at col 6");
+    assert_eq!(
+        explain_20x5("A1 = 2 * x?"),
+        "expected a number, cell or name, found 'x?' at col 10"
+    );
+    assert_eq!(explain_20x5("A1=SUM(B1:B2"), "missing ) at col 13");
+    // columns count characters, not bytes
+    assert_eq!(explain_20x5("A1=SUM(€1:B2"), "missing ) at col 13");
+    assert_eq!(
+        explain_20x5("A1=MAX(B5:B1)"),
+        "range B5:B1 is reversed at col 8"
+    );
+    assert_eq!(
+        explain_20x5("A1=AVG(B1)"),
+        "AVG takes a range such as A1:A10, found 'B1' at col 8"
+    );
+    assert_eq!(
+        explain_20x5("A1=SUM(A:F)"),
+        "A:F is not a range of rows or columns inside sheet 20x5"
+    );
+    assert_eq!(explain_20x5("=5"), "expected a cell at col 1");
+    assert_eq!(
+        explain_20x5("A1=SUM(Data!B1:B0)"),
+        "B0 is outside sheet 999x18278"
+    );
+    assert!(parser::check("A1=SUM(B1:B2)", &5, &20).is_ok());
+}
+
+#[test]
+fn test_explain_commands() {
+    assert_eq!(
+        explain_20x5("frobnicate A1"),
+        "unknown command frobnicate, type help for a list"
+    );
+    assert_eq!(explain_20x5("scroll_to B0"), "B0 is outside sheet 20x5");
+    assert_eq!(explain_20x5("copy A1:B2 C5"), "usage: copy RANGE to CELL");
+    assert_eq!(
+        explain_20x5("clear C3:A1"),
+        "range C3:A1 is reversed at col 7"
+    );
+    assert_eq!(
+        explain_20x5("help bogus"),
+        "no help for bogus, type help for a list"
+    );
+
+    // the frontends show the explanation instead of a bare failure
+    let mut frontend = Frontend::init_frontend(20, 5, "").unwrap();
+    assert_eq!(
+        frontend.process_command("A1=FOO(B1:B2)"),
+        Status::Error("unknown function FOO at col 4".to_string())
+    );
+    let status = frontend.process_command("show A99");
+    assert_eq!(
+        frontend.status_message(&status),
+        Some("A99 is outside sheet 20x5".to_string())
+    );
+    // failures of commands that do parse keep their status
+    assert_eq!(frontend.process_command("undo"), Status::UnrecognizedCmd);
+}
@@ -0,0 +1,150 @@
+use project::extension::backend::backend::*;
+use project::extension::common::{Operation, Value};
+use project::extension::parser::parser;
+
+fn run(backend: &mut Backend, cmd: &str) -> Status {
+    let rows = backend.get_grid().get_row_size() - 1;
+    let columns = backend.get_grid().get_column_size() - 1;
+    backend.process_command(rows, columns, cmd.to_string())
+}
+
+fn report(lines: &[&str]) -> Status {
+    Status::Report(lines.join("\n"))
+}
+
+/// Sheet with A1=0, A2=5, B1=A2/A1, B2=B1+1 and C1=SUM(A1:B2)
+fn sheet() -> Backend {
+    let mut backend = Backend::init_backend(10, 5);
+    for cmd in ["A1=0", "A2=5", "B1=A2/A1", "B2=B1+1", "C1=SUM(A1:B2)"] {
+        assert_eq!(run(&mut backend, cmd), Status::Success);
+    }
+    backend
+}
+
+#[test]
+fn test_validate_show_and_trace() {
+    assert_eq!(
+        parser::validate("show B3", &10, &10),
+        Some((
+            Some(Value::Cell(3, 2)),
+            Some(Value::Oper(None, None, Operation::Show))
+        ))
+    );
+    assert_eq!(
+        parser::validate("trace B3", &10, &10),
+        Some((
+            Some(Value::Cell(3, 2)),
+            Some(Value::Oper(None, None, Operation::Trace))
+        ))
+    );
+    assert!(parser::validate("show Z99", &10, &10).is_none());
+    assert!(parser::validate("trace B3:B4", &10, &10).is_none());
+}
+
+#[test]
+fn test_show_cell() {
+    let mut backend = sheet();
+    assert_eq!(
+        run(&mut backend, "show C1"),
+        report(&[
+            "Cell: C1",
+            "Formula: SUM(A1:B2)",
+            "Value: ERR (reads B1, which is an error)",
+            "Precedents: A1:B2",
+            "Dependents: none",
+        ])
+    );
+    run(&mut backend, "A1=2");
+    assert_eq!(
+        run(&mut backend, "show B1"),
+        report(&[
+            "Cell: B1",
+            "Formula: A2/A1",
+            "Value: 2",
+            "Precedents: A2, A1",
+            "Dependents: B2, C1",
+        ])
+    );
+    assert_eq!(
+        run(&mut backend, "show E9"),
+        report(&[
+            "Cell: E9",
+            "Formula: none",
+            "Value: 0 (empty)",
+            "Precedents: none",
+            "Dependents: none",
+        ])
+    );
+}
+
+#[test]
+fn test_show_error_causes() {
+    let mut backend = sheet();
+    let Status::Report(text) = run(&mut backend, "show B1") else {
+        panic!("show returns a report");
+    };
+    assert!(text.contains("Value: ERR (division by zero)"));
+
+    run(&mut backend, "D1=A2*2");
+    run(&mut backend, "delete_row 2");
+    let Status::Report(text) = run(&mut backend, "show D1") else {
+        panic!("show returns a report");
+    };
+    assert!(text.contains("Formula: #REF!*2"));
+    assert!(text.contains("Value: ERR (reads a deleted cell)"));
+}
+
+#[test]
+fn test_trace_tree() {
+    let mut backend = sheet();
+    assert_eq!(
+        run(&mut backend, "trace A2"),
+        report(&[
+            "A2: 5",
+            "Precedents:",
+            "  none",
+            "Dependents:",
+            "  B1: A2/A1 = ERR",
+            "    B2: B1+1 = ERR",
+            "      C1: SUM(A1:B2) = ERR",
+            "    C1: SUM(A1:B2) = ERR (see above)",
+            "  C1: SUM(A1:B2) = ERR (see above)",
+        ])
+    );
+    // empty cells of a range are left out
+    run(&mut backend, "C2=SUM(A1:A5)");
+    assert_eq!(
+        run(&mut backend, "trace C2"),
+        report(&[
+            "C2: SUM(A1:A5) = 5",
+            "Precedents:",
+            "  A1: 0",
+            "  A2: 5",
+            "Dependents:",
+            "  none",
+        ])
+    );
+}
+
+#[test]
+fn test_trace_across_sheets() {
+    let mut backend = sheet();
+    run(&mut backend, "add_sheet Data");
+    run(&mut backend, "switch_sheet Data");
+    run(&mut backend, "A1=Sheet1!A2*2");
+    assert_eq!(
+        run(&mut backend, "trace A1"),
+        report(&[
+            "A1: Sheet1!A2*2 = 10",
+            "Precedents:",
+            "  Sheet1!A2: 5",
+            "Dependents:",
+            "  none",
+        ])
+    );
+    run(&mut backend, "switch_sheet Sheet1");
+    let Status::Report(text) = run(&mut backend, "show A2") else {
+        panic!("show returns a report");
+    };
+    assert!(text.ends_with("Dependents: B1, C1, Data!A1"));
+}
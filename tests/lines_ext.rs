@@ -0,0 +1,155 @@
+use project::extension::backend::backend::*;
+use project::extension::backend::structure::*;
+use project::extension::common::{Anchors, Operation, Value};
+use project::extension::parser::parser;
+
+fn run(backend: &mut Backend, cmd: &str) -> Status {
+    let rows = backend.get_grid().get_row_size() - 1;
+    let columns = backend.get_grid().get_column_size() - 1;
+    backend.process_command(rows, columns, cmd.to_string())
+}
+
+fn formula(backend: &Backend, row: usize, col: usize) -> Option<String> {
+    backend.get_grid().cells_vec[row][col].formula()
+}
+
+fn value(backend: &Backend, row: usize, col: usize) -> Option<isize> {
+    backend.get_node_value(Value::Cell(row, col))
+}
+
+#[test]
+fn test_validate_lines() {
+    assert_eq!(
+        parser::validate("E1=SUM(A:B)", &10, &10),
+        Some((
+            Some(Value::Cell(1, 5)),
+            Some(Value::Oper(
+                Some(Box::new(Value::Column(1))),
+                Some(Box::new(Value::Column(2))),
+                Operation::Sum,
+            ))
+        ))
+    );
+    assert_eq!(
+        parser::validate("E1=MAX(2:2)", &10, &10),
+        Some((
+            Some(Value::Cell(1, 5)),
+            Some(Value::Oper(
+                Some(Box::new(Value::Row(2))),
+                Some(Box::new(Value::Row(2))),
+                Operation::Max,
+            ))
+        ))
+    );
+    // reversed, mixed or outside the sheet
+    assert!(parser::validate("E1=SUM(B:A)", &10, &10).is_none());
+    assert!(parser::validate("E1=SUM(A:2)", &10, &10).is_none());
+    assert!(parser::validate("E1=SUM(11:11)", &10, &10).is_none());
+    assert!(parser::validate("E1=SUM(0:1)", &10, &10).is_none());
+}
+
+#[test]
+fn test_whole_column_sum() {
+    let mut backend = Backend::init_backend(5, 5);
+    run(&mut backend, "A1=1");
+    run(&mut backend, "A2=2");
+    assert_eq!(run(&mut backend, "C1=SUM(A:A)"), Status::Success);
+    assert_eq!(value(&backend, 1, 3), Some(3));
+    assert_eq!(formula(&backend, 1, 3), Some("SUM(A:A)".to_string()));
+
+    // a new value anywhere in the column is picked up
+    run(&mut backend, "A5=10");
+    assert_eq!(value(&backend, 1, 3), Some(13));
+    // one entry for the formula instead of an edge per cell
+    assert!(backend.get_grid().cells_vec[5][1].dependents.is_empty());
+    assert_eq!(backend.get_grid().line_readers.len(), 1);
+    assert_eq!(backend.get_grid().line_readers[0].cell, Value::Cell(1, 3));
+
+    run(&mut backend, "C1=5");
+    assert!(backend.get_grid().line_readers.is_empty());
+    run(&mut backend, "A5=20");
+    assert_eq!(value(&backend, 1, 3), Some(5));
+}
+
+#[test]
+fn test_whole_column_stdev() {
+    let mut backend = Backend::init_backend(5, 5);
+    run(&mut backend, "A1=10");
+    assert_eq!(run(&mut backend, "B1=STDEV(A:A)"), Status::Success);
+    assert_eq!(value(&backend, 1, 2), Some(4));
+    // an edit inside the column recalculates it
+    run(&mut backend, "A2=10");
+    assert_eq!(value(&backend, 1, 2), Some(5));
+    run(&mut backend, "B2=STDEV(A1:A5)");
+    run(&mut backend, "A3=10");
+    assert_eq!(value(&backend, 1, 2), Some(5));
+    assert_eq!(value(&backend, 2, 2), Some(5));
+}
+
+#[test]
+fn test_whole_row_and_chains() {
+    let mut backend = Backend::init_backend(5, 5);
+    run(&mut backend, "A2=4");
+    run(&mut backend, "C2=9");
+    assert_eq!(run(&mut backend, "A4=MAX(2:2)"), Status::Success);
+    assert_eq!(run(&mut backend, "B4=A4+1"), Status::Success);
+    assert_eq!(value(&backend, 4, 2), Some(10));
+    run(&mut backend, "E2=A1+30");
+    assert_eq!(value(&backend, 4, 1), Some(30));
+    assert_eq!(value(&backend, 4, 2), Some(31));
+}
+
+#[test]
+fn test_line_cycles() {
+    let mut backend = Backend::init_backend(5, 5);
+    assert_eq!(run(&mut backend, "A1=SUM(A:A)"), Status::CircularDependency);
+    assert_eq!(run(&mut backend, "B1=SUM(A:A)"), Status::Success);
+    assert_eq!(run(&mut backend, "A3=B1+1"), Status::CircularDependency);
+    assert_eq!(value(&backend, 3, 1), Some(0));
+}
+
+#[test]
+fn test_lines_grow_with_the_sheet() {
+    let mut backend = Backend::init_backend(3, 3);
+    run(&mut backend, "A1=1");
+    run(&mut backend, "A3=2");
+    run(&mut backend, "C1=SUM(A:A)");
+    assert_eq!(run(&mut backend, "insert_row 4"), Status::Success);
+    run(&mut backend, "A4=7");
+    assert_eq!(value(&backend, 1, 3), Some(10));
+
+    // inserting a column before A moves the reference along with it
+    assert_eq!(run(&mut backend, "insert_col A"), Status::Success);
+    assert_eq!(formula(&backend, 1, 4), Some("SUM(B:B)".to_string()));
+    assert_eq!(value(&backend, 1, 4), Some(10));
+    assert_eq!(run(&mut backend, "delete_col B"), Status::Success);
+    assert_eq!(formula(&backend, 1, 3), Some("SUM(#REF!)".to_string()));
+    assert_eq!(value(&backend, 1, 3), None);
+}
+
+#[test]
+fn test_offset_and_shift_lines() {
+    let sum = Value::Oper(
+        Some(Box::new(Value::Column(1))),
+        Some(Box::new(Value::Column(2))),
+        Operation::Sum,
+    );
+    assert_eq!(
+        offset_function(&sum, &Anchors::default(), (3, 1), (10, 10)),
+        Value::Oper(
+            Some(Box::new(Value::Column(2))),
+            Some(Box::new(Value::Column(3))),
+            Operation::Sum,
+        )
+    );
+    // a row edit leaves whole columns alone
+    assert_eq!(shift_function(&sum, Axis::Row, 1, true), sum);
+    assert_eq!(
+        shift_function(&sum, Axis::Column, 2, false),
+        Value::Oper(
+            Some(Box::new(Value::Column(1))),
+            Some(Box::new(Value::Column(1))),
+            Operation::Sum,
+        )
+    );
+}
@@ -0,0 +1,215 @@
+use project::extension::backend::backend::*;
+use project::extension::backend::macros::*;
+use project::extension::common::{Operation, Value};
+use project::extension::parser::parser;
+
+fn run(backend: &mut Backend, cmd: &str) -> Status {
+    let rows = backend.get_grid().get_row_size() - 1;
+    let columns = backend.get_grid().get_column_size() - 1;
+    backend.process_command(rows, columns, cmd.to_string())
+}
+
+fn value(backend: &Backend, row: usize, col: usize) -> Option<isize> {
+    backend.get_node_value(Value::Cell(row, col))
+}
+
+/// A backend whose user macro file lives in a fresh temporary directory.
+fn backend_with_macro_file(test: &str) -> Backend {
+    let dir = std::env::temp_dir().join(format!("macro_ext_{}_{}", test, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let mut backend = Backend::init_backend(10, 10);
+    backend.macro_file = Some(dir.join("macros.json"));
+    let _ = std::fs::remove_file(dir.join("macros.json"));
+    backend
+}
+
+#[test]
+fn test_validate_macro_commands() {
+    let command = |cell, operation| Some((cell, Some(Value::Oper(None, None, operation))));
+    assert_eq!(
+        parser::validate("record start totals", &10, &10),
+        command(None, Operation::RecordStart("totals".to_string(), false))
+    );
+    assert_eq!(
+        parser::validate("record start totals global", &10, &10),
+        command(None, Operation::RecordStart("totals".to_string(), true))
+    );
+    assert_eq!(
+        parser::validate("record stop", &10, &10),
+        command(None, Operation::RecordStop)
+    );
+    assert_eq!(
+        parser::validate("play totals at C2", &10, &10),
+        command(
+            Some(Value::Cell(2, 3)),
+            Operation::Play("totals".to_string())
+        )
+    );
+    assert_eq!(
+        parser::validate("macros", &10, &10),
+        command(None, Operation::ListMacros)
+    );
+    assert!(parser::validate("record start 1x", &10, &10).is_none());
+    assert!(parser::validate("play totals at Z99", &10, &10).is_none());
+    assert!(parser::validate("play totals C2", &10, &10).is_none());
+}
+
+#[test]
+fn test_shift_command() {
+    assert_eq!(
+        shift_command("C1=SUM(A1:B1)", 2, 1).unwrap(),
+        "D3=SUM(B3:C3)"
+    );
+    assert_eq!(
+        shift_command("B2=$A$1+A$1*$A1", 1, 1).unwrap(),
+        "C3=$A$1+B$1*$A2"
+    );
+    assert_eq!(
+        shift_command("A1=Data!B2+Revenue", 1, 0).unwrap(),
+        "A2=Data!B3+Revenue"
+    );
+    assert_eq!(
+        shift_command("copy A1:B2 to C5", 0, 2).unwrap(),
+        "copy C1:D2 to E5"
+    );
+    // text arguments are replayed as typed, except the references of replace
+    assert_eq!(shift_command("find A1", 3, 3).unwrap(), "find A1");
+    assert_eq!(
+        shift_command("replace B1 with $B$1", 1, 0).unwrap(),
+        "replace B2 with $B$1"
+    );
+    assert!(shift_command("B2=A1+1", -1, -1).is_none());
+    let commands = ["C2=A3+B4".to_string(), "format B5 fixed 2".to_string()];
+    assert_eq!(origin(&commands), Some((2, 1)));
+}
+
+#[test]
+fn test_record_and_play() {
+    let mut backend = Backend::init_backend(10, 10);
+    for cmd in ["A1=1", "B1=2", "A2=3", "B2=4"] {
+        run(&mut backend, cmd);
+    }
+    run(&mut backend, "record start total");
+    assert!(matches!(
+        run(&mut backend, "record start other"),
+        Status::Error(_)
+    ));
+    run(&mut backend, "C1=A1+B1");
+    // failed commands and scrolling are not recorded
+    run(&mut backend, "C1=C1+1");
+    run(&mut backend, "s");
+    run(&mut backend, "D1=C1*$A$1");
+    assert_eq!(
+        run(&mut backend, "record stop"),
+        Status::Report("Recorded 2 commands as total".to_string())
+    );
+    assert_eq!(value(&backend, 1, 4), Some(3));
+
+    // A1 is the top-left cell of the macro, so everything but $A$1 moves down a row
+    assert_eq!(run(&mut backend, "play total at A2"), Status::Success);
+    assert_eq!(value(&backend, 2, 3), Some(7));
+    assert_eq!(value(&backend, 2, 4), Some(7));
+    assert_eq!(
+        backend.get_grid().cells_vec[2][4].function,
+        parser::validate("D2=C2*$A$1", &10, &10).unwrap().1
+    );
+    // the whole macro is undone in one step
+    run(&mut backend, "undo");
+    assert_eq!(backend.get_grid().cells_vec[2][3].function, None);
+    assert_eq!(backend.get_grid().cells_vec[2][4].function, None);
+    assert_eq!(value(&backend, 1, 4), Some(3));
+
+    let Status::Report(list) = run(&mut backend, "macros") else {
+        panic!("macros returns a report");
+    };
+    assert_eq!(list, "total (sheet): C1=A1+B1; D1=C1*$A$1");
+}
+
+#[test]
+fn test_record_and_play_replace() {
+    let path = std::env::temp_dir().join(format!("macro_ext_replace_{}.json", std::process::id()));
+    let mut backend = Backend::init_backend(10, 10);
+    for cmd in ["A1=1", "B1=2", "A2=3", "B2=4"] {
+        run(&mut backend, cmd);
+    }
+    run(&mut backend, "record start fix");
+    run(&mut backend, "C1=A1+B1");
+    assert_eq!(
+        run(&mut backend, "replace B1 with $B$1"),
+        Status::Report("Replaced in C1".to_string())
+    );
+    // commands that change nothing are left out even when they succeed
+    run(&mut backend, "show C1");
+    run(&mut backend, &format!("save {}", path.display()));
+    assert_eq!(
+        run(&mut backend, "record stop"),
+        Status::Report("Recorded 2 commands as fix".to_string())
+    );
+    std::fs::remove_file(&path).unwrap();
+
+    // the replace follows the macro down a row, its anchored part stays put
+    assert_eq!(run(&mut backend, "play fix at A2"), Status::Success);
+    assert_eq!(
+        backend.get_grid().cells_vec[2][3].formula(),
+        Some("A2+$B$1".to_string())
+    );
+    assert_eq!(value(&backend, 2, 3), Some(5));
+    assert_eq!(
+        backend.get_grid().cells_vec[1][3].formula(),
+        Some("A1+$B$1".to_string())
+    );
+}
+
+#[test]
+fn test_failed_play_changes_nothing() {
+    let mut backend = Backend::init_backend(10, 10);
+    run(&mut backend, "record start chain");
+    run(&mut backend, "B1=5");
+    run(&mut backend, "C1=A1+1");
+    run(&mut backend, "record stop");
+    run(&mut backend, "A5=C5");
+    // the second command closes a cycle through A5
+    assert_eq!(
+        run(&mut backend, "play chain at A5"),
+        Status::Error("chain stopped at 'C5=A5+1': circular dependency".to_string())
+    );
+    assert_eq!(backend.get_grid().cells_vec[5][2].function, None);
+    // the last undo step is still the one before the macro
+    run(&mut backend, "undo");
+    assert_eq!(backend.get_grid().cells_vec[5][1].function, None);
+    assert_eq!(
+        run(&mut backend, "play chain at J1"),
+        Status::Error("chain stopped at 'K1=5': K1 is outside sheet 10x10".to_string())
+    );
+    assert_eq!(
+        run(&mut backend, "play missing"),
+        Status::Error("Unknown macro missing".to_string())
+    );
+}
+
+#[test]
+fn test_macros_are_saved() {
+    let mut backend = backend_with_macro_file("saved");
+    let path = std::env::temp_dir().join(format!("macro_ext_sheet_{}.json", std::process::id()));
+    let path = path.to_str().unwrap();
+    run(&mut backend, "record start local");
+    run(&mut backend, "A1=1");
+    run(&mut backend, "record stop");
+    run(&mut backend, "record start shared global");
+    run(&mut backend, "B1=2");
+    run(&mut backend, "record stop");
+    run(&mut backend, &format!("save {}", path));
+
+    let mut other = backend_with_macro_file("other");
+    other.macro_file = backend.macro_file.clone();
+    assert_eq!(run(&mut other, &format!("open {}", path)), Status::Opened);
+    assert_eq!(run(&mut other, "play local at A3"), Status::Success);
+    assert_eq!(run(&mut other, "play shared at B4"), Status::Success);
+    assert_eq!(value(&other, 3, 1), Some(1));
+    assert_eq!(value(&other, 4, 2), Some(2));
+    std::fs::remove_file(path).unwrap();
+
+    let macros = read_macro_file(backend.macro_file.as_ref().unwrap()).unwrap();
+    assert_eq!(macros["shared"], ["B1=2"]);
+    assert!(!macros.contains_key("local"));
+}
@@ -0,0 +1,157 @@
+use project::extension::backend::backend::*;
+use project::extension::backend::structure::*;
+use project::extension::common::{Operation, Value};
+use project::extension::parser::parser;
+
+fn run(backend: &mut Backend, cmd: &str) -> Status {
+    let rows = backend.get_grid().get_row_size() - 1;
+    let columns = backend.get_grid().get_column_size() - 1;
+    backend.process_command(rows, columns, cmd.to_string())
+}
+
+fn formula(backend: &Backend, row: usize, col: usize) -> Option<String> {
+    backend.get_grid().cells_vec[row][col].formula()
+}
+
+#[test]
+fn test_validate_move() {
+    assert_eq!(
+        parser::validate("move A1:C10 to E1", &10, &10),
+        Some((
+            Some(Value::Cell(1, 5)),
+            Some(Value::Oper(
+                Some(Box::new(Value::Cell(1, 1))),
+                Some(Box::new(Value::Cell(10, 3))),
+                Operation::Move,
+            ))
+        ))
+    );
+    assert!(parser::validate("move A1:C10 E1", &10, &10).is_none());
+}
+
+#[test]
+fn test_move_function() {
+    let block = (&Value::Cell(1, 1), &Value::Cell(2, 2));
+    let add = Value::Oper(
+        Some(Box::new(Value::Cell(2, 1))),
+        Some(Box::new(Value::Cell(1, 4))),
+        Operation::Add,
+    );
+    // A2 follows the block to D2, D1 is overwritten by it
+    assert_eq!(move_function(&add, block, (0, 3)).to_string(), "D2+#REF!");
+    let sum = Value::Oper(
+        Some(Box::new(Value::Cell(1, 1))),
+        Some(Box::new(Value::Cell(3, 1))),
+        Operation::Sum,
+    );
+    // a range that only overlaps the block is left alone
+    assert_eq!(move_function(&sum, block, (0, 3)).to_string(), "SUM(A1:A3)");
+}
+
+#[test]
+fn test_move_rewrites_references() {
+    let mut backend = Backend::init_backend(6, 6);
+    run(&mut backend, "A1=2");
+    run(&mut backend, "A2=A1*3");
+    run(&mut backend, "B1=A2+1");
+    assert_eq!(run(&mut backend, "move A1:A2 to E3"), Status::Success);
+
+    assert_eq!(formula(&backend, 4, 5), Some("E3*3".to_string()));
+    assert_eq!(formula(&backend, 1, 2), Some("E4+1".to_string()));
+    assert_eq!(backend.get_node_value(Value::Cell(1, 2)), Some(7));
+    assert_eq!(formula(&backend, 1, 1), None);
+    assert_eq!(backend.get_node_value(Value::Cell(1, 1)), Some(0));
+
+    // the graph follows the new cells, not the old ones
+    run(&mut backend, "E3=10");
+    assert_eq!(backend.get_node_value(Value::Cell(1, 2)), Some(31));
+    run(&mut backend, "A1=100");
+    assert_eq!(backend.get_node_value(Value::Cell(1, 2)), Some(31));
+}
+
+#[test]
+fn test_move_block_read_twice() {
+    let mut backend = Backend::init_backend(6, 6);
+    run(&mut backend, "A1=2");
+    run(&mut backend, "A2=A1*3");
+    // C1 reads A1 directly and through A2
+    run(&mut backend, "C1=SUM(A1:A2)");
+    assert_eq!(run(&mut backend, "move A1:A2 to E3"), Status::Success);
+
+    assert_eq!(formula(&backend, 1, 3), Some("SUM(E3:E4)".to_string()));
+    assert_eq!(backend.get_node_value(Value::Cell(1, 3)), Some(8));
+    run(&mut backend, "E3=10");
+    assert_eq!(backend.get_node_value(Value::Cell(1, 3)), Some(40));
+}
+
+#[test]
+fn test_move_rewrites_other_sheets() {
+    let mut backend = Backend::init_backend(6, 6);
+    run(&mut backend, "A1=2");
+    run(&mut backend, "A2=5");
+    run(&mut backend, "add_sheet Data");
+    run(&mut backend, "switch_sheet Data");
+    run(&mut backend, "A1=Sheet1!A2*10");
+    run(&mut backend, "B1=SUM(Sheet1!A1:A2)");
+    run(&mut backend, "C1=Sheet1!A3+1");
+    run(&mut backend, "switch_sheet Sheet1");
+    assert_eq!(run(&mut backend, "move A1:A2 to C2"), Status::Success);
+
+    run(&mut backend, "switch_sheet Data");
+    assert_eq!(formula(&backend, 1, 1), Some("Sheet1!C3*10".to_string()));
+    assert_eq!(
+        formula(&backend, 1, 2),
+        Some("SUM(Sheet1!C2:C3)".to_string())
+    );
+    // a cell outside the block keeps its reference
+    assert_eq!(formula(&backend, 1, 3), Some("Sheet1!A3+1".to_string()));
+    assert_eq!(backend.get_node_value(Value::Cell(1, 1)), Some(50));
+
+    // and they recalculate from the cells the block moved to
+    run(&mut backend, "switch_sheet Sheet1");
+    run(&mut backend, "C3=7");
+    run(&mut backend, "switch_sheet Data");
+    assert_eq!(backend.get_node_value(Value::Cell(1, 1)), Some(70));
+    assert_eq!(backend.get_node_value(Value::Cell(1, 2)), Some(9));
+}
+
+#[test]
+fn test_move_overwrites_and_overlaps() {
+    let mut backend = Backend::init_backend(5, 3);
+    run(&mut backend, "A1=1");
+    run(&mut backend, "A2=2");
+    run(&mut backend, "A3=4");
+    run(&mut backend, "B1=A3+0");
+    run(&mut backend, "B2=A1+A2");
+    // shifting the block down by one overlaps itself and overwrites A3
+    assert_eq!(run(&mut backend, "move A1:A2 to A2"), Status::Success);
+    assert_eq!(backend.get_node_value(Value::Cell(2, 1)), Some(1));
+    assert_eq!(backend.get_node_value(Value::Cell(3, 1)), Some(2));
+    assert_eq!(formula(&backend, 1, 2), Some("#REF!+0".to_string()));
+    assert_eq!(backend.get_node_value(Value::Cell(1, 2)), None);
+    assert_eq!(formula(&backend, 2, 2), Some("A2+A3".to_string()));
+    assert_eq!(backend.get_node_value(Value::Cell(2, 2)), Some(3));
+
+    assert_eq!(run(&mut backend, "undo"), Status::Success);
+    assert_eq!(formula(&backend, 1, 2), Some("A3+0".to_string()));
+    assert_eq!(backend.get_node_value(Value::Cell(1, 2)), Some(4));
+}
+
+#[test]
+fn test_move_rejects_cycles_and_overflow() {
+    let mut backend = Backend::init_backend(5, 3);
+    run(&mut backend, "A1=SUM(B1:B5)");
+    assert!(matches!(
+        run(&mut backend, "move A1 to C5"),
+        Status::Success
+    ));
+    assert!(matches!(
+        run(&mut backend, "move C5:C5 to B3"),
+        Status::CircularDependency
+    ));
+    assert_eq!(formula(&backend, 5, 3), Some("SUM(B1:B5)".to_string()));
+    assert!(matches!(
+        run(&mut backend, "move A1:B2 to C1"),
+        Status::Error(_)
+    ));
+}
@@ -0,0 +1,178 @@
+use project::extension::backend::backend::*;
+use project::extension::common::{Operation, Value};
+use project::extension::parser::parser;
+
+fn run(backend: &mut Backend, cmd: &str) -> Status {
+    let rows = backend.get_grid().get_row_size() - 1;
+    let columns = backend.get_grid().get_column_size() - 1;
+    backend.process_command(rows, columns, cmd.to_string())
+}
+
+fn formula(backend: &Backend, row: usize, col: usize) -> Option<String> {
+    backend.get_grid().cells_vec[row][col].formula()
+}
+
+fn value(backend: &Backend, row: usize, col: usize) -> Option<isize> {
+    backend.get_node_value(Value::Cell(row, col))
+}
+
+/// Sheet with 1..=5 in B1:B5, named Revenue, and TaxRate = 18
+fn named_sheet() -> Backend {
+    let mut backend = Backend::init_backend(6, 6);
+    for row in 1..=5 {
+        assert_eq!(
+            run(&mut backend, &format!("B{}={}", row, row)),
+            Status::Success
+        );
+    }
+    assert_eq!(run(&mut backend, "define Revenue = B1:B5"), Status::Success);
+    assert_eq!(run(&mut backend, "define TaxRate = 18"), Status::Success);
+    backend
+}
+
+#[test]
+fn test_validate_define() {
+    assert_eq!(
+        parser::validate("define Revenue = B2:B5", &10, &10),
+        Some((
+            None,
+            Some(Value::Oper(
+                Some(Box::new(Value::Cell(2, 2))),
+                Some(Box::new(Value::Cell(5, 2))),
+                Operation::Define("Revenue".to_string()),
+            ))
+        ))
+    );
+    assert_eq!(
+        parser::validate("define TaxRate = -18", &10, &10),
+        Some((
+            None,
+            Some(Value::Oper(
+                Some(Box::new(Value::Const(-18))),
+                None,
+                Operation::Define("TaxRate".to_string()),
+            ))
+        ))
+    );
+    assert_eq!(
+        parser::validate("C1=SUM(Revenue)", &10, &10),
+        Some((
+            Some(Value::Cell(1, 3)),
+            Some(Value::Oper(
+                Some(Box::new(Value::Name("Revenue".to_string()))),
+                Some(Box::new(Value::Name("Revenue".to_string()))),
+                Operation::Sum,
+            ))
+        ))
+    );
+    // names must not read as cells or functions
+    assert!(parser::validate("define AB12 = 3", &10, &10).is_none());
+    assert!(parser::validate("define SUM = 3", &10, &10).is_none());
+    assert!(parser::validate("define 1st = 3", &10, &10).is_none());
+}
+
+#[test]
+fn test_names_in_formulas() {
+    let mut backend = named_sheet();
+    assert_eq!(run(&mut backend, "C1=SUM(Revenue)"), Status::Success);
+    assert_eq!(run(&mut backend, "C2=C1*TaxRate"), Status::Success);
+    assert_eq!(run(&mut backend, "C3=TaxRate"), Status::Success);
+    assert_eq!(value(&backend, 1, 3), Some(15));
+    assert_eq!(value(&backend, 2, 3), Some(270));
+    assert_eq!(value(&backend, 3, 3), Some(18));
+    assert_eq!(formula(&backend, 1, 3), Some("SUM(Revenue)".to_string()));
+    assert_eq!(formula(&backend, 2, 3), Some("C1*TaxRate".to_string()));
+
+    // the named range is tracked like the cells it stands for
+    run(&mut backend, "B5=10");
+    assert_eq!(value(&backend, 1, 3), Some(20));
+    assert_eq!(value(&backend, 2, 3), Some(360));
+}
+
+#[test]
+fn test_name_errors() {
+    let mut backend = named_sheet();
+    assert_eq!(
+        run(&mut backend, "C1=SUM(Costs)"),
+        Status::Error("Unknown name Costs".to_string())
+    );
+    assert_eq!(
+        run(&mut backend, "C1=SUM(TaxRate)"),
+        Status::Error("TaxRate is not a range".to_string())
+    );
+    assert_eq!(
+        run(&mut backend, "C1=Revenue+1"),
+        Status::Error("Revenue is a range, not a single value".to_string())
+    );
+}
+
+#[test]
+fn test_redefine_reevaluates() {
+    let mut backend = named_sheet();
+    run(&mut backend, "C1=SUM(Revenue)");
+    run(&mut backend, "C2=C1*TaxRate");
+    assert_eq!(run(&mut backend, "define TaxRate = 2"), Status::Success);
+    assert_eq!(value(&backend, 2, 3), Some(30));
+    assert_eq!(run(&mut backend, "define Revenue = B1:B2"), Status::Success);
+    assert_eq!(value(&backend, 1, 3), Some(3));
+    assert_eq!(value(&backend, 2, 3), Some(6));
+    // edges follow the new definition
+    run(&mut backend, "B5=100");
+    assert_eq!(value(&backend, 1, 3), Some(3));
+    run(&mut backend, "B1=11");
+    assert_eq!(value(&backend, 1, 3), Some(13));
+
+    assert_eq!(run(&mut backend, "undo"), Status::Success);
+    assert_eq!(run(&mut backend, "undo"), Status::Success);
+    assert_eq!(run(&mut backend, "undo"), Status::Success);
+    assert_eq!(value(&backend, 1, 3), Some(15));
+    assert_eq!(value(&backend, 2, 3), Some(30));
+}
+
+#[test]
+fn test_redefine_cycle_and_mismatch() {
+    let mut backend = named_sheet();
+    run(&mut backend, "C1=SUM(Revenue)");
+    assert_eq!(
+        run(&mut backend, "define Revenue = A1:C1"),
+        Status::CircularDependency
+    );
+    assert_eq!(
+        run(&mut backend, "define Revenue = 4"),
+        Status::Error("Revenue is not a range in C1".to_string())
+    );
+    // both failures leave the old definition in place
+    assert_eq!(value(&backend, 1, 3), Some(15));
+    assert_eq!(
+        run(&mut backend, "names"),
+        Status::Report("Revenue = Sheet1!B1:B5\nTaxRate = 18".to_string())
+    );
+}
+
+#[test]
+fn test_names_follow_edits() {
+    let mut backend = named_sheet();
+    run(&mut backend, "C1=SUM(Revenue)");
+    assert_eq!(run(&mut backend, "insert_row 1"), Status::Success);
+    assert_eq!(
+        run(&mut backend, "names"),
+        Status::Report("Revenue = Sheet1!B2:B6\nTaxRate = 18".to_string())
+    );
+    // a copy keeps reading the named range
+    assert_eq!(run(&mut backend, "copy C2 to D3"), Status::Success);
+    assert_eq!(formula(&backend, 3, 4), Some("SUM(Revenue)".to_string()));
+    assert_eq!(value(&backend, 3, 4), Some(15));
+}
+
+#[test]
+fn test_names_round_trip() {
+    let mut backend = named_sheet();
+    run(&mut backend, "C1=SUM(Revenue)");
+    let temp_file = "test_names_round_trip.json";
+    assert!(backend.serial(temp_file).is_ok());
+    let mut loaded = Backend::deserial(temp_file).unwrap();
+    std::fs::remove_file(temp_file).unwrap();
+    assert_eq!(formula(&loaded, 1, 3), Some("SUM(Revenue)".to_string()));
+    assert_eq!(run(&mut loaded, "define Revenue = B1:B3"), Status::Success);
+    assert_eq!(value(&loaded, 1, 3), Some(6));
+}
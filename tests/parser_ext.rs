@@ -294,3 +294,38 @@ fn test_validate_invalid_commands() {
     let expected_value = Some((Some(Value::Cell(1, 1)), None));
     assert_eq!(result, expected_value);
 }
+
+#[test]
+fn test_validate_open_command() {
+    let rows = 10;
+    let cols = 10;
+
+    let result = parser::validate("open sheet.json", &cols, &rows);
+    assert_eq!(
+        result,
+        Some((
+            None,
+            Some(Value::Oper(
+                None,
+                None,
+                Operation::Open("sheet.json".to_string())
+            ))
+        ))
+    );
+
+    let result = parser::validate("load data.csv", &cols, &rows);
+    assert_eq!(
+        result,
+        Some((
+            None,
+            Some(Value::Oper(
+                None,
+                None,
+                Operation::Open("data.csv".to_string())
+            ))
+        ))
+    );
+
+    // A path is required
+    assert!(parser::validate("open ", &cols, &rows).is_none());
+}
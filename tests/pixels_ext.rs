@@ -0,0 +1,109 @@
+use project::extension::backend::backend::*;
+use project::extension::backend::pixels::*;
+use project::extension::common::{Operation, Value, number_to_rgb, rgb_to_number};
+use project::extension::parser::parser;
+use std::fs;
+
+#[test]
+fn test_rgb_round_trip() {
+    assert_eq!(number_to_rgb(0xFF8000), (255, 128, 0));
+    assert_eq!(rgb_to_number(255, 128, 0), 0xFF8000);
+    assert_eq!(rgb_to_number(1, 2, 3), 0x010203);
+}
+
+#[test]
+fn test_validate_image_commands() {
+    assert_eq!(
+        parser::validate("export_img A1:B2 out.png 4", &5, &5),
+        Some((
+            None,
+            Some(Value::Oper(
+                Some(Box::new(Value::Cell(1, 1))),
+                Some(Box::new(Value::Cell(2, 2))),
+                Operation::ExportImage("out.png".to_string(), 4),
+            ))
+        ))
+    );
+    assert_eq!(
+        parser::validate("import_img in.ppm C3", &5, &5),
+        Some((
+            Some(Value::Cell(3, 3)),
+            Some(Value::Oper(
+                None,
+                None,
+                Operation::ImportImage("in.ppm".to_string())
+            ))
+        ))
+    );
+    assert!(parser::validate("export_img A1:B2 out.png 0", &5, &5).is_none());
+    assert!(parser::validate("export_img A1:B2", &5, &5).is_none());
+    assert!(parser::validate("import_img in.png", &5, &5).is_none());
+}
+
+#[test]
+fn test_export_then_import_image() {
+    let mut backend = Backend::init_backend(5, 5);
+    backend.process_command(5, 5, "A1=16711680".to_string()); // red
+    backend.process_command(5, 5, "B1=65280".to_string()); // green
+    backend.process_command(5, 5, "A2=255".to_string()); // blue
+    backend.process_command(5, 5, "B2=-4".to_string()); // not a colour, drawn white
+
+    let path = "test_export_image.png";
+    let status = backend.process_command(5, 5, format!("export_img A1:B2 {} 3", path));
+    assert_eq!(status, Status::Success);
+
+    let pixels = read_image(path).unwrap();
+    assert_eq!(pixels.len(), 6);
+    assert_eq!(pixels[0].len(), 6);
+    assert_eq!(pixels[2][2], 0xFF0000);
+    assert_eq!(pixels[0][5], 0x00FF00);
+    assert_eq!(pixels[5][0], 0x0000FF);
+    assert_eq!(pixels[5][5], 0xFFFFFF);
+
+    // Importing puts every pixel in its own cell, as a single undo step
+    let status = backend.process_command(5, 5, format!("import_img {} A1", path));
+    assert_eq!(
+        status,
+        Status::Error("6x6 block does not fit in the sheet at A1".to_string())
+    );
+    let mut big = Backend::init_backend(8, 8);
+    let status = big.process_command(8, 8, format!("import_img {} B2", path));
+    assert_eq!(status, Status::Success);
+    assert_eq!(big.get_node_value(Value::Cell(2, 2)), Some(0xFF0000));
+    assert_eq!(big.get_node_value(Value::Cell(7, 7)), Some(0xFFFFFF));
+    big.process_command(8, 8, "undo".to_string());
+    assert_eq!(big.get_node_value(Value::Cell(2, 2)), Some(0));
+
+    let _ = fs::remove_file(path);
+}
+
+#[test]
+fn test_export_ppm_and_errors() {
+    let mut backend = Backend::init_backend(2, 2);
+    backend.process_command(2, 2, "A1=1193046".to_string()); // 0x123456
+    let path = "test_export_image.ppm";
+    assert_eq!(
+        export_image(
+            backend.get_grid(),
+            &Value::Cell(1, 1),
+            &Value::Cell(1, 1),
+            path,
+            1
+        ),
+        Ok(())
+    );
+    assert_eq!(read_image(path), Ok(vec![vec![0x123456]]));
+    let _ = fs::remove_file(path);
+
+    assert!(
+        export_image(
+            backend.get_grid(),
+            &Value::Cell(1, 1),
+            &Value::Cell(1, 1),
+            "x.bmp",
+            1
+        )
+        .is_err()
+    );
+    assert!(read_image("missing.png").is_err());
+}
@@ -0,0 +1,129 @@
+use project::extension::backend::backend::*;
+use project::extension::backend::plot::*;
+use project::extension::common::{ChartKind, Operation, Value};
+use project::extension::parser::parser;
+
+fn run(backend: &mut Backend, cmd: &str) -> Status {
+    let rows = backend.get_grid().get_row_size() - 1;
+    let columns = backend.get_grid().get_column_size() - 1;
+    backend.process_command(rows, columns, cmd.to_string())
+}
+
+fn report(status: Status) -> String {
+    let Status::Report(text) = status else {
+        panic!("expected a report, got {:?}", status);
+    };
+    text
+}
+
+#[test]
+fn test_validate_plot_and_spark() {
+    let command = |operation| {
+        Some((
+            None,
+            Some(Value::Oper(
+                Some(Box::new(Value::Cell(1, 1))),
+                Some(Box::new(Value::Cell(30, 1))),
+                operation,
+            )),
+        ))
+    };
+    assert_eq!(
+        parser::validate("plot A1:A30", &5, &30),
+        command(Operation::Plot(ChartKind::Bar))
+    );
+    assert_eq!(
+        parser::validate("plot A1:A30 line", &5, &30),
+        command(Operation::Plot(ChartKind::Line))
+    );
+    assert_eq!(
+        parser::validate("spark A1:A30", &5, &30),
+        command(Operation::Spark)
+    );
+    assert!(parser::validate("plot A1:A30 scatter", &5, &30).is_none());
+    assert!(parser::validate("plot A1:A31", &5, &30).is_none());
+    assert!(parser::validate("spark A1", &5, &30).is_none());
+}
+
+#[test]
+fn test_sparkline() {
+    let values = [Some(1), Some(5), None, Some(8), Some(3)];
+    assert_eq!(sparkline(&values), "▁▅ █▃ min 1, max 8, last 3");
+    assert_eq!(sparkline(&[Some(4), Some(4)]), "▁▁ min 4, max 4, last 4");
+    assert_eq!(
+        sparkline(&[Some(-2), Some(2), None]),
+        "▁█  min -2, max 2, last 2"
+    );
+}
+
+#[test]
+fn test_bar_plot() {
+    let values = [Some(10), Some(5), Some(10)];
+    let mut expected = vec!["10 ┤█ █"];
+    expected.extend(["   │█ █"; 4]);
+    expected.extend(["   │███"; 4]);
+    expected.extend([" 0 ┤███", "   └───", "    A1"]);
+    assert_eq!(
+        plot(
+            &values,
+            &ChartKind::Bar,
+            &Value::Cell(1, 1),
+            &Value::Cell(3, 1)
+        ),
+        expected.join("\n")
+    );
+
+    // zero lies on a row edge, bars below it grow down
+    let text = plot(
+        &[Some(-3), Some(4), Some(-1), Some(2)],
+        &ChartKind::Bar,
+        &Value::Cell(1, 1),
+        &Value::Cell(1, 4),
+    );
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), PLOT_HEIGHT + 2);
+    assert_eq!(lines[5], " 0 ┤ █ █");
+    assert_eq!(lines[6], "   │█ █");
+    assert_eq!(lines[9], "-3 ┤█");
+}
+
+#[test]
+fn test_line_plot() {
+    let values = [Some(0), Some(9), None, Some(4), Some(5)];
+    let text = plot(
+        &values,
+        &ChartKind::Line,
+        &Value::Cell(1, 1),
+        &Value::Cell(1, 5),
+    );
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[0], "9 ┤ •");
+    // the rise from A1 is joined, the gap after it breaks the line
+    assert_eq!(lines[4], "  │ │  •");
+    assert_eq!(lines[5], "  │ │ •");
+    assert_eq!(lines[9], "0 ┤•");
+    assert_eq!(lines[11], "   A1 E1");
+}
+
+#[test]
+fn test_plot_commands() {
+    let mut backend = Backend::init_backend(10, 3);
+    for cmd in ["A1=2", "A2=7", "A3=A2/0", "A5=4"] {
+        run(&mut backend, cmd);
+    }
+    assert_eq!(
+        report(run(&mut backend, "spark A1:A5")),
+        "▁█  ▄ min 2, max 7, last 4"
+    );
+    let text = report(run(&mut backend, "plot A1:A5"));
+    assert!(text.starts_with("7 ┤ █"));
+    assert!(text.ends_with("\n   A1 A5"));
+    assert_eq!(
+        run(&mut backend, "plot A1:B5"),
+        Status::Error("Plot range must be one row or one column".to_string())
+    );
+    assert_eq!(
+        run(&mut backend, "spark C1:C9"),
+        Status::Error("Plot range has no values".to_string())
+    );
+}
@@ -0,0 +1,93 @@
+use project::extension::backend::backend::*;
+use project::extension::common::{MAX_ROWS, Operation, Value};
+use project::extension::parser::parser;
+
+fn run(backend: &mut Backend, cmd: &str) -> Status {
+    let rows = backend.get_grid().get_row_size() - 1;
+    let columns = backend.get_grid().get_column_size() - 1;
+    backend.process_command(rows, columns, cmd.to_string())
+}
+
+fn size(backend: &Backend) -> (usize, usize) {
+    (
+        backend.get_grid().get_row_size() - 1,
+        backend.get_grid().get_column_size() - 1,
+    )
+}
+
+fn value(backend: &Backend, row: usize, col: usize) -> Option<isize> {
+    backend.get_node_value(Value::Cell(row, col))
+}
+
+#[test]
+fn test_validate_resize() {
+    assert_eq!(
+        parser::validate("resize 20 8", &10, &10),
+        Some((
+            None,
+            Some(Value::Oper(None, None, Operation::Resize(20, 8)))
+        ))
+    );
+    assert!(parser::validate("resize 20", &10, &10).is_none());
+    assert!(parser::validate("resize 20 x", &10, &10).is_none());
+}
+
+#[test]
+fn test_grow_and_use_new_cells() {
+    let mut backend = Backend::init_backend(3, 3);
+    run(&mut backend, "A1=2");
+    run(&mut backend, "B3=MIN(A:A)");
+    assert_eq!(run(&mut backend, "resize 6 5"), Status::Success);
+    assert_eq!(size(&backend), (6, 5));
+    // the new cells are addressable right away
+    assert_eq!(run(&mut backend, "E6=A1*10"), Status::Success);
+    assert_eq!(value(&backend, 6, 5), Some(20));
+    run(&mut backend, "A6=-4");
+    assert_eq!(value(&backend, 3, 2), Some(-4));
+}
+
+#[test]
+fn test_shrink_refuses_referenced_cells() {
+    let mut backend = Backend::init_backend(5, 5);
+    run(&mut backend, "A1=E5+1");
+    assert_eq!(
+        run(&mut backend, "resize 4 4"),
+        Status::Error("Cannot resize: A1 reads E5".to_string())
+    );
+    run(&mut backend, "B1=SUM(E:E)");
+    run(&mut backend, "A1=1");
+    assert_eq!(
+        run(&mut backend, "resize 5 4"),
+        Status::Error("Cannot resize: B1 reads E:E".to_string())
+    );
+    assert_eq!(size(&backend), (5, 5));
+}
+
+#[test]
+fn test_shrink_drops_unreferenced_cells() {
+    let mut backend = Backend::init_backend(5, 5);
+    run(&mut backend, "A1=SUM(A:A)");
+    run(&mut backend, "B1=SUM(A:A)");
+    run(&mut backend, "A5=7");
+    // formulas on the dropped cells go with them
+    run(&mut backend, "E5=A1+1");
+    run(&mut backend, "A4=3");
+    assert_eq!(value(&backend, 1, 2), Some(10));
+    assert_eq!(run(&mut backend, "resize 4 4"), Status::Success);
+    assert_eq!(size(&backend), (4, 4));
+    assert_eq!(value(&backend, 1, 2), Some(3));
+
+    assert_eq!(run(&mut backend, "undo"), Status::Success);
+    assert_eq!(size(&backend), (5, 5));
+    assert_eq!(value(&backend, 1, 2), Some(10));
+}
+
+#[test]
+fn test_resize_bounds() {
+    let mut backend = Backend::init_backend(5, 5);
+    assert!(matches!(run(&mut backend, "resize 0 5"), Status::Error(_)));
+    assert!(matches!(
+        run(&mut backend, &format!("resize {} 5", MAX_ROWS + 1)),
+        Status::Error(_)
+    ));
+}
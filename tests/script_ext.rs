@@ -0,0 +1,116 @@
+use project::extension::backend::backend::*;
+use project::extension::frontend::script::*;
+use project::script::*;
+
+fn args(list: &[&str]) -> Vec<String> {
+    list.iter().map(|s| s.to_string()).collect()
+}
+
+fn options(format: OutputFormat, keep_going: bool, ranges: &[&str]) -> ScriptOptions {
+    ScriptOptions {
+        path: "script.txt".to_string(),
+        keep_going,
+        format,
+        ranges: args(ranges),
+    }
+}
+
+#[test]
+fn test_split_script_args() {
+    let (positional, script) = split_script_args(&args(&["ext", "5", "5"])).unwrap();
+    assert_eq!(positional, args(&["ext", "5", "5"]));
+    assert!(script.is_none());
+
+    let (positional, script) = split_script_args(&args(&[
+        "ext",
+        "5",
+        "--script",
+        "cmds.txt",
+        "5",
+        "--format",
+        "json",
+        "--range",
+        "A1:B2",
+        "--keep-going",
+    ]))
+    .unwrap();
+    assert_eq!(positional, args(&["ext", "5", "5"]));
+    let script = script.unwrap();
+    assert_eq!(script.path, "cmds.txt");
+    assert_eq!(script.format, OutputFormat::Json);
+    assert_eq!(script.ranges, args(&["A1:B2"]));
+    assert!(script.keep_going);
+
+    assert!(split_script_args(&args(&["ext", "--format", "xml"])).is_err());
+    assert!(split_script_args(&args(&["ext", "--script"])).is_err());
+}
+
+#[test]
+fn test_run_script_csv() {
+    let mut backend = Backend::init_backend(2, 3);
+    let script = "A1=4\n# comment\n\nB1=A1*2\nC2=SUM(A1:B1)\nA2=1/0\n";
+    let output = run_script(
+        &mut backend,
+        script,
+        &options(OutputFormat::Csv, false, &[]),
+    )
+    .unwrap();
+    assert_eq!(output, "4,8,\nERR,,12\n");
+}
+
+#[test]
+fn test_run_script_json_ranges() {
+    let mut backend = Backend::init_backend(3, 3);
+    let script = "A1=1\nB2=A1+1\nC3=B2+1\n";
+    let output = run_script(
+        &mut backend,
+        script,
+        &options(OutputFormat::Json, false, &["A1:B2", "C3:C3"]),
+    )
+    .unwrap();
+    assert_eq!(output, "{\"A1:B2\":[[1,null],[null,2]],\"C3:C3\":[[3]]}\n");
+}
+
+#[test]
+fn test_run_script_errors() {
+    let script = "A1=1\nbogus\nA2=A1+1\n";
+
+    let mut backend = Backend::init_backend(3, 3);
+    let result = run_script(
+        &mut backend,
+        script,
+        &options(OutputFormat::Csv, false, &["A1:A2"]),
+    );
+    assert_eq!(
+        result,
+        Err("line 2: bogus: unknown command bogus, type help for a list".to_string())
+    );
+
+    // formulas the parser rejects are explained as at the prompt
+    let mut backend = Backend::init_backend(3, 3);
+    let result = run_script(
+        &mut backend,
+        "A1=1\nC1=FOO(A1:B1)\n",
+        &options(OutputFormat::Csv, false, &[]),
+    );
+    assert_eq!(
+        result,
+        Err("line 2: C1=FOO(A1:B1): unknown function FOO at col 4".to_string())
+    );
+
+    let mut backend = Backend::init_backend(3, 3);
+    let result = run_script(
+        &mut backend,
+        script,
+        &options(OutputFormat::Csv, true, &["A1:A2"]),
+    );
+    assert_eq!(result, Ok("1\n2\n".to_string()));
+
+    let mut backend = Backend::init_backend(3, 3);
+    let result = run_script(
+        &mut backend,
+        "",
+        &options(OutputFormat::Csv, true, &["A1:Z9"]),
+    );
+    assert_eq!(result, Err("invalid range: A1:Z9".to_string()));
+}
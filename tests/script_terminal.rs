@@ -0,0 +1,81 @@
+use project::script::*;
+use project::terminal::backend::generate_grid;
+use project::terminal::script::*;
+use project::terminal::types::Coordinates;
+
+fn options(format: OutputFormat, keep_going: bool, ranges: &[&str]) -> ScriptOptions {
+    ScriptOptions {
+        path: "script.txt".to_string(),
+        keep_going,
+        format,
+        ranges: ranges.iter().map(|s| s.to_string()).collect(),
+    }
+}
+
+#[test]
+fn test_parse_range() {
+    assert_eq!(
+        parse_range("B2:C3", 5, 5),
+        Some((
+            Coordinates { row: 2, col: 2 },
+            Coordinates { row: 3, col: 3 }
+        ))
+    );
+    assert_eq!(parse_range("C3:B2", 5, 5), None);
+    assert_eq!(parse_range("A1:F1", 5, 5), None);
+    assert_eq!(parse_range("A:B", 5, 5), None);
+    assert_eq!(parse_range("A1", 5, 5), None);
+}
+
+#[test]
+fn test_run_script_csv() {
+    let mut grid = generate_grid(2, 2);
+    let script = "A1=3\nscroll_to B2\nB2=A1*A1\nA2=5/0\n";
+    let output = run_script(
+        script,
+        2,
+        2,
+        &mut grid,
+        &options(OutputFormat::Csv, false, &[]),
+    );
+    assert_eq!(output, Ok("3,\nERR,9\n".to_string()));
+}
+
+#[test]
+fn test_run_script_json() {
+    let mut grid = generate_grid(3, 3);
+    let script = "A1=2\nA2=A1+1\nq\nA3=7\n";
+    let output = run_script(
+        script,
+        3,
+        3,
+        &mut grid,
+        &options(OutputFormat::Json, false, &["A1:A3"]),
+    );
+    assert_eq!(output, Ok("{\"A1:A3\":[[2],[3],[null]]}\n".to_string()));
+}
+
+#[test]
+fn test_run_script_errors() {
+    let script = "A1=B1\nB1=A1\nC1=1\n";
+
+    let mut grid = generate_grid(2, 3);
+    let output = run_script(
+        script,
+        2,
+        3,
+        &mut grid,
+        &options(OutputFormat::Csv, false, &[]),
+    );
+    assert_eq!(output, Err("line 2: B1=A1: cycle not allowed".to_string()));
+
+    let mut grid = generate_grid(2, 3);
+    let output = run_script(
+        script,
+        2,
+        3,
+        &mut grid,
+        &options(OutputFormat::Csv, true, &["C1:C1"]),
+    );
+    assert_eq!(output, Ok("1\n".to_string()));
+}
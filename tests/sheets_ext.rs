@@ -0,0 +1,214 @@
+use project::extension::backend::backend::*;
+use project::extension::common::{Operation, Value};
+use project::extension::parser::parser;
+
+fn run(backend: &mut Backend, cmd: &str) -> Status {
+    let rows = backend.get_grid().get_row_size() - 1;
+    let columns = backend.get_grid().get_column_size() - 1;
+    backend.process_command(rows, columns, cmd.to_string())
+}
+
+fn formula(backend: &Backend, row: usize, col: usize) -> Option<String> {
+    backend.get_grid().cells_vec[row][col].formula()
+}
+
+fn value(backend: &Backend, cell: &str) -> Option<isize> {
+    let rows = backend.get_grid().get_row_size() - 1;
+    let columns = backend.get_grid().get_column_size() - 1;
+    let (cell, _) = parser::parse_range(&format!("{}:{}", cell, cell), &rows, &columns).unwrap();
+    backend.get_node_value(cell)
+}
+
+/// Workbook with sheets Sheet1 and Data, where Data!A1:A3 holds 1, 2, 3
+fn workbook() -> Backend {
+    let mut backend = Backend::init_backend(5, 5);
+    assert_eq!(run(&mut backend, "add_sheet Data"), Status::Success);
+    assert_eq!(
+        run(&mut backend, "switch_sheet Data"),
+        Status::SwitchedSheet
+    );
+    for cmd in ["A1=1", "A2=2", "A3=3"] {
+        assert_eq!(run(&mut backend, cmd), Status::Success);
+    }
+    assert_eq!(
+        run(&mut backend, "switch_sheet Sheet1"),
+        Status::SwitchedSheet
+    );
+    backend
+}
+
+#[test]
+fn test_validate_sheet_references() {
+    assert_eq!(
+        parser::validate("A1=Data!B3", &10, &10),
+        Some((
+            Some(Value::Cell(1, 1)),
+            Some(Value::Oper(
+                Some(Box::new(Value::SheetRef(
+                    "Data".to_string(),
+                    Box::new(Value::Cell(3, 2))
+                ))),
+                Some(Box::new(Value::Const(0))),
+                Operation::Cons,
+            ))
+        ))
+    );
+    assert_eq!(
+        parser::validate("A1=SUM(Data!A1:A100)", &10, &10),
+        Some((
+            Some(Value::Cell(1, 1)),
+            Some(Value::Oper(
+                Some(Box::new(Value::SheetRef(
+                    "Data".to_string(),
+                    Box::new(Value::Cell(1, 1))
+                ))),
+                Some(Box::new(Value::SheetRef(
+                    "Data".to_string(),
+                    Box::new(Value::Cell(100, 1))
+                ))),
+                Operation::Sum,
+            ))
+        ))
+    );
+    assert!(parser::validate("A1=1x!B3", &10, &10).is_none());
+    assert!(parser::validate("rename_sheet Data", &10, &10).is_none());
+}
+
+#[test]
+fn test_cross_sheet_values_propagate() {
+    let mut backend = workbook();
+    assert_eq!(run(&mut backend, "B1=SUM(Data!A1:A3)"), Status::Success);
+    assert_eq!(run(&mut backend, "B2=Data!A2*10"), Status::Success);
+    assert_eq!(value(&backend, "B1"), Some(6));
+    assert_eq!(value(&backend, "B2"), Some(20));
+    assert_eq!(formula(&backend, 1, 2), Some("SUM(Data!A1:A3)".to_string()));
+
+    run(&mut backend, "switch_sheet Data");
+    assert_eq!(run(&mut backend, "A2=5"), Status::Success);
+    run(&mut backend, "switch_sheet Sheet1");
+    assert_eq!(value(&backend, "B1"), Some(9));
+    assert_eq!(value(&backend, "B2"), Some(50));
+}
+
+#[test]
+fn test_unknown_sheet_and_bounds() {
+    let mut backend = workbook();
+    assert_eq!(
+        run(&mut backend, "B1=Nope!A1"),
+        Status::Error("Unknown sheet Nope".to_string())
+    );
+    assert_eq!(
+        run(&mut backend, "B1=Data!A9"),
+        Status::Error("Data!A9 is outside sheet Data".to_string())
+    );
+    // a reference to the active sheet is stored as a plain reference
+    assert_eq!(run(&mut backend, "B1=Sheet1!A1+1"), Status::Success);
+    assert_eq!(formula(&backend, 1, 2), Some("A1+1".to_string()));
+}
+
+#[test]
+fn test_cycle_across_sheets() {
+    let mut backend = workbook();
+    assert_eq!(run(&mut backend, "A1=Data!B1+1"), Status::Success);
+    run(&mut backend, "switch_sheet Data");
+    assert_eq!(
+        run(&mut backend, "B1=Sheet1!A1*2"),
+        Status::CircularDependency
+    );
+    assert_eq!(run(&mut backend, "B1=7"), Status::Success);
+    run(&mut backend, "switch_sheet Sheet1");
+    assert_eq!(value(&backend, "A1"), Some(8));
+}
+
+#[test]
+fn test_rename_and_delete_sheet() {
+    let mut backend = workbook();
+    run(&mut backend, "B1=SUM(Data!A1:A3)");
+    assert_eq!(
+        run(&mut backend, "rename_sheet Data Input"),
+        Status::Success
+    );
+    assert_eq!(
+        formula(&backend, 1, 2),
+        Some("SUM(Input!A1:A3)".to_string())
+    );
+    assert_eq!(backend.sheet_names(), vec!["Sheet1", "Input"]);
+
+    // renamed references still propagate
+    run(&mut backend, "switch_sheet Input");
+    run(&mut backend, "A1=10");
+    run(&mut backend, "switch_sheet Sheet1");
+    assert_eq!(value(&backend, "B1"), Some(15));
+
+    assert_eq!(run(&mut backend, "delete_sheet Input"), Status::Success);
+    assert_eq!(value(&backend, "B1"), None);
+    assert_eq!(
+        run(&mut backend, "delete_sheet Sheet1"),
+        Status::Error("Cannot delete the only sheet".to_string())
+    );
+
+    assert_eq!(run(&mut backend, "undo"), Status::Success);
+    assert_eq!(backend.sheet_names(), vec!["Sheet1", "Input"]);
+    assert_eq!(value(&backend, "B1"), Some(15));
+}
+
+#[test]
+fn test_list_sheets() {
+    let mut backend = workbook();
+    assert_eq!(
+        run(&mut backend, "sheets"),
+        Status::Report("* Sheet1\n  Data".to_string())
+    );
+    assert_eq!(
+        run(&mut backend, "add_sheet Data"),
+        Status::Error("Sheet Data already exists".to_string())
+    );
+}
+
+#[test]
+fn test_insert_row_shifts_other_sheets() {
+    let mut backend = workbook();
+    run(&mut backend, "B1=Data!A3");
+    run(&mut backend, "switch_sheet Data");
+    assert_eq!(run(&mut backend, "insert_row 1"), Status::Success);
+    run(&mut backend, "switch_sheet Sheet1");
+    assert_eq!(formula(&backend, 1, 2), Some("Data!A4".to_string()));
+    assert_eq!(value(&backend, "B1"), Some(3));
+}
+
+#[test]
+fn test_sheets_round_trip() {
+    let mut backend = workbook();
+    run(&mut backend, "B1=SUM(Data!A1:A3)");
+    let temp_file = "test_sheets_round_trip.json";
+    assert!(backend.serial(temp_file).is_ok());
+    let mut loaded = Backend::deserial(temp_file).unwrap();
+    std::fs::remove_file(temp_file).unwrap();
+
+    assert_eq!(loaded.sheet_names(), vec!["Sheet1", "Data"]);
+    assert_eq!(value(&loaded, "B1"), Some(6));
+    run(&mut loaded, "switch_sheet Data");
+    run(&mut loaded, "A3=30");
+    run(&mut loaded, "switch_sheet Sheet1");
+    assert_eq!(value(&loaded, "B1"), Some(33));
+}
+
+#[test]
+fn test_single_grid_file_loads() {
+    let mut backend = Backend::init_backend(3, 3);
+    run(&mut backend, "A1=4");
+    let text = serde_json::to_string(backend.get_grid()).unwrap();
+    let old = format!("{{\"grid\":{},\"undo_stack\":[],\"redo_stack\":[]}}", text);
+    let loaded = Backend::deserial_text(old).unwrap();
+    assert_eq!(loaded.sheet_names(), vec!["Sheet1"]);
+    assert_eq!(value(&loaded, "A1"), Some(4));
+}
+
+#[test]
+fn test_copy_keeps_other_sheet_range() {
+    let mut backend = workbook();
+    run(&mut backend, "B1=SUM(Data!A1:A3)");
+    assert_eq!(run(&mut backend, "copy B1 to C2"), Status::Success);
+    assert_eq!(formula(&backend, 2, 3), Some("SUM(Data!A1:A3)".to_string()));
+    assert_eq!(value(&backend, "C2"), Some(6));
+}
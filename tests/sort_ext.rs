@@ -0,0 +1,107 @@
+use project::extension::backend::backend::*;
+use project::extension::common::{Operation, SortKey, Value};
+use project::extension::parser::parser;
+
+fn run(backend: &mut Backend, cmd: &str) -> Status {
+    let rows = backend.get_grid().get_row_size() - 1;
+    let columns = backend.get_grid().get_column_size() - 1;
+    backend.process_command(rows, columns, cmd.to_string())
+}
+
+fn column(
+    backend: &Backend,
+    col: usize,
+    rows: std::ops::RangeInclusive<usize>,
+) -> Vec<Option<isize>> {
+    rows.map(|row| backend.get_grid().get_node_value(row, col))
+        .collect()
+}
+
+#[test]
+fn test_validate_sort() {
+    assert_eq!(
+        parser::validate("sort A2:D100 by B desc, A asc", &4, &100),
+        Some((
+            None,
+            Some(Value::Oper(
+                Some(Box::new(Value::Cell(2, 1))),
+                Some(Box::new(Value::Cell(100, 4))),
+                Operation::Sort(vec![
+                    SortKey {
+                        col: 2,
+                        descending: true
+                    },
+                    SortKey {
+                        col: 1,
+                        descending: false
+                    },
+                ]),
+            ))
+        ))
+    );
+    assert!(parser::validate("sort A1:B5 by C", &4, &10).is_none());
+    assert!(parser::validate("sort A1:B5 by A up", &4, &10).is_none());
+    assert!(parser::validate("sort A1:B5", &4, &10).is_none());
+}
+
+#[test]
+fn test_sort_by_several_keys_is_stable() {
+    let mut backend = Backend::init_backend(6, 3);
+    for (row, (a, b, c)) in [(1, 2, 1), (2, 1, 2), (1, 1, 3), (2, 2, 4), (1, 2, 5)]
+        .iter()
+        .enumerate()
+    {
+        run(&mut backend, &format!("A{}={}", row + 1, a));
+        run(&mut backend, &format!("B{}={}", row + 1, b));
+        run(&mut backend, &format!("C{}={}", row + 1, c));
+    }
+    assert_eq!(
+        run(&mut backend, "sort A1:C5 by A desc, B"),
+        Status::Success
+    );
+    assert_eq!(
+        column(&backend, 3, 1..=5),
+        vec![Some(2), Some(4), Some(3), Some(1), Some(5)]
+    );
+
+    // one undo step restores the original order
+    run(&mut backend, "undo");
+    assert_eq!(
+        column(&backend, 3, 1..=5),
+        vec![Some(1), Some(2), Some(3), Some(4), Some(5)]
+    );
+}
+
+#[test]
+fn test_sort_puts_errors_last_and_moves_formulas() {
+    let mut backend = Backend::init_backend(5, 3);
+    run(&mut backend, "C1=0");
+    run(&mut backend, "A1=5");
+    run(&mut backend, "A2=1/C1");
+    run(&mut backend, "A3=9");
+    run(&mut backend, "A4=3");
+    run(&mut backend, "B1=A1*$C$5");
+    run(&mut backend, "B2=A2*$C$5");
+    run(&mut backend, "B3=A3*$C$5");
+    run(&mut backend, "B4=A4*$C$5");
+    run(&mut backend, "C5=10");
+    for descending in ["asc", "desc"] {
+        assert_eq!(
+            run(&mut backend, &format!("sort A1:B4 by A {}", descending)),
+            Status::Success
+        );
+        assert_eq!(backend.get_node_value(Value::Cell(4, 1)), None);
+    }
+    assert_eq!(column(&backend, 1, 1..=3), vec![Some(9), Some(5), Some(3)]);
+    // each formula moved with its row and still reads its own row
+    assert_eq!(
+        column(&backend, 2, 1..=3),
+        vec![Some(90), Some(50), Some(30)]
+    );
+    assert_eq!(
+        backend.get_grid().cells_vec[2][2].formula(),
+        Some("A2*$C$5".to_string())
+    );
+    run(&mut backend, "C5=1");
+    assert_eq!(backend.get_node_value(Value::Cell(1, 2)), Some(9));
+}
@@ -727,3 +727,55 @@ fn test_output_toggle() {
         assert!(!is_disabled);
     }
 }
+
+#[test]
+fn test_process_command_with_view() {
+    let rows = 30;
+    let cols = 30;
+    let mut grid = create_test_grid(rows, cols);
+    let mut start_x = 1;
+    let mut start_y = 1;
+    let mut is_disabled = true;
+    let mut view = View::default();
+
+    let mut run = |command: &str, start_x: &mut usize, start_y: &mut usize, view: &mut View| {
+        process_command_with_view(
+            command,
+            start_x,
+            start_y,
+            rows,
+            cols,
+            &mut is_disabled,
+            &mut grid,
+            view,
+        )
+    };
+
+    assert_eq!(
+        run("set view 5x3", &mut start_x, &mut start_y, &mut view),
+        1
+    );
+    assert_eq!((view.rows, view.columns), (5, 3));
+    assert_eq!(
+        run("set width B 20", &mut start_x, &mut start_y, &mut view),
+        1
+    );
+    assert_eq!(view.width(2), 20);
+    assert_eq!(
+        run("set width b 20", &mut start_x, &mut start_y, &mut view),
+        3
+    );
+    assert_eq!(
+        run("set view 0x3", &mut start_x, &mut start_y, &mut view),
+        3
+    );
+
+    // scrolling moves by the view size
+    run("s", &mut start_x, &mut start_y, &mut view);
+    run("d", &mut start_x, &mut start_y, &mut view);
+    assert_eq!((start_x, start_y), (6, 4));
+    run("a", &mut start_x, &mut start_y, &mut view);
+    assert_eq!(start_y, 1);
+
+    assert_eq!(fit_text("123456789", 5), "1234~");
+}
@@ -0,0 +1,162 @@
+use project::extension::backend::backend::*;
+use project::extension::backend::structure::*;
+use project::extension::common::{Operation, Value};
+use project::extension::parser::parser;
+
+fn run(backend: &mut Backend, cmd: &str) -> Status {
+    let rows = backend.get_grid().get_row_size() - 1;
+    let columns = backend.get_grid().get_column_size() - 1;
+    backend.process_command(rows, columns, cmd.to_string())
+}
+
+fn formula(backend: &mut Backend, row: usize, col: usize) -> String {
+    match &backend.grid.get_node(row, col).function {
+        Some(func) => func.to_string(),
+        None => String::new(),
+    }
+}
+
+#[test]
+fn test_validate_structure_commands() {
+    let none = |op| Some((None, Some(Value::Oper(None, None, op))));
+    assert_eq!(
+        parser::validate("insert_row 3", &5, &4),
+        none(Operation::InsertRow(3))
+    );
+    assert_eq!(
+        parser::validate("insert_row 5", &5, &4),
+        none(Operation::InsertRow(5))
+    );
+    assert_eq!(
+        parser::validate("delete_row 4", &5, &4),
+        none(Operation::DeleteRow(4))
+    );
+    assert_eq!(
+        parser::validate("insert_col F", &5, &4),
+        none(Operation::InsertCol(6))
+    );
+    assert_eq!(
+        parser::validate("delete_col AA", &30, &4),
+        none(Operation::DeleteCol(27))
+    );
+    assert!(parser::validate("delete_row 5", &5, &4).is_none());
+    assert!(parser::validate("delete_row 0", &5, &4).is_none());
+    assert!(parser::validate("delete_col F", &5, &4).is_none());
+    assert!(parser::validate("insert_col 3", &5, &4).is_none());
+}
+
+#[test]
+fn test_shift_function() {
+    let sum = Value::Oper(
+        Some(Box::new(Value::Cell(2, 1))),
+        Some(Box::new(Value::Cell(4, 1))),
+        Operation::Sum,
+    );
+    let shifted = shift_function(&sum, Axis::Row, 3, true);
+    assert_eq!(shifted.to_string(), "SUM(A2:A5)");
+    let shifted = shift_function(&sum, Axis::Row, 2, false);
+    assert_eq!(shifted.to_string(), "SUM(A2:A3)");
+    let single = Value::Oper(
+        Some(Box::new(Value::Cell(2, 1))),
+        Some(Box::new(Value::Cell(2, 1))),
+        Operation::Max,
+    );
+    assert_eq!(
+        shift_function(&single, Axis::Row, 2, false).to_string(),
+        "MAX(#REF!)"
+    );
+    let add = Value::Oper(
+        Some(Box::new(Value::Cell(1, 2))),
+        Some(Box::new(Value::Const(3))),
+        Operation::Add,
+    );
+    assert_eq!(
+        shift_function(&add, Axis::Column, 1, true).to_string(),
+        "C1+3"
+    );
+    assert_eq!(
+        shift_function(&add, Axis::Column, 2, false).to_string(),
+        "#REF!+3"
+    );
+}
+
+#[test]
+fn test_insert_row_moves_cells_and_references() {
+    let mut backend = Backend::init_backend(5, 3);
+    run(&mut backend, "A1=5");
+    run(&mut backend, "A2=7");
+    run(&mut backend, "B3=A1+A2");
+    run(&mut backend, "C1=SUM(A1:A2)");
+    assert_eq!(run(&mut backend, "insert_row 2"), Status::Success);
+
+    assert_eq!(backend.get_grid().get_row_size(), 7);
+    assert_eq!(backend.get_node_value(Value::Cell(3, 1)), Some(7));
+    assert_eq!(backend.get_node_value(Value::Cell(2, 1)), Some(0));
+    assert_eq!(formula(&mut backend, 4, 2), "A1+A3");
+    assert_eq!(formula(&mut backend, 1, 3), "SUM(A1:A3)");
+    assert_eq!(backend.get_node_value(Value::Cell(1, 3)), Some(12));
+
+    // the new row is part of the widened range and of the graph
+    run(&mut backend, "A2=100");
+    assert_eq!(backend.get_node_value(Value::Cell(1, 3)), Some(112));
+    run(&mut backend, "A3=1");
+    assert_eq!(backend.get_node_value(Value::Cell(4, 2)), Some(6));
+}
+
+#[test]
+fn test_delete_column_gives_ref_errors() {
+    let mut backend = Backend::init_backend(3, 4);
+    run(&mut backend, "A1=2");
+    run(&mut backend, "B1=3");
+    run(&mut backend, "C1=A1*B1");
+    run(&mut backend, "D1=C1+1");
+    assert_eq!(backend.get_node_value(Value::Cell(1, 4)), Some(7));
+    assert_eq!(run(&mut backend, "delete_col B"), Status::Success);
+
+    assert_eq!(backend.get_grid().get_column_size(), 4);
+    assert_eq!(formula(&mut backend, 1, 2), "A1*#REF!");
+    assert_eq!(formula(&mut backend, 1, 3), "B1+1");
+    assert_eq!(backend.get_node_value(Value::Cell(1, 2)), None);
+    assert_eq!(backend.get_node_value(Value::Cell(1, 3)), None);
+
+    // fixing the broken formula recovers its dependents
+    run(&mut backend, "B1=A1*5");
+    assert_eq!(backend.get_node_value(Value::Cell(1, 3)), Some(11));
+}
+
+#[test]
+fn test_restructure_is_one_undo_step() {
+    let mut backend = Backend::init_backend(4, 2);
+    run(&mut backend, "A3=9");
+    run(&mut backend, "B1=A3+1");
+    run(&mut backend, "delete_row 2");
+    assert_eq!(backend.get_grid().get_row_size(), 4);
+    assert_eq!(formula(&mut backend, 1, 2), "A2+1");
+
+    assert_eq!(run(&mut backend, "undo"), Status::Success);
+    assert_eq!(backend.get_grid().get_row_size(), 5);
+    assert_eq!(formula(&mut backend, 1, 2), "A3+1");
+    run(&mut backend, "A3=1");
+    assert_eq!(backend.get_node_value(Value::Cell(1, 2)), Some(2));
+
+    assert_eq!(run(&mut backend, "redo"), Status::UnrecognizedCmd);
+}
+
+#[test]
+fn test_restructure_limits() {
+    let mut backend = Backend::init_backend(1, 1);
+    assert!(matches!(
+        backend.restructure(Axis::Row, 1, false),
+        Status::Error(_)
+    ));
+    assert!(matches!(
+        backend.restructure(Axis::Column, 1, false),
+        Status::Error(_)
+    ));
+    assert_eq!(
+        backend.restructure(Axis::Row, 3, true),
+        Status::UnrecognizedCmd
+    );
+    assert_eq!(backend.restructure(Axis::Row, 2, true), Status::Success);
+    assert_eq!(backend.get_grid().get_row_size(), 3);
+}
@@ -0,0 +1,188 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use project::extension::common::Value;
+use project::extension::frontend::terminal::Frontend;
+use project::extension::frontend::tui::*;
+
+const PAGE: (usize, usize) = (5, 3);
+
+fn press(tui: &mut Tui, code: KeyCode) -> bool {
+    tui.handle_key(KeyEvent::new(code, KeyModifiers::NONE), PAGE)
+}
+
+fn type_text(tui: &mut Tui, text: &str) {
+    for c in text.chars() {
+        press(tui, KeyCode::Char(c));
+    }
+}
+
+fn tui(rows: usize, columns: usize) -> Tui {
+    Tui::new(Frontend::init_frontend(rows, columns, "").unwrap())
+}
+
+#[test]
+fn test_cursor_moves_inside_sheet() {
+    let mut tui = tui(20, 20);
+    press(&mut tui, KeyCode::Down);
+    press(&mut tui, KeyCode::Right);
+    assert_eq!(tui.cursor, Value::Cell(2, 2));
+    for _ in 0..3 {
+        press(&mut tui, KeyCode::Up);
+        press(&mut tui, KeyCode::Left);
+    }
+    assert_eq!(tui.cursor, Value::Cell(1, 1));
+    press(&mut tui, KeyCode::PageDown);
+    assert_eq!(tui.cursor, Value::Cell(6, 1));
+    for _ in 0..5 {
+        press(&mut tui, KeyCode::PageDown);
+    }
+    assert_eq!(tui.cursor, Value::Cell(20, 1));
+}
+
+#[test]
+fn test_edit_in_place() {
+    let mut tui = tui(5, 5);
+    press(&mut tui, KeyCode::Enter);
+    assert_eq!(tui.prompt, Some(Prompt::Edit(String::new())));
+    type_text(&mut tui, "42");
+    assert!(press(&mut tui, KeyCode::Enter));
+    assert_eq!(tui.prompt, None);
+    assert_eq!(tui.message, "ok");
+    // the cursor moves down after an edit, like in other spreadsheets
+    assert_eq!(tui.cursor, Value::Cell(2, 1));
+
+    // typing over a cell starts a fresh edit
+    type_text(&mut tui, "A1*2");
+    press(&mut tui, KeyCode::Enter);
+    assert_eq!(tui.frontend.cell_text(2, 1), "84");
+
+    // Enter on a formula cell edits its formula
+    press(&mut tui, KeyCode::Up);
+    press(&mut tui, KeyCode::Enter);
+    assert_eq!(tui.prompt, Some(Prompt::Edit("A1*2".to_string())));
+    press(&mut tui, KeyCode::Backspace);
+    type_text(&mut tui, "3");
+    press(&mut tui, KeyCode::Enter);
+    assert_eq!(tui.frontend.cell_text(2, 1), "126");
+}
+
+#[test]
+fn test_failed_edit_keeps_text() {
+    let mut tui = tui(5, 5);
+    type_text(&mut tui, "A1+");
+    press(&mut tui, KeyCode::Enter);
+    assert_eq!(tui.message, "missing operand after + at col 6");
+    assert_eq!(tui.prompt, Some(Prompt::Edit("A1+".to_string())));
+    assert_eq!(tui.cursor, Value::Cell(1, 1));
+    press(&mut tui, KeyCode::Esc);
+    assert_eq!(tui.prompt, None);
+    assert_eq!(tui.frontend.cell_text(1, 1), "");
+}
+
+#[test]
+fn test_commands_and_quit() {
+    let mut tui = tui(5, 5);
+    type_text(&mut tui, ":");
+    type_text(&mut tui, "scroll_to C4");
+    assert!(press(&mut tui, KeyCode::Enter));
+    assert_eq!(tui.cursor, Value::Cell(4, 3));
+    type_text(&mut tui, "7");
+    press(&mut tui, KeyCode::Enter);
+    type_text(&mut tui, ":find 7");
+    press(&mut tui, KeyCode::Enter);
+    assert_eq!(tui.message, "C4: 7");
+
+    press(&mut tui, KeyCode::Up);
+    press(&mut tui, KeyCode::Delete);
+    assert_eq!(tui.frontend.cell_text(4, 3), "");
+
+    type_text(&mut tui, ":q");
+    assert!(!press(&mut tui, KeyCode::Enter));
+    assert!(!press(&mut tui, KeyCode::Char('q')));
+}
+
+#[test]
+fn test_open_asks_before_losing_changes() {
+    let path = std::env::temp_dir().join(format!("tui_ext_open_{}.json", std::process::id()));
+    let path = path.to_str().unwrap();
+    let mut tui = tui(5, 5);
+    type_text(&mut tui, "5");
+    press(&mut tui, KeyCode::Enter);
+    type_text(&mut tui, &format!(":save {}", path));
+    press(&mut tui, KeyCode::Enter);
+    type_text(&mut tui, "6");
+    press(&mut tui, KeyCode::Enter);
+
+    type_text(&mut tui, &format!(":open {}", path));
+    press(&mut tui, KeyCode::Enter);
+    assert_eq!(tui.prompt, Some(Prompt::ConfirmOpen(path.to_string())));
+    assert_eq!(
+        tui.message,
+        "unsaved changes: y opens anyway, any other key cancels"
+    );
+    assert!(tui.screen(200, 8)[0].ends_with("anyway? (y/n) "));
+    press(&mut tui, KeyCode::Char('n'));
+    assert_eq!(tui.prompt, None);
+    assert_eq!(tui.message, "open cancelled");
+    assert_eq!(tui.frontend.cell_text(2, 1), "6");
+
+    type_text(&mut tui, &format!(":open {}", path));
+    press(&mut tui, KeyCode::Enter);
+    press(&mut tui, KeyCode::Char('y'));
+    assert_eq!(tui.prompt, None);
+    assert_eq!(tui.message, "ok");
+    assert_eq!(tui.frontend.cell_text(2, 1), "");
+    assert_eq!(tui.cursor, Value::Cell(1, 1));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_screen_layout() {
+    let mut tui = tui(20, 20);
+    type_text(&mut tui, "5");
+    press(&mut tui, KeyCode::Enter);
+    press(&mut tui, KeyCode::Up);
+    // 3 columns and 5 rows fit on a 48x8 screen
+    let lines = tui.screen(48, 8);
+    assert_eq!(lines.len(), 8);
+    assert_eq!(lines[0], "A1: 5");
+    assert_eq!(
+        lines[1].split_whitespace().collect::<Vec<_>>(),
+        ["A", "B", "C"]
+    );
+    assert_eq!(lines[2].split_whitespace().collect::<Vec<_>>(), ["1", "5"]);
+    assert_eq!(lines[6].trim(), "5");
+    assert_eq!(lines[7], "Sheet1 | ok");
+
+    // the view follows the cursor
+    for _ in 0..6 {
+        press(&mut tui, KeyCode::Down);
+    }
+    press(&mut tui, KeyCode::End);
+    let lines = tui.screen(48, 8);
+    assert_eq!(lines[0], "D7: ");
+    assert_eq!(
+        lines[1].split_whitespace().collect::<Vec<_>>(),
+        ["B", "C", "D"]
+    );
+    assert_eq!(lines[6].trim(), "7");
+}
+
+#[test]
+fn test_split_cursor() {
+    assert_eq!(split_cursor("1  5  6", 3, 2), Some(("1  ", "5 ", " 6")));
+    assert_eq!(split_cursor("1  5", 3, 4), Some(("1  ", "5", "")));
+    assert_eq!(split_cursor("1", 3, 2), None);
+
+    // currency cells hold characters wider than one byte
+    let mut tui = tui(5, 5);
+    for cmd in ["format A1:B1 currency €", "A1=1234", "B1=5"] {
+        tui.frontend.process_command(cmd);
+    }
+    press(&mut tui, KeyCode::Right);
+    let lines = tui.screen(48, 8);
+    let width = tui.frontend.view.default_width;
+    let (before, cell, after) = split_cursor(&lines[2], 2 * width, width).unwrap();
+    assert_eq!(cell.trim(), "€5");
+    assert!(before.ends_with("€1,234"));
+    assert_eq!(after.trim(), "");
+}
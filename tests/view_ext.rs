@@ -0,0 +1,105 @@
+use project::extension::backend::backend::Status;
+use project::extension::common::{Operation, Value, ViewChange};
+use project::extension::frontend::terminal::*;
+use project::extension::parser::parser;
+
+fn set(change: ViewChange) -> Option<(Option<Value>, Option<Value>)> {
+    Some((None, Some(Value::Oper(None, None, Operation::View(change)))))
+}
+
+#[test]
+fn test_validate_set() {
+    assert_eq!(
+        parser::validate("set view 25x8", &10, &10),
+        set(ViewChange::Size(25, 8))
+    );
+    assert_eq!(
+        parser::validate("set view auto", &10, &10),
+        set(ViewChange::Auto)
+    );
+    assert_eq!(
+        parser::validate("set width B 20", &10, &10),
+        set(ViewChange::Width(2, 20))
+    );
+    assert!(parser::validate("set view 0x8", &10, &10).is_none());
+    assert!(parser::validate("set view 25", &10, &10).is_none());
+    assert!(parser::validate("set width B 0", &10, &10).is_none());
+    assert!(parser::validate("set width K 5", &10, &10).is_none());
+}
+
+#[test]
+fn test_view_scroll_steps() {
+    let mut frontend = Frontend::init_frontend(50, 30, "").unwrap();
+    frontend.execute_status(&Status::View(ViewChange::Size(25, 8)));
+    assert_eq!(frontend.view.rows, 25);
+    assert_eq!(frontend.view.columns, 8);
+
+    frontend.execute_status(&Status::Down);
+    frontend.execute_status(&Status::Right);
+    assert_eq!(frontend.start, Value::Cell(26, 9));
+    // the last page stays full
+    frontend.execute_status(&Status::Down);
+    frontend.execute_status(&Status::Right);
+    frontend.execute_status(&Status::Right);
+    frontend.execute_status(&Status::Right);
+    assert_eq!(frontend.start, Value::Cell(26, 23));
+    frontend.execute_status(&Status::Up);
+    frontend.execute_status(&Status::Left);
+    assert_eq!(frontend.start, Value::Cell(1, 15));
+}
+
+#[test]
+fn test_column_widths() {
+    let mut view = View::default();
+    assert_eq!(view.width(2), 12);
+    view.apply(&ViewChange::Width(2, 20));
+    assert_eq!(view.width(2), 20);
+    assert_eq!(view.width(3), 12);
+    // labels, A and B take 44 characters
+    assert_eq!(view.columns_in(1, 50), 2);
+    assert_eq!(view.columns_in(1, 56), 3);
+    // a single column is always shown
+    assert_eq!(view.columns_in(2, 10), 1);
+
+    view.apply(&ViewChange::Auto);
+    assert!(view.auto);
+    view.apply(&ViewChange::Size(5, 5));
+    assert!(!view.auto);
+}
+
+#[test]
+fn test_fit_text_truncates() {
+    assert_eq!(fit_text("42", 5), "   42");
+    assert_eq!(fit_text("12345", 5), "12345");
+    assert_eq!(fit_text("123456789", 5), "1234~");
+    assert_eq!(fit_text("-7", 1), "~");
+}
+
+#[test]
+fn test_show_formulas() {
+    assert_eq!(
+        parser::validate("show_formulas on", &10, &10),
+        set(ViewChange::Formulas(true))
+    );
+    assert_eq!(
+        parser::validate("show_formulas off", &10, &10),
+        set(ViewChange::Formulas(false))
+    );
+    assert!(parser::validate("show_formulas", &10, &10).is_none());
+    assert!(parser::validate("show_formulas yes", &10, &10).is_none());
+
+    let mut frontend = Frontend::init_frontend(5, 5, "").unwrap();
+    for cmd in ["A1=3", "B1=$A$1+2", "C1=SUM(A1:B1)", "D1=A1/0"] {
+        frontend.backend.process_command(5, 5, cmd.to_string());
+    }
+    assert_eq!(frontend.cell_text(1, 2), "5");
+    assert_eq!(frontend.cell_text(1, 4), "ERR");
+    frontend.execute_status(&Status::View(ViewChange::Formulas(true)));
+    assert_eq!(frontend.cell_text(1, 1), "3");
+    assert_eq!(frontend.cell_text(1, 2), "$A$1+2");
+    assert_eq!(frontend.cell_text(1, 3), "SUM(A1:B1)");
+    assert_eq!(frontend.cell_text(1, 4), "A1/0");
+    assert_eq!(frontend.cell_text(2, 1), "");
+    frontend.execute_status(&Status::View(ViewChange::Formulas(false)));
+    assert_eq!(frontend.cell_text(1, 3), "8");
+}